@@ -30,6 +30,8 @@ fn main() {
     
     // 設置默認運行的映像（BIOS）
     println!("cargo:rustc-env=BIOS_DISK_IMAGE={}", bios_path.display());
+    // UEFI 映像，運行器使用 --uefi 時啟動
+    println!("cargo:rustc-env=UEFI_DISK_IMAGE={}", uefi_path.display());
 }
 
 fn out_dir() -> PathBuf {
@@ -0,0 +1,50 @@
+// kernel/build.rs
+// 把构建信息 (git 提交、构建配置、编译器版本) 注入为编译期环境变量，供 version 命令和启动横幅使用
+// 同时转发 KERNEL_FG / KERNEL_BG (启动时的默认文字颜色，例如 KERNEL_FG=yellow)
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+/// 获取不到信息时使用的值
+const UNKNOWN: &str = "unknown";
+
+fn main() {
+    let git_hash = command_output("git", &["rev-parse", "--short", "HEAD"]);
+    let profile = env::var("PROFILE").unwrap_or_else(|_| UNKNOWN.to_string());
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]);
+
+    println!("cargo:rustc-env=GIT_HASH={}", git_hash);
+    println!("cargo:rustc-env=BUILD_PROFILE={}", profile);
+    println!("cargo:rustc-env=RUSTC_VERSION={}", rustc_version);
+
+    // 未设置时导出空字符串，由内核退回白字黑底
+    for name in ["KERNEL_FG", "KERNEL_BG"] {
+        println!("cargo:rustc-env={}={}", name, env::var(name).unwrap_or_default());
+        println!("cargo:rerun-if-env-changed={}", name);
+    }
+
+    // 提交或切换分支后重新生成 git 哈希
+    let git_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../.git");
+    for file in ["HEAD", "index"] {
+        let path = git_dir.join(file);
+        if path.exists() {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
+
+/// 运行命令并返回去掉首尾空白的输出，命令不存在或失败 (例如不在 git 仓库里) 时返回 "unknown"
+fn command_output(program: &str, args: &[&str]) -> String {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+        .unwrap_or_else(|| UNKNOWN.to_string())
+}
@@ -0,0 +1,128 @@
+// kernel/src/alias.rs
+// 命令别名表 - 定长数组，无需堆分配
+
+/// 最多别名数量
+pub const MAX_ALIASES: usize = 16;
+/// 别名名称最大长度
+pub const MAX_NAME_LEN: usize = 16;
+/// 别名展开内容最大长度
+pub const MAX_VALUE_LEN: usize = 64;
+
+/// 别名操作错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AliasError {
+    /// 名称为空或包含非法字符
+    InvalidName,
+    /// 展开内容为空
+    EmptyValue,
+    /// 名称过长
+    NameTooLong,
+    /// 展开内容过长
+    ValueTooLong,
+    /// 别名表已满
+    TableFull,
+    /// 别名不存在
+    NotFound,
+}
+
+/// 单个别名
+#[derive(Clone, Copy)]
+struct Alias {
+    name: [u8; MAX_NAME_LEN],
+    name_len: usize,
+    value: [u8; MAX_VALUE_LEN],
+    value_len: usize,
+}
+
+impl Alias {
+    const EMPTY: Alias = Alias {
+        name: [0; MAX_NAME_LEN],
+        name_len: 0,
+        value: [0; MAX_VALUE_LEN],
+        value_len: 0,
+    };
+
+    fn is_used(&self) -> bool {
+        self.name_len > 0
+    }
+
+    fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len]).unwrap_or("")
+    }
+
+    fn value(&self) -> &str {
+        core::str::from_utf8(&self.value[..self.value_len]).unwrap_or("")
+    }
+}
+
+/// 别名表
+pub struct AliasTable {
+    entries: [Alias; MAX_ALIASES],
+}
+
+impl AliasTable {
+    pub const fn new() -> AliasTable {
+        AliasTable {
+            entries: [Alias::EMPTY; MAX_ALIASES],
+        }
+    }
+
+    /// 定义或更新别名
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), AliasError> {
+        if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '=') {
+            return Err(AliasError::InvalidName);
+        }
+        if value.is_empty() {
+            return Err(AliasError::EmptyValue);
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(AliasError::NameTooLong);
+        }
+        if value.len() > MAX_VALUE_LEN {
+            return Err(AliasError::ValueTooLong);
+        }
+
+        // 已存在则覆盖，否则使用第一个空槽位
+        let slot = match self.position(name) {
+            Some(index) => index,
+            None => self
+                .entries
+                .iter()
+                .position(|entry| !entry.is_used())
+                .ok_or(AliasError::TableFull)?,
+        };
+
+        let entry = &mut self.entries[slot];
+        entry.name[..name.len()].copy_from_slice(name.as_bytes());
+        entry.name_len = name.len();
+        entry.value[..value.len()].copy_from_slice(value.as_bytes());
+        entry.value_len = value.len();
+        Ok(())
+    }
+
+    /// 删除别名
+    pub fn remove(&mut self, name: &str) -> Result<(), AliasError> {
+        let index = self.position(name).ok_or(AliasError::NotFound)?;
+        self.entries[index] = Alias::EMPTY;
+        Ok(())
+    }
+
+    /// 查找别名的展开内容
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.position(name).map(|index| self.entries[index].value())
+    }
+
+    /// 遍历所有别名 (名称, 展开内容)
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_used())
+            .map(|entry| (entry.name(), entry.value()))
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.is_used() && entry.name() == name)
+    }
+}
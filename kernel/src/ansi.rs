@@ -0,0 +1,253 @@
+// kernel/src/ansi.rs
+// ANSI 转义序列解析 - 逐个字符输入，识别 ESC [ 参数 结束字符 形式的 CSI 序列
+
+/// CSI 序列最多记录的参数个数，更多的参数被忽略
+pub const MAX_PARAMS: usize = 8;
+
+/// 转义序列的起始字符
+const ESC: char = '\x1b';
+
+/// 文字属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attributes {
+    /// 粗体: 笔画向右加粗一个像素
+    pub bold: bool,
+    /// 暗淡: 前景色向背景色靠拢
+    pub dim: bool,
+    /// 下划线: 画在字形的最后一行
+    pub underline: bool,
+    /// 删除线: 画在字形的中间一行
+    pub strikethrough: bool,
+}
+
+impl Attributes {
+    pub const NONE: Attributes = Attributes { bold: false, dim: false, underline: false, strikethrough: false };
+}
+
+/// 解析一个字符的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// 普通字符，照常输出
+    Print(char),
+    /// 完整的 CSI 序列；省略的参数为 None
+    Csi {
+        params: [Option<u16>; MAX_PARAMS],
+        count: usize,
+        final_char: char,
+    },
+    /// 字符属于尚未结束 (或被丢弃) 的转义序列
+    Consumed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    Csi,
+}
+
+/// 转义序列解析器，序列可以分多次输入
+pub struct AnsiParser {
+    state: State,
+    params: [Option<u16>; MAX_PARAMS],
+    count: usize,
+}
+
+impl AnsiParser {
+    pub const fn new() -> AnsiParser {
+        AnsiParser {
+            state: State::Ground,
+            params: [None; MAX_PARAMS],
+            count: 0,
+        }
+    }
+
+    /// 输入一个字符
+    pub fn feed(&mut self, ch: char) -> Action {
+        match self.state {
+            State::Ground if ch == ESC => {
+                self.state = State::Escape;
+                Action::Consumed
+            },
+            State::Ground => Action::Print(ch),
+            State::Escape => {
+                // 只支持 CSI，其它 ESC 序列连同下一个字符一起丢弃
+                if ch == '[' {
+                    self.state = State::Csi;
+                    self.params = [None; MAX_PARAMS];
+                    self.count = 0;
+                } else {
+                    self.state = State::Ground;
+                }
+                Action::Consumed
+            },
+            State::Csi => self.feed_csi(ch),
+        }
+    }
+
+    fn feed_csi(&mut self, ch: char) -> Action {
+        match ch {
+            '0'..='9' => {
+                let digit = ch as u16 - '0' as u16;
+                if self.count == 0 {
+                    self.count = 1;
+                }
+                if let Some(param) = self.params.get_mut(self.count - 1) {
+                    *param = Some(param.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                }
+                Action::Consumed
+            },
+            ';' => {
+                // "ESC [ ; 5 m" 的第一个参数被省略
+                self.count = (self.count.max(1) + 1).min(MAX_PARAMS + 1);
+                Action::Consumed
+            },
+            '\x40'..='\x7e' => {
+                self.state = State::Ground;
+                Action::Csi {
+                    params: self.params,
+                    count: self.count.min(MAX_PARAMS),
+                    final_char: ch,
+                }
+            },
+            // 私有前缀 ('?' 等) 和中间字符: 忽略
+            '\x20'..='\x3f' => Action::Consumed,
+            // 序列中出现其它字符: 放弃整个序列
+            _ => {
+                self.state = State::Ground;
+                Action::Consumed
+            },
+        }
+    }
+}
+
+/// 光标控制序列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorCommand {
+    /// ESC [ 行 ; 列 H: 移到绝对位置 (这里已换算为从 0 开始)
+    Position { row: usize, column: usize },
+    /// ESC [ n A
+    Up(usize),
+    /// ESC [ n B
+    Down(usize),
+    /// ESC [ n C
+    Forward(usize),
+    /// ESC [ n D
+    Back(usize),
+    /// ESC [ 2 J: 清屏
+    ClearScreen,
+}
+
+/// 把 CSI 序列解释为光标控制序列，不支持的序列返回 None
+/// 省略的参数和 0 都按 1 处理 (行列从 1 开始)
+pub fn cursor_command(params: &[Option<u16>], final_char: char) -> Option<CursorCommand> {
+    let param = |index: usize| params.get(index).copied().flatten().unwrap_or(1).max(1) as usize;
+    match final_char {
+        'H' | 'f' => Some(CursorCommand::Position { row: param(0) - 1, column: param(1) - 1 }),
+        'A' => Some(CursorCommand::Up(param(0))),
+        'B' => Some(CursorCommand::Down(param(0))),
+        'C' => Some(CursorCommand::Forward(param(0))),
+        'D' => Some(CursorCommand::Back(param(0))),
+        'J' if params.first().copied().flatten() == Some(2) => Some(CursorCommand::ClearScreen),
+        _ => None,
+    }
+}
+
+/// 按 SGR (ESC [ ... m) 参数更新文字属性；没有参数等同于 0 (全部重置)
+/// 不认识的参数 (例如颜色) 被忽略
+pub fn apply_sgr(attributes: &mut Attributes, params: &[Option<u16>]) {
+    if params.is_empty() {
+        *attributes = Attributes::NONE;
+        return;
+    }
+    for param in params {
+        match param.unwrap_or(0) {
+            0 => *attributes = Attributes::NONE,
+            1 => attributes.bold = true,
+            2 => attributes.dim = true,
+            4 => attributes.underline = true,
+            9 => attributes.strikethrough = true,
+            22 => {
+                attributes.bold = false;
+                attributes.dim = false;
+            },
+            24 => attributes.underline = false,
+            29 => attributes.strikethrough = false,
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_all(parser: &mut AnsiParser, text: &str) -> Option<Action> {
+        let mut last = None;
+        for ch in text.chars() {
+            last = Some(parser.feed(ch));
+        }
+        last
+    }
+
+    fn csi(params: &[Option<u16>], final_char: char) -> Action {
+        let mut all = [None; MAX_PARAMS];
+        all[..params.len()].copy_from_slice(params);
+        Action::Csi { params: all, count: params.len(), final_char }
+    }
+
+    #[test]
+    fn plain_text_passes_through() {
+        let mut parser = AnsiParser::new();
+        assert_eq!(parser.feed('a'), Action::Print('a'));
+        assert_eq!(parser.feed('\n'), Action::Print('\n'));
+    }
+
+    #[test]
+    fn parses_csi_parameters() {
+        let mut parser = AnsiParser::new();
+        assert_eq!(feed_all(&mut parser, "\x1b[1;4m"), Some(csi(&[Some(1), Some(4)], 'm')));
+        assert_eq!(feed_all(&mut parser, "\x1b[m"), Some(csi(&[], 'm')));
+        assert_eq!(feed_all(&mut parser, "\x1b[;5H"), Some(csi(&[None, Some(5)], 'H')));
+        assert_eq!(parser.feed('x'), Action::Print('x'));
+    }
+
+    #[test]
+    fn sequences_may_arrive_in_pieces_and_unknown_escapes_are_dropped() {
+        let mut parser = AnsiParser::new();
+        assert_eq!(feed_all(&mut parser, "\x1b["), Some(Action::Consumed));
+        assert_eq!(feed_all(&mut parser, "2m"), Some(csi(&[Some(2)], 'm')));
+        assert_eq!(feed_all(&mut parser, "\x1bc"), Some(Action::Consumed));
+        assert_eq!(parser.feed('y'), Action::Print('y'));
+    }
+
+    #[test]
+    fn cursor_commands_default_to_one() {
+        assert_eq!(
+            cursor_command(&[Some(5), Some(10)], 'H'),
+            Some(CursorCommand::Position { row: 4, column: 9 })
+        );
+        assert_eq!(cursor_command(&[], 'H'), Some(CursorCommand::Position { row: 0, column: 0 }));
+        assert_eq!(cursor_command(&[None, Some(3)], 'H'), Some(CursorCommand::Position { row: 0, column: 2 }));
+        assert_eq!(cursor_command(&[], 'A'), Some(CursorCommand::Up(1)));
+        assert_eq!(cursor_command(&[Some(0)], 'B'), Some(CursorCommand::Down(1)));
+        assert_eq!(cursor_command(&[Some(4)], 'C'), Some(CursorCommand::Forward(4)));
+        assert_eq!(cursor_command(&[Some(2)], 'D'), Some(CursorCommand::Back(2)));
+        assert_eq!(cursor_command(&[Some(2)], 'J'), Some(CursorCommand::ClearScreen));
+        assert_eq!(cursor_command(&[], 'J'), None);
+        assert_eq!(cursor_command(&[Some(1)], 'K'), None);
+    }
+
+    #[test]
+    fn sgr_sets_and_clears_attributes() {
+        let mut attributes = Attributes::NONE;
+        apply_sgr(&mut attributes, &[Some(1), Some(2), Some(4), Some(9)]);
+        assert_eq!(attributes, Attributes { bold: true, dim: true, underline: true, strikethrough: true });
+        apply_sgr(&mut attributes, &[Some(22), Some(29)]);
+        assert_eq!(attributes, Attributes { underline: true, ..Attributes::NONE });
+        apply_sgr(&mut attributes, &[Some(31)]);
+        assert!(attributes.underline);
+        apply_sgr(&mut attributes, &[]);
+        assert_eq!(attributes, Attributes::NONE);
+    }
+}
@@ -0,0 +1,224 @@
+// kernel/src/ata.rs
+// ATA PIO 读扇区 - 主通道 (0x1F0-0x1F7)，轮询方式，28 位 LBA，不使用 IRQ14
+// QEMU 中启动盘是主通道的主盘，运行器附加的数据盘是从盘
+
+use x86_64::instructions::port::{Port, PortReadOnly};
+
+/// 扇区大小 (字节)
+pub const SECTOR_SIZE: usize = 512;
+/// 28 位 LBA 能寻址的最大扇区号
+pub const MAX_LBA: u32 = (1 << 28) - 1;
+
+/// 主通道命令块寄存器的起始端口
+const PRIMARY_IO: u16 = 0x1F0;
+/// 主通道控制寄存器 (写) / 备用状态寄存器 (读)
+const PRIMARY_CONTROL: u16 = 0x3F6;
+
+/// 命令块寄存器相对 PRIMARY_IO 的偏移
+const REG_DATA: u16 = 0;
+const REG_ERROR: u16 = 1;
+const REG_SECTOR_COUNT: u16 = 2;
+const REG_LBA_LOW: u16 = 3;
+const REG_LBA_MID: u16 = 4;
+const REG_LBA_HIGH: u16 = 5;
+const REG_DRIVE_HEAD: u16 = 6;
+const REG_STATUS_COMMAND: u16 = 7;
+
+/// 状态位
+const STATUS_ERR: u8 = 0x01;
+const STATUS_DRQ: u8 = 0x08;
+const STATUS_DF: u8 = 0x20;
+const STATUS_BSY: u8 = 0x80;
+/// 没有设备时总线悬空，读到全 1
+const FLOATING_BUS: u8 = 0xFF;
+
+/// 驱动器/磁头寄存器: LBA 模式 (0xE0)，从盘再置位 0x10
+const DRIVE_HEAD_LBA: u8 = 0xE0;
+const DRIVE_HEAD_SLAVE: u8 = 0x10;
+/// 控制寄存器: 关闭设备中断 (nIEN)，改用轮询
+const CONTROL_NO_INTERRUPT: u8 = 0x02;
+/// 命令: 读扇区 (PIO，28 位 LBA)
+const CMD_READ_SECTORS: u8 = 0x20;
+
+/// 等待状态位变化的轮询次数上限
+const POLL_LIMIT: u32 = 1_000_000;
+
+/// 主通道上的驱动器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Drive {
+    Master,
+    Slave,
+}
+
+impl Drive {
+    pub fn name(self) -> &'static str {
+        match self {
+            Drive::Master => "primary master",
+            Drive::Slave => "primary slave",
+        }
+    }
+}
+
+/// 读扇区时的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtaError {
+    /// 总线上没有这个驱动器
+    NoDrive,
+    /// 驱动器一直忙或一直没有准备好数据
+    Timeout,
+    /// 驱动器报告错误 (附带错误寄存器)
+    DeviceError(u8),
+    /// 驱动器故障
+    DriveFault,
+    /// 扇区号超出 28 位 LBA
+    LbaOutOfRange,
+}
+
+impl AtaError {
+    /// 错误的简短说明
+    pub fn describe(&self) -> &'static str {
+        match self {
+            AtaError::NoDrive => "no drive attached",
+            AtaError::Timeout => "drive timed out",
+            AtaError::DeviceError(_) => "drive reported an error",
+            AtaError::DriveFault => "drive fault",
+            AtaError::LbaOutOfRange => "LBA out of range for 28-bit addressing",
+        }
+    }
+}
+
+/// 驱动器/磁头寄存器的值: LBA 模式、驱动器选择和 LBA 的最高 4 位
+fn drive_head(drive: Drive, lba: u32) -> u8 {
+    let slave = if drive == Drive::Slave { DRIVE_HEAD_SLAVE } else { 0 };
+    DRIVE_HEAD_LBA | slave | ((lba >> 24) & 0x0F) as u8
+}
+
+/// 按状态寄存器判断数据是否就绪: Ok(true) 就绪，Ok(false) 仍在等待
+fn check_status(status: u8) -> Result<bool, AtaError> {
+    if status & STATUS_BSY != 0 {
+        Ok(false)
+    } else if status & STATUS_DF != 0 {
+        Err(AtaError::DriveFault)
+    } else if status & STATUS_ERR != 0 {
+        // 错误寄存器由调用方读取后填入
+        Err(AtaError::DeviceError(0))
+    } else {
+        Ok(status & STATUS_DRQ != 0)
+    }
+}
+
+/// 主通道
+struct Channel {
+    io_base: u16,
+    control: Port<u8>,
+    alt_status: PortReadOnly<u8>,
+}
+
+impl Channel {
+    const fn primary() -> Channel {
+        Channel {
+            io_base: PRIMARY_IO,
+            control: Port::new(PRIMARY_CONTROL),
+            alt_status: PortReadOnly::new(PRIMARY_CONTROL),
+        }
+    }
+
+    fn read_register(&self, register: u16) -> u8 {
+        unsafe { PortReadOnly::new(self.io_base + register).read() }
+    }
+
+    fn write_register(&self, register: u16, value: u8) {
+        unsafe { Port::new(self.io_base + register).write(value) }
+    }
+
+    fn status(&self) -> u8 {
+        self.read_register(REG_STATUS_COMMAND)
+    }
+
+    /// 选择驱动器或发出命令后等待约 400ns 再看状态:
+    /// 读 4 次备用状态寄存器 (每次约 100ns)，不会清除中断状态
+    fn delay_400ns(&mut self) {
+        for _ in 0..4 {
+            unsafe { self.alt_status.read() };
+        }
+    }
+
+    fn wait_not_busy(&self) -> Result<(), AtaError> {
+        for _ in 0..POLL_LIMIT {
+            if self.status() & STATUS_BSY == 0 {
+                return Ok(());
+            }
+            core::hint::spin_loop();
+        }
+        Err(AtaError::Timeout)
+    }
+
+    /// 等待 BSY 清除且 DRQ 置位 (数据就绪)
+    fn wait_data(&self) -> Result<(), AtaError> {
+        for _ in 0..POLL_LIMIT {
+            match check_status(self.status()) {
+                Ok(true) => return Ok(()),
+                Ok(false) => core::hint::spin_loop(),
+                Err(AtaError::DeviceError(_)) => {
+                    return Err(AtaError::DeviceError(self.read_register(REG_ERROR)));
+                },
+                Err(error) => return Err(error),
+            }
+        }
+        Err(AtaError::Timeout)
+    }
+}
+
+/// 用 PIO 从主通道的驱动器读取一个扇区
+pub fn read_sector(drive: Drive, lba: u32, buf: &mut [u8; SECTOR_SIZE]) -> Result<(), AtaError> {
+    if lba > MAX_LBA {
+        return Err(AtaError::LbaOutOfRange);
+    }
+    let mut channel = Channel::primary();
+    unsafe { channel.control.write(CONTROL_NO_INTERRUPT) };
+
+    channel.write_register(REG_DRIVE_HEAD, drive_head(drive, lba));
+    channel.delay_400ns();
+    let status = channel.status();
+    if status == FLOATING_BUS || status == 0 {
+        return Err(AtaError::NoDrive);
+    }
+    channel.wait_not_busy()?;
+
+    channel.write_register(REG_SECTOR_COUNT, 1);
+    channel.write_register(REG_LBA_LOW, lba as u8);
+    channel.write_register(REG_LBA_MID, (lba >> 8) as u8);
+    channel.write_register(REG_LBA_HIGH, (lba >> 16) as u8);
+    channel.write_register(REG_STATUS_COMMAND, CMD_READ_SECTORS);
+    channel.delay_400ns();
+    channel.wait_data()?;
+
+    // 数据寄存器是 16 位的，每次读两个字节 (小端)
+    let mut data: Port<u16> = Port::new(channel.io_base + REG_DATA);
+    for word in buf.chunks_exact_mut(2) {
+        word.copy_from_slice(&unsafe { data.read() }.to_le_bytes());
+    }
+    channel.delay_400ns();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drive_head_selects_drive_and_high_lba_bits() {
+        assert_eq!(drive_head(Drive::Master, 0), 0xE0);
+        assert_eq!(drive_head(Drive::Slave, 0), 0xF0);
+        assert_eq!(drive_head(Drive::Slave, 0x0ABC_DEF0), 0xFA);
+    }
+
+    #[test]
+    fn status_waits_for_busy_then_data() {
+        assert_eq!(check_status(STATUS_BSY | STATUS_DRQ), Ok(false));
+        assert_eq!(check_status(0x40), Ok(false));
+        assert_eq!(check_status(0x40 | STATUS_DRQ), Ok(true));
+        assert_eq!(check_status(STATUS_ERR), Err(AtaError::DeviceError(0)));
+        assert_eq!(check_status(STATUS_DF | STATUS_ERR), Err(AtaError::DriveFault));
+    }
+}
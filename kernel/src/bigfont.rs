@@ -0,0 +1,91 @@
+// kernel/src/bigfont.rs
+// 大字体 - banner 命令用 5x5 的方块拼出大号字母 (与屏幕字体无关)
+
+/// 字形宽度 (列)
+pub const GLYPH_WIDTH: usize = 5;
+/// 字形高度 (行)
+pub const GLYPH_HEIGHT: usize = 5;
+/// 相邻字形之间的空列数
+pub const GLYPH_SPACING: usize = 1;
+/// 组成笔画的字符
+pub const BLOCK: char = '#';
+
+/// A-Z、0-9 的字形，每行 5 位，最高位 (0x10) 是最左边一列
+const GLYPHS: [[u8; GLYPH_HEIGHT]; 36] = [
+    [0x0E, 0x11, 0x1F, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x1E, 0x11, 0x1E], // B
+    [0x0F, 0x10, 0x10, 0x10, 0x0F], // C
+    [0x1E, 0x11, 0x11, 0x11, 0x1E], // D
+    [0x1F, 0x10, 0x1E, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x1E, 0x10, 0x10], // F
+    [0x0F, 0x10, 0x17, 0x11, 0x0E], // G
+    [0x11, 0x11, 0x1F, 0x11, 0x11], // H
+    [0x1F, 0x04, 0x04, 0x04, 0x1F], // I
+    [0x07, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x1C, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x11, 0x11], // M
+    [0x11, 0x19, 0x15, 0x13, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x1E, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x1E, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x0E, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x15, 0x1B, 0x11], // W
+    [0x11, 0x0A, 0x04, 0x0A, 0x11], // X
+    [0x11, 0x0A, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x02, 0x04, 0x08, 0x1F], // Z
+    [0x0E, 0x13, 0x15, 0x19, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x0E], // 1
+    [0x1E, 0x01, 0x0E, 0x10, 0x1F], // 2
+    [0x1E, 0x01, 0x0E, 0x01, 0x1E], // 3
+    [0x11, 0x11, 0x1F, 0x01, 0x01], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x1E], // 5
+    [0x0E, 0x10, 0x1E, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x04], // 7
+    [0x0E, 0x11, 0x0E, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x0F, 0x01, 0x0E], // 9
+];
+
+/// 字符的字形，小写字母按大写处理，不支持的字符是空白
+pub fn glyph(ch: char) -> [u8; GLYPH_HEIGHT] {
+    let ch = ch.to_ascii_uppercase();
+    match ch {
+        'A'..='Z' => GLYPHS[ch as usize - 'A' as usize],
+        '0'..='9' => GLYPHS[26 + ch as usize - '0' as usize],
+        _ => [0; GLYPH_HEIGHT],
+    }
+}
+
+/// 把字形的一行展开为 GLYPH_WIDTH 个字符 (笔画为 BLOCK，其余为空格)
+pub fn row_chars(glyph: &[u8; GLYPH_HEIGHT], row: usize) -> [char; GLYPH_WIDTH] {
+    let bits = glyph[row];
+    core::array::from_fn(|column| {
+        if bits & (0x10 >> column) != 0 { BLOCK } else { ' ' }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letters_ignore_case_and_unknown_chars_are_blank() {
+        assert_eq!(glyph('a'), glyph('A'));
+        assert_eq!(glyph('?'), [0; GLYPH_HEIGHT]);
+        assert_eq!(glyph(' '), [0; GLYPH_HEIGHT]);
+        assert_eq!(glyph('0'), GLYPHS[26]);
+        assert_eq!(glyph('9'), GLYPHS[35]);
+    }
+
+    #[test]
+    fn rows_expand_to_blocks() {
+        let h = glyph('H');
+        assert_eq!(row_chars(&h, 0), ['#', ' ', ' ', ' ', '#']);
+        assert_eq!(row_chars(&h, 2), ['#'; 5]);
+        assert_eq!(row_chars(&glyph('-'), 2), [' '; 5]);
+    }
+}
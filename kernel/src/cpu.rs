@@ -0,0 +1,219 @@
+// kernel/src/cpu.rs
+// CPU 信息检测 (CPUID 指令) 和时间戳计数器
+
+use core::arch::x86_64::{CpuidResult, __cpuid, _rdtsc};
+
+/// 扩展功能叶的起始编号
+const EXTENDED_LEAF_BASE: u32 = 0x8000_0000;
+/// 品牌字符串所在的扩展叶 (0x80000002 - 0x80000004)
+const BRAND_LEAVES: [u32; 3] = [0x8000_0002, 0x8000_0003, 0x8000_0004];
+
+/// 功能位所在的寄存器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeatureRegister {
+    /// 叶 1 的 EDX
+    Leaf1Edx,
+    /// 叶 1 的 ECX
+    Leaf1Ecx,
+    /// 叶 7 子叶 0 的 EBX
+    Leaf7Ebx,
+    /// 扩展叶 0x80000001 的 EDX
+    ExtendedEdx,
+}
+
+/// 功能位描述
+struct Feature {
+    name: &'static str,
+    register: FeatureRegister,
+    bit: u32,
+}
+
+/// 需要检测和显示的功能位
+const FEATURES: &[Feature] = &[
+    Feature { name: "FPU", register: FeatureRegister::Leaf1Edx, bit: 0 },
+    Feature { name: "TSC", register: FeatureRegister::Leaf1Edx, bit: 4 },
+    Feature { name: "MSR", register: FeatureRegister::Leaf1Edx, bit: 5 },
+    Feature { name: "PAE", register: FeatureRegister::Leaf1Edx, bit: 6 },
+    Feature { name: "APIC", register: FeatureRegister::Leaf1Edx, bit: 9 },
+    Feature { name: "MTRR", register: FeatureRegister::Leaf1Edx, bit: 12 },
+    Feature { name: "PGE", register: FeatureRegister::Leaf1Edx, bit: 13 },
+    Feature { name: "CMOV", register: FeatureRegister::Leaf1Edx, bit: 15 },
+    Feature { name: "MMX", register: FeatureRegister::Leaf1Edx, bit: 23 },
+    Feature { name: "FXSR", register: FeatureRegister::Leaf1Edx, bit: 24 },
+    Feature { name: "SSE", register: FeatureRegister::Leaf1Edx, bit: 25 },
+    Feature { name: "SSE2", register: FeatureRegister::Leaf1Edx, bit: 26 },
+    Feature { name: "HTT", register: FeatureRegister::Leaf1Edx, bit: 28 },
+    Feature { name: "SSE3", register: FeatureRegister::Leaf1Ecx, bit: 0 },
+    Feature { name: "PCLMUL", register: FeatureRegister::Leaf1Ecx, bit: 1 },
+    Feature { name: "SSSE3", register: FeatureRegister::Leaf1Ecx, bit: 9 },
+    Feature { name: "FMA", register: FeatureRegister::Leaf1Ecx, bit: 12 },
+    Feature { name: "CX16", register: FeatureRegister::Leaf1Ecx, bit: 13 },
+    Feature { name: "SSE4.1", register: FeatureRegister::Leaf1Ecx, bit: 19 },
+    Feature { name: "SSE4.2", register: FeatureRegister::Leaf1Ecx, bit: 20 },
+    Feature { name: "x2APIC", register: FeatureRegister::Leaf1Ecx, bit: 21 },
+    Feature { name: "POPCNT", register: FeatureRegister::Leaf1Ecx, bit: 23 },
+    Feature { name: "AES", register: FeatureRegister::Leaf1Ecx, bit: 25 },
+    Feature { name: "XSAVE", register: FeatureRegister::Leaf1Ecx, bit: 26 },
+    Feature { name: "AVX", register: FeatureRegister::Leaf1Ecx, bit: 28 },
+    Feature { name: "F16C", register: FeatureRegister::Leaf1Ecx, bit: 29 },
+    Feature { name: "RDRAND", register: FeatureRegister::Leaf1Ecx, bit: 30 },
+    Feature { name: "HYPERVISOR", register: FeatureRegister::Leaf1Ecx, bit: 31 },
+    Feature { name: "BMI1", register: FeatureRegister::Leaf7Ebx, bit: 3 },
+    Feature { name: "AVX2", register: FeatureRegister::Leaf7Ebx, bit: 5 },
+    Feature { name: "BMI2", register: FeatureRegister::Leaf7Ebx, bit: 8 },
+    Feature { name: "AVX512F", register: FeatureRegister::Leaf7Ebx, bit: 16 },
+    Feature { name: "RDSEED", register: FeatureRegister::Leaf7Ebx, bit: 18 },
+    Feature { name: "SHA", register: FeatureRegister::Leaf7Ebx, bit: 29 },
+    Feature { name: "NX", register: FeatureRegister::ExtendedEdx, bit: 20 },
+    Feature { name: "PDPE1GB", register: FeatureRegister::ExtendedEdx, bit: 26 },
+    Feature { name: "RDTSCP", register: FeatureRegister::ExtendedEdx, bit: 27 },
+    Feature { name: "LM", register: FeatureRegister::ExtendedEdx, bit: 29 },
+];
+
+/// CPUID 检测结果
+#[derive(Debug, Clone, Copy)]
+pub struct CpuInfo {
+    vendor: [u8; 12],
+    brand: [u8; 48],
+    has_brand: bool,
+    max_leaf: u32,
+    max_extended_leaf: u32,
+    signature: u32,
+    leaf1_ecx: u32,
+    leaf1_edx: u32,
+    leaf7_ebx: u32,
+    extended_edx: u32,
+}
+
+impl CpuInfo {
+    /// 厂商字符串 (例如 "GenuineIntel")
+    pub fn vendor(&self) -> &str {
+        core::str::from_utf8(&self.vendor).unwrap_or("Unknown")
+    }
+
+    /// 品牌字符串，扩展叶不可用时返回 None
+    pub fn brand(&self) -> Option<&str> {
+        if !self.has_brand {
+            return None;
+        }
+        let end = self.brand.iter().position(|&b| b == 0).unwrap_or(self.brand.len());
+        core::str::from_utf8(&self.brand[..end])
+            .ok()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+    }
+
+    /// 最大标准叶
+    pub fn max_leaf(&self) -> u32 {
+        self.max_leaf
+    }
+
+    /// 最大扩展叶，不支持时为 0
+    pub fn max_extended_leaf(&self) -> u32 {
+        self.max_extended_leaf
+    }
+
+    /// 获取 (family, model, stepping)
+    pub fn family_model_stepping(&self) -> (u32, u32, u32) {
+        let stepping = self.signature & 0xF;
+        let base_model = (self.signature >> 4) & 0xF;
+        let base_family = (self.signature >> 8) & 0xF;
+        let extended_model = (self.signature >> 16) & 0xF;
+        let extended_family = (self.signature >> 20) & 0xFF;
+
+        let family = if base_family == 0xF {
+            base_family + extended_family
+        } else {
+            base_family
+        };
+        let model = if base_family == 0x6 || base_family == 0xF {
+            (extended_model << 4) | base_model
+        } else {
+            base_model
+        };
+        (family, model, stepping)
+    }
+
+    /// 遍历所有支持的功能名称
+    pub fn features(&self) -> impl Iterator<Item = &'static str> + '_ {
+        FEATURES
+            .iter()
+            .filter(move |feature| self.register(feature.register) & (1 << feature.bit) != 0)
+            .map(|feature| feature.name)
+    }
+
+    fn register(&self, register: FeatureRegister) -> u32 {
+        match register {
+            FeatureRegister::Leaf1Edx => self.leaf1_edx,
+            FeatureRegister::Leaf1Ecx => self.leaf1_ecx,
+            FeatureRegister::Leaf7Ebx => self.leaf7_ebx,
+            FeatureRegister::ExtendedEdx => self.extended_edx,
+        }
+    }
+}
+
+/// 执行 CPUID
+#[allow(unused_unsafe)] // 较新的工具链已将 __cpuid 标记为安全函数
+fn cpuid(leaf: u32) -> CpuidResult {
+    // x86_64 上 CPUID 总是可用
+    unsafe { __cpuid(leaf) }
+}
+
+/// 读取时间戳计数器 (TSC)
+/// 多核时各核的 TSC 不一定同步，跨核比较读数没有意义；目前只运行在单核上
+#[allow(unused_unsafe)]
+pub fn rdtsc() -> u64 {
+    unsafe { _rdtsc() }
+}
+
+/// 检测 CPU 信息
+pub fn detect() -> CpuInfo {
+    // 叶 0: 最大标准叶 + 厂商字符串 (EBX, EDX, ECX 顺序)
+    let leaf0 = cpuid(0);
+    let mut vendor = [0u8; 12];
+    vendor[0..4].copy_from_slice(&leaf0.ebx.to_le_bytes());
+    vendor[4..8].copy_from_slice(&leaf0.edx.to_le_bytes());
+    vendor[8..12].copy_from_slice(&leaf0.ecx.to_le_bytes());
+
+    let max_leaf = leaf0.eax;
+    let leaf1 = if max_leaf >= 1 { cpuid(1) } else { CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 0 } };
+    let leaf7_ebx = if max_leaf >= 7 { cpuid(7).ebx } else { 0 };
+
+    // 扩展叶: 0x80000000 返回最大扩展叶，小于基址表示不支持
+    let max_extended_leaf = match cpuid(EXTENDED_LEAF_BASE).eax {
+        eax if eax > EXTENDED_LEAF_BASE => eax,
+        _ => 0,
+    };
+    let extended_edx = if max_extended_leaf >= 0x8000_0001 {
+        cpuid(0x8000_0001).edx
+    } else {
+        0
+    };
+
+    // 品牌字符串: 3 个叶，每个 16 字节 (EAX, EBX, ECX, EDX)
+    let mut brand = [0u8; 48];
+    let has_brand = max_extended_leaf >= BRAND_LEAVES[2];
+    if has_brand {
+        for (i, &leaf) in BRAND_LEAVES.iter().enumerate() {
+            let result = cpuid(leaf);
+            let offset = i * 16;
+            brand[offset..offset + 4].copy_from_slice(&result.eax.to_le_bytes());
+            brand[offset + 4..offset + 8].copy_from_slice(&result.ebx.to_le_bytes());
+            brand[offset + 8..offset + 12].copy_from_slice(&result.ecx.to_le_bytes());
+            brand[offset + 12..offset + 16].copy_from_slice(&result.edx.to_le_bytes());
+        }
+    }
+
+    CpuInfo {
+        vendor,
+        brand,
+        has_brand,
+        max_leaf,
+        max_extended_leaf,
+        signature: leaf1.eax,
+        leaf1_ecx: leaf1.ecx,
+        leaf1_edx: leaf1.edx,
+        leaf7_ebx,
+        extended_edx,
+    }
+}
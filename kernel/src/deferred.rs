@@ -0,0 +1,79 @@
+// kernel/src/deferred.rs
+// 延迟工作队列 (bottom half) - 中断处理程序只登记任务，任务在主循环中开中断执行
+
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+
+use crate::event::{self, WorkFlags};
+
+/// 队列容量
+const QUEUE_SIZE: usize = 64;
+
+/// 一个延迟任务: 函数 + 一个参数 (中断处理程序中采集到的数据，例如扫描码)
+#[derive(Clone, Copy)]
+pub struct Job {
+    run: fn(u64),
+    arg: u64,
+}
+
+impl Job {
+    pub const fn new(run: fn(u64), arg: u64) -> Job {
+        Job { run, arg }
+    }
+}
+
+/// 任务环形队列
+struct JobQueue {
+    jobs: [Option<Job>; QUEUE_SIZE],
+    head: usize,
+    len: usize,
+}
+
+impl JobQueue {
+    const fn new() -> JobQueue {
+        JobQueue {
+            jobs: [None; QUEUE_SIZE],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, job: Job) -> bool {
+        if self.len == QUEUE_SIZE {
+            return false;
+        }
+        self.jobs[(self.head + self.len) % QUEUE_SIZE] = Some(job);
+        self.len += 1;
+        true
+    }
+
+    fn pop(&mut self) -> Option<Job> {
+        if self.len == 0 {
+            return None;
+        }
+        let job = self.jobs[self.head].take();
+        self.head = (self.head + 1) % QUEUE_SIZE;
+        self.len -= 1;
+        job
+    }
+}
+
+/// 全局任务队列 (总是在关中断时加锁)
+static QUEUE: Mutex<JobQueue> = Mutex::new(JobQueue::new());
+
+/// 登记一个延迟任务，队列已满时丢弃并返回 false
+/// 可以在中断处理程序中调用
+pub fn defer(job: Job) -> bool {
+    let queued = interrupts::without_interrupts(|| QUEUE.lock().push(job));
+    if queued {
+        event::raise(WorkFlags::DEFERRED);
+    }
+    queued
+}
+
+/// 在主循环中调用 (处理 DEFERRED 工作): 依次执行所有待执行的任务 (执行时中断开启)
+pub fn run_deferred() {
+    while let Some(job) = interrupts::without_interrupts(|| QUEUE.lock().pop()) {
+        (job.run)(job.arg);
+    }
+}
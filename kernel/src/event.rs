@@ -0,0 +1,86 @@
+// kernel/src/event.rs
+// 事件循环 - 中断处理程序只设置"有待处理的工作"标志，主循环 hlt 醒来后检查标志并分派处理
+
+use core::ops::BitOr;
+use core::sync::atomic::{AtomicU32, Ordering};
+use x86_64::instructions::interrupts;
+
+/// 待处理工作的位集合，新增工作类型时在这里加一个位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkFlags(u32);
+
+impl WorkFlags {
+    /// 键盘队列中有扫描码
+    pub const INPUT: WorkFlags = WorkFlags(1 << 0);
+    /// 运行时间过了一秒 (每秒的 tick 数个 tick)
+    pub const SECOND: WorkFlags = WorkFlags(1 << 1);
+    /// 延迟队列中有任务
+    pub const DEFERRED: WorkFlags = WorkFlags(1 << 2);
+    /// 有软件定时器到期
+    pub const TIMER: WorkFlags = WorkFlags(1 << 3);
+    /// 定时器中断递增了 tick 计数
+    pub const TICK: WorkFlags = WorkFlags(1 << 4);
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// 是否包含 other 中的所有位
+    pub const fn contains(self, other: WorkFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for WorkFlags {
+    type Output = WorkFlags;
+
+    fn bitor(self, other: WorkFlags) -> WorkFlags {
+        WorkFlags(self.0 | other.0)
+    }
+}
+
+/// 已登记但主循环还没处理的工作
+static PENDING: AtomicU32 = AtomicU32::new(0);
+
+/// 登记待处理的工作，可以在中断处理程序中调用
+pub fn raise(work: WorkFlags) {
+    PENDING.fetch_or(work.bits(), Ordering::SeqCst);
+}
+
+/// 取出并清除所有待处理的工作
+pub fn take() -> WorkFlags {
+    WorkFlags(PENDING.swap(0, Ordering::SeqCst))
+}
+
+/// 等待工作: 没有待处理的工作时 hlt 直到下一个中断
+/// 关中断后再检查，避免错过检查与 hlt 之间登记的工作
+pub fn wait() -> WorkFlags {
+    loop {
+        interrupts::disable();
+        let work = take();
+        if !work.is_empty() {
+            interrupts::enable();
+            return work;
+        }
+        interrupts::enable_and_hlt();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_combine_and_contain() {
+        let work = WorkFlags::INPUT | WorkFlags::DEFERRED;
+        assert!(work.contains(WorkFlags::INPUT));
+        assert!(work.contains(WorkFlags::DEFERRED));
+        assert!(!work.contains(WorkFlags::SECOND));
+        assert!(!work.is_empty());
+        assert!(WorkFlags(0).is_empty());
+    }
+}
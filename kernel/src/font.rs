@@ -1,23 +1,208 @@
 // kernel/src/font.rs
 
+/// 點陣字體
+/// 字形每行一個字節，最低位是最左邊的像素，所以寬度最多 8
+pub trait Font: Sync {
+    /// 字體名稱 (font 命令使用)
+    fn name(&self) -> &'static str;
+    /// 字符寬度 (像素)
+    fn width(&self) -> usize;
+    /// 字符高度 (像素)
+    fn height(&self) -> usize;
+    /// 獲取字符的位圖數據，共 height() 行
+    fn glyph(&self, ch: char) -> &'static [u8];
+}
+
 /// 8x8 位圖字體
 pub struct Font8x8;
 
-impl Font8x8 {
-    /// 字符寬度
-    pub const WIDTH: usize = 8;
-    /// 字符高度  
-    pub const HEIGHT: usize = 8;
-    
-    /// 獲取字符的位圖數據
-    pub fn get_char(ch: char) -> [u8; 8] {
-        let index = ch as usize;
-        if index < FONT_8X8.len() {
-            FONT_8X8[index]
-        } else {
-            FONT_8X8[32] // 默認使用空格字符
+impl Font for Font8x8 {
+    fn name(&self) -> &'static str {
+        "8x8"
+    }
+
+    fn width(&self) -> usize {
+        8
+    }
+
+    fn height(&self) -> usize {
+        8
+    }
+
+    fn glyph(&self, ch: char) -> &'static [u8] {
+        lookup(&FONT_8X8, &EXTRA_GLYPHS_8X8, &MISSING_GLYPH_8X8, ch)
+    }
+}
+
+/// 8x16 位圖字體
+pub struct Font8x16;
+
+impl Font for Font8x16 {
+    fn name(&self) -> &'static str {
+        "8x16"
+    }
+
+    fn width(&self) -> usize {
+        8
+    }
+
+    fn height(&self) -> usize {
+        16
+    }
+
+    fn glyph(&self, ch: char) -> &'static [u8] {
+        lookup(&FONT_8X16, &EXTRA_GLYPHS_8X16, &MISSING_GLYPH_8X16, ch)
+    }
+}
+
+/// 所有內置字體，第一個是默認字體
+pub static FONTS: [&dyn Font; 2] = [&Font8x8, &Font8x16];
+
+/// 按名稱查找字體
+pub fn find(name: &str) -> Option<&'static dyn Font> {
+    FONTS.iter().copied().find(|font| font.name() == name)
+}
+
+/// 查找字形：ASCII 直接索引，其它字符按 Unicode 碼位在附加字形表中查找
+/// 都找不到時返回「缺失字形」方框，方便看出哪些字符不受支持
+fn lookup<const H: usize>(
+    ascii: &'static [[u8; H]; 128],
+    extra: &'static [(char, [u8; H])],
+    missing: &'static [u8; H],
+    ch: char,
+) -> &'static [u8] {
+    if let Some(bitmap) = ascii.get(ch as usize) {
+        return bitmap;
+    }
+    match extra.iter().find(|(extra_ch, _)| *extra_ch == ch) {
+        Some((_, bitmap)) => bitmap,
+        None => missing,
+    }
+}
+
+/// 附加字形數量
+const EXTRA_GLYPH_COUNT: usize = 34;
+
+/// 8x8 附加字形 (製表符、✓ 和組合鍵能輸入的帶重音字母)
+static EXTRA_GLYPHS_8X8: [(char, [u8; 8]); EXTRA_GLYPH_COUNT] = extra_glyphs();
+/// 8x16 附加字形
+static EXTRA_GLYPHS_8X16: [(char, [u8; 16]); EXTRA_GLYPH_COUNT] = extra_glyphs();
+/// 8x8 缺失字形
+static MISSING_GLYPH_8X8: [u8; 8] = missing_glyph();
+/// 8x16 缺失字形
+static MISSING_GLYPH_8X16: [u8; 16] = missing_glyph();
+
+/// ✓ 的 8x8 字形
+const CHECK_MARK_8X8: [u8; 8] = [0x00, 0x40, 0x60, 0x31, 0x1B, 0x0E, 0x04, 0x00];
+
+/// 重音符號，放在小寫字母上方空出的前兩行
+const ACUTE: [u8; 2] = [0x10, 0x08];
+const GRAVE: [u8; 2] = [0x04, 0x08];
+const CIRCUMFLEX: [u8; 2] = [0x0C, 0x12];
+const DIAERESIS: [u8; 2] = [0x12, 0x00];
+const TILDE: [u8; 2] = [0x26, 0x19];
+/// 下加符 (ç)，放在最後一行
+const CEDILLA: u8 = 0x0C;
+
+/// 生成附加字形表 (編譯期求值)
+const fn extra_glyphs<const H: usize>() -> [(char, [u8; H]); EXTRA_GLYPH_COUNT] {
+    [
+        ('─', box_glyph(false, false, true, true)),
+        ('│', box_glyph(true, true, false, false)),
+        ('┌', box_glyph(false, true, false, true)),
+        ('┐', box_glyph(false, true, true, false)),
+        ('└', box_glyph(true, false, false, true)),
+        ('┘', box_glyph(true, false, true, false)),
+        ('├', box_glyph(true, true, false, true)),
+        ('┤', box_glyph(true, true, true, false)),
+        ('┬', box_glyph(false, true, true, true)),
+        ('┴', box_glyph(true, false, true, true)),
+        ('┼', box_glyph(true, true, true, true)),
+        ('✓', stretch(CHECK_MARK_8X8)),
+        ('á', stretch(accented('a', ACUTE))),
+        ('à', stretch(accented('a', GRAVE))),
+        ('â', stretch(accented('a', CIRCUMFLEX))),
+        ('ä', stretch(accented('a', DIAERESIS))),
+        ('é', stretch(accented('e', ACUTE))),
+        ('è', stretch(accented('e', GRAVE))),
+        ('ê', stretch(accented('e', CIRCUMFLEX))),
+        ('ë', stretch(accented('e', DIAERESIS))),
+        ('í', stretch(accented('i', ACUTE))),
+        ('ì', stretch(accented('i', GRAVE))),
+        ('î', stretch(accented('i', CIRCUMFLEX))),
+        ('ï', stretch(accented('i', DIAERESIS))),
+        ('ó', stretch(accented('o', ACUTE))),
+        ('ò', stretch(accented('o', GRAVE))),
+        ('ô', stretch(accented('o', CIRCUMFLEX))),
+        ('ö', stretch(accented('o', DIAERESIS))),
+        ('ú', stretch(accented('u', ACUTE))),
+        ('ù', stretch(accented('u', GRAVE))),
+        ('û', stretch(accented('u', CIRCUMFLEX))),
+        ('ü', stretch(accented('u', DIAERESIS))),
+        ('ñ', stretch(accented('n', TILDE))),
+        ('ç', stretch(with_cedilla('c'))),
+    ]
+}
+
+/// 在小寫字母上方加重音符號 (替換前兩行，i 的點也被替換掉)
+const fn accented(base: char, accent: [u8; 2]) -> [u8; 8] {
+    let mut glyph = FONT_8X8[base as usize];
+    glyph[0] = accent[0];
+    glyph[1] = accent[1];
+    glyph
+}
+
+/// 在字母下方加下加符
+const fn with_cedilla(base: char) -> [u8; 8] {
+    let mut glyph = FONT_8X8[base as usize];
+    glyph[7] |= CEDILLA;
+    glyph
+}
+
+/// 生成製表符字形：線條寬 2 像素，從字符格中心延伸到指定的邊，相鄰字符可以無縫連接
+const fn box_glyph<const H: usize>(up: bool, down: bool, left: bool, right: bool) -> [u8; H] {
+    let mid = H / 2 - 1;
+    let mut glyph = [0u8; H];
+    let mut row = 0;
+    while row < H {
+        if (up && row <= mid + 1) || (down && row >= mid) {
+            glyph[row] |= 0x18; // 第 3、4 列
         }
+        if row == mid || row == mid + 1 {
+            if left {
+                glyph[row] |= 0x1F; // 第 0-4 列
+            }
+            if right {
+                glyph[row] |= 0xF8; // 第 3-7 列
+            }
+        }
+        row += 1;
+    }
+    glyph
+}
+
+/// 把 8 行字形拉伸到 H 行，與 FONT_8X16 的生成方式相同 (中間 3/4 高度，上下留白)
+const fn stretch<const H: usize>(glyph: [u8; 8]) -> [u8; H] {
+    let inner = if H > 8 { H * 3 / 4 } else { H };
+    let pad = (H - inner) / 2;
+    let mut out = [0u8; H];
+    let mut row = 0;
+    while row < inner {
+        out[pad + row] = glyph[row * 8 / inner];
+        row += 1;
+    }
+    out
+}
+
+/// 生成缺失字形：一個空心方框
+const fn missing_glyph<const H: usize>() -> [u8; H] {
+    let mut glyph = [0u8; H];
+    let mut row = 1;
+    while row < H - 1 {
+        glyph[row] = if row == 1 || row == H - 2 { 0x7E } else { 0x42 };
+        row += 1;
     }
+    glyph
 }
 
 /// 8x8 ASCII 字體數據 (只包含常用字符 0-127)
@@ -162,4 +347,141 @@ static FONT_8X8: [[u8; 8]; 128] = [
     [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // 0x7D '}'
     [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x7E '~'
     [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x7F DEL
-];
\ No newline at end of file
+];
+
+
+/// 8x16 ASCII 字體數據
+/// 由 8x8 字形縱向拉伸到 12 行，上下各留 2 行間距，長時間閱讀時更清晰
+static FONT_8X16: [[u8; 16]; 128] = [
+    // 0x00-0x1F: 控制字符，全部用空格代替
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x00
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x01
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x02
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x03
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x04
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x05
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x06
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x07
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x08
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x09
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x0A
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x0B
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x0C
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x0D
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x0E
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x0F
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x10
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x11
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x12
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x13
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x14
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x15
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x16
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x17
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x18
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x19
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1A
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1B
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1C
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1D
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1E
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1F
+
+    // 0x20-0x7F: 可打印 ASCII 字符
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x20 ' ' 空格
+    [0x00, 0x00, 0x18, 0x18, 0x3C, 0x3C, 0x3C, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00], // 0x21 '!'
+    [0x00, 0x00, 0x36, 0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x22 '"'
+    [0x00, 0x00, 0x36, 0x36, 0x36, 0x7F, 0x7F, 0x36, 0x7F, 0x7F, 0x36, 0x36, 0x36, 0x00, 0x00, 0x00], // 0x23 '#'
+    [0x00, 0x00, 0x0C, 0x0C, 0x3E, 0x03, 0x03, 0x1E, 0x30, 0x30, 0x1F, 0x0C, 0x0C, 0x00, 0x00, 0x00], // 0x24 '$'
+    [0x00, 0x00, 0x00, 0x00, 0x63, 0x33, 0x33, 0x18, 0x0C, 0x0C, 0x66, 0x63, 0x63, 0x00, 0x00, 0x00], // 0x25 '%'
+    [0x00, 0x00, 0x1C, 0x1C, 0x36, 0x1C, 0x1C, 0x6E, 0x3B, 0x3B, 0x33, 0x6E, 0x6E, 0x00, 0x00, 0x00], // 0x26 '&'
+    [0x00, 0x00, 0x06, 0x06, 0x06, 0x03, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x27 '\''
+    [0x00, 0x00, 0x18, 0x18, 0x0C, 0x06, 0x06, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x18, 0x00, 0x00, 0x00], // 0x28 '('
+    [0x00, 0x00, 0x06, 0x06, 0x0C, 0x18, 0x18, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x06, 0x00, 0x00, 0x00], // 0x29 ')'
+    [0x00, 0x00, 0x00, 0x00, 0x66, 0x3C, 0x3C, 0xFF, 0x3C, 0x3C, 0x66, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x2A '*'
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x0C, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x2B '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x06, 0x06, 0x00, 0x00, 0x00], // 0x2C ','
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x2D '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x0C, 0x00, 0x00, 0x00], // 0x2E '.'
+    [0x00, 0x00, 0x60, 0x60, 0x30, 0x18, 0x18, 0x0C, 0x06, 0x06, 0x03, 0x01, 0x01, 0x00, 0x00, 0x00], // 0x2F '/'
+    [0x00, 0x00, 0x3E, 0x3E, 0x63, 0x73, 0x73, 0x7B, 0x6F, 0x6F, 0x67, 0x3E, 0x3E, 0x00, 0x00, 0x00], // 0x30 '0'
+    [0x00, 0x00, 0x0C, 0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x3F, 0x00, 0x00, 0x00], // 0x31 '1'
+    [0x00, 0x00, 0x1E, 0x1E, 0x33, 0x30, 0x30, 0x1C, 0x06, 0x06, 0x33, 0x3F, 0x3F, 0x00, 0x00, 0x00], // 0x32 '2'
+    [0x00, 0x00, 0x1E, 0x1E, 0x33, 0x30, 0x30, 0x1C, 0x30, 0x30, 0x33, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x33 '3'
+    [0x00, 0x00, 0x38, 0x38, 0x3C, 0x36, 0x36, 0x33, 0x7F, 0x7F, 0x30, 0x78, 0x78, 0x00, 0x00, 0x00], // 0x34 '4'
+    [0x00, 0x00, 0x3F, 0x3F, 0x03, 0x1F, 0x1F, 0x30, 0x30, 0x30, 0x33, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x35 '5'
+    [0x00, 0x00, 0x1C, 0x1C, 0x06, 0x03, 0x03, 0x1F, 0x33, 0x33, 0x33, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x36 '6'
+    [0x00, 0x00, 0x3F, 0x3F, 0x33, 0x30, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x00, 0x00, 0x00], // 0x37 '7'
+    [0x00, 0x00, 0x1E, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x38 '8'
+    [0x00, 0x00, 0x1E, 0x1E, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x30, 0x18, 0x0E, 0x0E, 0x00, 0x00, 0x00], // 0x39 '9'
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x0C, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x0C, 0x00, 0x00, 0x00], // 0x3A ':'
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x0C, 0x00, 0x00, 0x00, 0x0C, 0x06, 0x06, 0x00, 0x00, 0x00], // 0x3B ';'
+    [0x00, 0x00, 0x18, 0x18, 0x0C, 0x06, 0x06, 0x03, 0x06, 0x06, 0x0C, 0x18, 0x18, 0x00, 0x00, 0x00], // 0x3C '<'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3F, 0x3F, 0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x3D '='
+    [0x00, 0x00, 0x06, 0x06, 0x0C, 0x18, 0x18, 0x30, 0x18, 0x18, 0x0C, 0x06, 0x06, 0x00, 0x00, 0x00], // 0x3E '>'
+    [0x00, 0x00, 0x1E, 0x1E, 0x33, 0x30, 0x30, 0x18, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00, 0x00, 0x00], // 0x3F '?'
+    [0x00, 0x00, 0x3E, 0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x40 '@'
+    [0x00, 0x00, 0x0C, 0x0C, 0x1E, 0x33, 0x33, 0x33, 0x3F, 0x3F, 0x33, 0x33, 0x33, 0x00, 0x00, 0x00], // 0x41 'A'
+    [0x00, 0x00, 0x3F, 0x3F, 0x66, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x66, 0x3F, 0x3F, 0x00, 0x00, 0x00], // 0x42 'B'
+    [0x00, 0x00, 0x3C, 0x3C, 0x66, 0x03, 0x03, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x3C, 0x00, 0x00, 0x00], // 0x43 'C'
+    [0x00, 0x00, 0x1F, 0x1F, 0x36, 0x66, 0x66, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x1F, 0x00, 0x00, 0x00], // 0x44 'D'
+    [0x00, 0x00, 0x7F, 0x7F, 0x46, 0x16, 0x16, 0x1E, 0x16, 0x16, 0x46, 0x7F, 0x7F, 0x00, 0x00, 0x00], // 0x45 'E'
+    [0x00, 0x00, 0x7F, 0x7F, 0x46, 0x16, 0x16, 0x1E, 0x16, 0x16, 0x06, 0x0F, 0x0F, 0x00, 0x00, 0x00], // 0x46 'F'
+    [0x00, 0x00, 0x3C, 0x3C, 0x66, 0x03, 0x03, 0x03, 0x73, 0x73, 0x66, 0x7C, 0x7C, 0x00, 0x00, 0x00], // 0x47 'G'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x33, 0x33, 0x00, 0x00, 0x00], // 0x48 'H'
+    [0x00, 0x00, 0x1E, 0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x49 'I'
+    [0x00, 0x00, 0x78, 0x78, 0x30, 0x30, 0x30, 0x30, 0x33, 0x33, 0x33, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x4A 'J'
+    [0x00, 0x00, 0x67, 0x67, 0x66, 0x36, 0x36, 0x1E, 0x36, 0x36, 0x66, 0x67, 0x67, 0x00, 0x00, 0x00], // 0x4B 'K'
+    [0x00, 0x00, 0x0F, 0x0F, 0x06, 0x06, 0x06, 0x06, 0x46, 0x46, 0x66, 0x7F, 0x7F, 0x00, 0x00, 0x00], // 0x4C 'L'
+    [0x00, 0x00, 0x63, 0x63, 0x77, 0x7F, 0x7F, 0x7F, 0x6B, 0x6B, 0x63, 0x63, 0x63, 0x00, 0x00, 0x00], // 0x4D 'M'
+    [0x00, 0x00, 0x63, 0x63, 0x67, 0x6F, 0x6F, 0x7B, 0x73, 0x73, 0x63, 0x63, 0x63, 0x00, 0x00, 0x00], // 0x4E 'N'
+    [0x00, 0x00, 0x1C, 0x1C, 0x36, 0x63, 0x63, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x1C, 0x00, 0x00, 0x00], // 0x4F 'O'
+    [0x00, 0x00, 0x3F, 0x3F, 0x66, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x06, 0x0F, 0x0F, 0x00, 0x00, 0x00], // 0x50 'P'
+    [0x00, 0x00, 0x1E, 0x1E, 0x33, 0x33, 0x33, 0x33, 0x3B, 0x3B, 0x1E, 0x38, 0x38, 0x00, 0x00, 0x00], // 0x51 'Q'
+    [0x00, 0x00, 0x3F, 0x3F, 0x66, 0x66, 0x66, 0x3E, 0x36, 0x36, 0x66, 0x67, 0x67, 0x00, 0x00, 0x00], // 0x52 'R'
+    [0x00, 0x00, 0x1E, 0x1E, 0x33, 0x07, 0x07, 0x0E, 0x38, 0x38, 0x33, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x53 'S'
+    [0x00, 0x00, 0x3F, 0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x54 'T'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x3F, 0x00, 0x00, 0x00], // 0x55 'U'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x00, 0x00, 0x00], // 0x56 'V'
+    [0x00, 0x00, 0x63, 0x63, 0x63, 0x63, 0x63, 0x6B, 0x7F, 0x7F, 0x77, 0x63, 0x63, 0x00, 0x00, 0x00], // 0x57 'W'
+    [0x00, 0x00, 0x63, 0x63, 0x63, 0x36, 0x36, 0x1C, 0x1C, 0x1C, 0x36, 0x63, 0x63, 0x00, 0x00, 0x00], // 0x58 'X'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x0C, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x59 'Y'
+    [0x00, 0x00, 0x7F, 0x7F, 0x63, 0x31, 0x31, 0x18, 0x4C, 0x4C, 0x66, 0x7F, 0x7F, 0x00, 0x00, 0x00], // 0x5A 'Z'
+    [0x00, 0x00, 0x1E, 0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x5B '['
+    [0x00, 0x00, 0x03, 0x03, 0x06, 0x0C, 0x0C, 0x18, 0x30, 0x30, 0x60, 0x40, 0x40, 0x00, 0x00, 0x00], // 0x5C '\'
+    [0x00, 0x00, 0x1E, 0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x5D ']'
+    [0x00, 0x00, 0x08, 0x08, 0x1C, 0x36, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x5E '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0x00, 0x00], // 0x5F '_'
+    [0x00, 0x00, 0x0C, 0x0C, 0x0C, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x60 '`'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x1E, 0x1E, 0x30, 0x3E, 0x3E, 0x33, 0x6E, 0x6E, 0x00, 0x00, 0x00], // 0x61 'a'
+    [0x00, 0x00, 0x07, 0x07, 0x06, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x66, 0x3B, 0x3B, 0x00, 0x00, 0x00], // 0x62 'b'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x1E, 0x1E, 0x33, 0x03, 0x03, 0x33, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x63 'c'
+    [0x00, 0x00, 0x38, 0x38, 0x30, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x33, 0x6E, 0x6E, 0x00, 0x00, 0x00], // 0x64 'd'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x1E, 0x1E, 0x33, 0x3F, 0x3F, 0x03, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x65 'e'
+    [0x00, 0x00, 0x1C, 0x1C, 0x36, 0x06, 0x06, 0x0F, 0x06, 0x06, 0x06, 0x0F, 0x0F, 0x00, 0x00, 0x00], // 0x66 'f'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x6E, 0x6E, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x30, 0x1F, 0x00, 0x00], // 0x67 'g'
+    [0x00, 0x00, 0x07, 0x07, 0x06, 0x36, 0x36, 0x6E, 0x66, 0x66, 0x66, 0x67, 0x67, 0x00, 0x00, 0x00], // 0x68 'h'
+    [0x00, 0x00, 0x0C, 0x0C, 0x00, 0x0E, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x69 'i'
+    [0x00, 0x00, 0x30, 0x30, 0x00, 0x30, 0x30, 0x30, 0x30, 0x30, 0x33, 0x33, 0x33, 0x1E, 0x00, 0x00], // 0x6A 'j'
+    [0x00, 0x00, 0x07, 0x07, 0x06, 0x66, 0x66, 0x36, 0x1E, 0x1E, 0x36, 0x67, 0x67, 0x00, 0x00, 0x00], // 0x6B 'k'
+    [0x00, 0x00, 0x0E, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x6C 'l'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x33, 0x33, 0x7F, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00, 0x00, 0x00], // 0x6D 'm'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x00, 0x00, 0x00], // 0x6E 'n'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x1E, 0x1E, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x1E, 0x00, 0x00, 0x00], // 0x6F 'o'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3B, 0x3B, 0x66, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00, 0x00], // 0x70 'p'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x6E, 0x6E, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x30, 0x78, 0x00, 0x00], // 0x71 'q'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3B, 0x3B, 0x6E, 0x66, 0x66, 0x06, 0x0F, 0x0F, 0x00, 0x00, 0x00], // 0x72 'r'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3E, 0x3E, 0x03, 0x1E, 0x1E, 0x30, 0x1F, 0x1F, 0x00, 0x00, 0x00], // 0x73 's'
+    [0x00, 0x00, 0x08, 0x08, 0x0C, 0x3E, 0x3E, 0x0C, 0x0C, 0x0C, 0x2C, 0x18, 0x18, 0x00, 0x00, 0x00], // 0x74 't'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x6E, 0x00, 0x00, 0x00], // 0x75 'u'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x00, 0x00, 0x00], // 0x76 'v'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x63, 0x63, 0x6B, 0x7F, 0x7F, 0x7F, 0x36, 0x36, 0x00, 0x00, 0x00], // 0x77 'w'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x63, 0x00, 0x00, 0x00], // 0x78 'x'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x30, 0x1F, 0x00, 0x00], // 0x79 'y'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3F, 0x3F, 0x19, 0x0C, 0x0C, 0x26, 0x3F, 0x3F, 0x00, 0x00, 0x00], // 0x7A 'z'
+    [0x00, 0x00, 0x38, 0x38, 0x0C, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x0C, 0x38, 0x38, 0x00, 0x00, 0x00], // 0x7B '{'
+    [0x00, 0x00, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x00, 0x00], // 0x7C '|'
+    [0x00, 0x00, 0x07, 0x07, 0x0C, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x0C, 0x07, 0x07, 0x00, 0x00, 0x00], // 0x7D '}'
+    [0x00, 0x00, 0x6E, 0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x7E '~'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x7F DEL
+];
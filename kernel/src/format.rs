@@ -0,0 +1,322 @@
+// kernel/src/format.rs
+// 纯格式化函数 - 只接收数字，输出到任意 fmt::Write
+// 不直接访问屏幕，方便脱离帧缓冲区进行单元测试
+
+use core::fmt::{self, Write};
+use crate::time::UptimeInfo;
+
+/// 定长的行缓冲区，用于先格式化再原地输出 (例如 Writer::write_at)
+/// 超出容量的部分被截断
+pub struct LineBuffer<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> LineBuffer<N> {
+    pub const fn new() -> LineBuffer<N> {
+        LineBuffer { buf: [0; N], len: 0 }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // 只在字符边界截断，内容始终是合法 UTF-8
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Write for LineBuffer<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for ch in s.chars() {
+            let mut utf8 = [0u8; 4];
+            let bytes = ch.encode_utf8(&mut utf8).as_bytes();
+            if self.len + bytes.len() > N {
+                return Err(fmt::Error);
+            }
+            self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+            self.len += bytes.len();
+        }
+        Ok(())
+    }
+}
+
+/// 写入运行时间: "N days, HH:MM:SS.mmm"，不足一天时省略天数
+pub fn write_uptime(out: &mut dyn Write, total_ms: u64) -> fmt::Result {
+    let info = UptimeInfo::from_ms(total_ms);
+    if info.days > 0 {
+        write!(out, "{} days, ", info.days)?;
+    }
+    write!(out, "{:02}:{:02}:{:02}.{:03}", info.hours, info.minutes, info.seconds, info.milliseconds)
+}
+
+/// 写入 HH:MM:SS (天数折算进小时)
+pub fn write_hms(out: &mut dyn Write, total_ms: u64) -> fmt::Result {
+    let (hours, minutes, seconds) = UptimeInfo::from_ms(total_ms).format_detailed().short_format();
+    write!(out, "{:02}:{:02}:{:02}", hours, minutes, seconds)
+}
+
+/// 写入 HH:MM:SS.mmm (天数折算进小时)
+pub fn write_hms_millis(out: &mut dyn Write, total_ms: u64) -> fmt::Result {
+    write_hms(out, total_ms)?;
+    write!(out, ".{:03}", total_ms % 1000)
+}
+
+/// 写入 uptime 命令的定时器详情
+pub fn write_timer_details(
+    out: &mut dyn Write,
+    total_ms: u64,
+    tick_count: u64,
+    frequency: u32,
+    interval_ms: u32,
+) -> fmt::Result {
+    writeln!(out, "  Total milliseconds: {}", total_ms)?;
+    writeln!(out, "  Timer ticks:        {}", tick_count)?;
+    writeln!(out, "  Timer frequency:    {} Hz", frequency)?;
+    writeln!(out, "  Tick interval:      {} ms", interval_ms)?;
+    if let Some(avg_ms_per_tick) = average_ms_per_tick(total_ms, tick_count) {
+        writeln!(out, "  Average per tick:   {:.2} ms", avg_ms_per_tick)?;
+    }
+    Ok(())
+}
+
+/// 写入 stats 命令的命令统计
+/// `uptime_ms` 为 None 表示时间系统未初始化
+pub fn write_command_stats(
+    out: &mut dyn Write,
+    command_count: u64,
+    buffer_size: usize,
+    buffer_used: usize,
+    available_commands: usize,
+    uptime_ms: Option<u64>,
+) -> fmt::Result {
+    writeln!(out, "Commands executed:    {}", command_count)?;
+    writeln!(out, "Input buffer size:    {} bytes", buffer_size)?;
+    writeln!(out, "Current buffer used:  {} bytes", buffer_used)?;
+    writeln!(out, "Available commands:   {}", available_commands)?;
+    if let Some(avg) = uptime_ms.and_then(|ms| average_ms_per_command(ms, command_count)) {
+        writeln!(out, "Avg time per command: {} ms", avg)?;
+    }
+    Ok(())
+}
+
+/// 写入 stats 命令的会话速率
+/// `uptime_seconds` 为 None 表示时间系统未初始化
+pub fn write_session_rates(out: &mut dyn Write, command_count: u64, uptime_seconds: Option<u64>) -> fmt::Result {
+    if let Some(rate) = uptime_seconds.and_then(|secs| commands_per_minute(command_count, secs)) {
+        writeln!(out, "  Commands per minute: {}", rate)?;
+    }
+    Ok(())
+}
+
+// === 统计计算 ===
+// 所有除法都集中在这里，分母为 0 时返回 None，调用方直接省略该行
+
+/// 平均每个 tick 的毫秒数
+pub fn average_ms_per_tick(total_ms: u64, tick_count: u64) -> Option<f32> {
+    if total_ms == 0 || tick_count == 0 {
+        return None;
+    }
+    Some(total_ms as f32 / tick_count as f32)
+}
+
+/// 平均每条命令间隔的毫秒数
+pub fn average_ms_per_command(uptime_ms: u64, command_count: u64) -> Option<u64> {
+    if uptime_ms == 0 {
+        return None;
+    }
+    uptime_ms.checked_div(command_count)
+}
+
+/// 每分钟执行的命令数
+pub fn commands_per_minute(command_count: u64, uptime_seconds: u64) -> Option<u64> {
+    if command_count == 0 {
+        return None;
+    }
+    command_count.saturating_mul(60).checked_div(uptime_seconds)
+}
+
+/// hexdump 每行的字节数
+pub const HEXDUMP_BYTES_PER_LINE: usize = 16;
+
+/// 输出一帧渲染耗时: "scroll 0.412 ms"，TSC 未校准时显示 "n/a"
+pub fn write_frame_time(out: &mut dyn Write, kind: &str, ns: Option<u64>) -> fmt::Result {
+    match ns {
+        Some(ns) => write!(out, "{} {}.{:03} ms", kind, ns / 1_000_000, ns % 1_000_000 / 1000),
+        None => write!(out, "{} n/a", kind),
+    }
+}
+
+/// 输出一行 hexdump: 地址、十六进制字节、可打印的 ASCII 字符 (其它显示为 '.')
+/// 不足一行时十六进制部分用空格补齐，ASCII 列保持对齐
+pub fn write_hexdump_line(out: &mut dyn Write, address: u64, bytes: &[u8]) -> fmt::Result {
+    write!(out, "{:012x} ", address)?;
+    for i in 0..HEXDUMP_BYTES_PER_LINE {
+        match bytes.get(i) {
+            Some(byte) => write!(out, " {:02x}", byte)?,
+            None => out.write_str("   ")?,
+        }
+    }
+    out.write_str("  ")?;
+    for &byte in bytes.iter().take(HEXDUMP_BYTES_PER_LINE) {
+        let ch = if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' };
+        out.write_char(ch)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 基于定长数组的 fmt::Write，用于捕获输出
+    struct TestBuffer {
+        buf: [u8; 512],
+        len: usize,
+    }
+
+    impl TestBuffer {
+        fn new() -> TestBuffer {
+            TestBuffer { buf: [0; 512], len: 0 }
+        }
+
+        fn as_str(&self) -> &str {
+            core::str::from_utf8(&self.buf[..self.len]).unwrap()
+        }
+    }
+
+    impl Write for TestBuffer {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let bytes = s.as_bytes();
+            if self.len + bytes.len() > self.buf.len() {
+                return Err(fmt::Error);
+            }
+            self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+            self.len += bytes.len();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn uptime_omits_days_when_zero() {
+        let mut out = TestBuffer::new();
+        write_uptime(&mut out, 3_723_004).unwrap();
+        assert_eq!(out.as_str(), "01:02:03.004");
+    }
+
+    #[test]
+    fn uptime_includes_days() {
+        let mut out = TestBuffer::new();
+        write_uptime(&mut out, 2 * 86_400_000 + 59_999).unwrap();
+        assert_eq!(out.as_str(), "2 days, 00:00:59.999");
+    }
+
+    #[test]
+    fn hms_folds_days_into_hours() {
+        let mut out = TestBuffer::new();
+        write_hms(&mut out, 86_400_000 + 61_000).unwrap();
+        assert_eq!(out.as_str(), "24:01:01");
+
+        let mut out = TestBuffer::new();
+        write_hms_millis(&mut out, 1_230).unwrap();
+        assert_eq!(out.as_str(), "00:00:01.230");
+    }
+
+    #[test]
+    fn timer_details_skip_average_without_ticks() {
+        let mut out = TestBuffer::new();
+        write_timer_details(&mut out, 0, 0, 100, 10).unwrap();
+        assert!(!out.as_str().contains("Average"));
+
+        let mut out = TestBuffer::new();
+        write_timer_details(&mut out, 1000, 100, 100, 10).unwrap();
+        assert!(out.as_str().contains("  Average per tick:   10.00 ms\n"));
+    }
+
+    #[test]
+    fn command_stats_skip_average_without_uptime() {
+        let mut out = TestBuffer::new();
+        write_command_stats(&mut out, 3, 256, 0, 8, None).unwrap();
+        assert!(!out.as_str().contains("Avg time"));
+
+        let mut out = TestBuffer::new();
+        write_command_stats(&mut out, 0, 256, 0, 8, Some(0)).unwrap();
+        assert!(!out.as_str().contains("Avg time"));
+
+        let mut out = TestBuffer::new();
+        write_command_stats(&mut out, 4, 256, 0, 8, Some(5000)).unwrap();
+        assert!(out.as_str().contains("Avg time per command: 1250 ms\n"));
+    }
+
+    #[test]
+    fn session_rates_skip_zero_uptime() {
+        let mut out = TestBuffer::new();
+        write_session_rates(&mut out, 5, Some(0)).unwrap();
+        assert_eq!(out.as_str(), "");
+
+        let mut out = TestBuffer::new();
+        write_session_rates(&mut out, 10, Some(120)).unwrap();
+        assert_eq!(out.as_str(), "  Commands per minute: 5\n");
+    }
+
+    #[test]
+    fn average_per_tick_handles_zero_ticks() {
+        assert_eq!(average_ms_per_tick(0, 0), None);
+        assert_eq!(average_ms_per_tick(100, 0), None);
+        assert_eq!(average_ms_per_tick(0, 10), None);
+        assert_eq!(average_ms_per_tick(100, 10), Some(10.0));
+    }
+
+    #[test]
+    fn average_per_command_handles_zero_commands_and_uptime() {
+        assert_eq!(average_ms_per_command(0, 0), None);
+        assert_eq!(average_ms_per_command(5000, 0), None);
+        assert_eq!(average_ms_per_command(0, 3), None);
+        assert_eq!(average_ms_per_command(5000, 4), Some(1250));
+    }
+
+    #[test]
+    fn commands_per_minute_handles_zero_uptime_and_commands() {
+        assert_eq!(commands_per_minute(0, 0), None);
+        assert_eq!(commands_per_minute(10, 0), None);
+        assert_eq!(commands_per_minute(0, 60), None);
+        assert_eq!(commands_per_minute(10, 120), Some(5));
+        assert_eq!(commands_per_minute(u64::MAX, 1), Some(u64::MAX));
+    }
+
+    #[test]
+    fn frame_time_shows_milliseconds() {
+        let mut out = TestBuffer::new();
+        write_frame_time(&mut out, "scroll", Some(412_345)).unwrap();
+        assert_eq!(out.as_str(), "scroll 0.412 ms");
+
+        let mut out = TestBuffer::new();
+        write_frame_time(&mut out, "redraw", Some(12_003_999)).unwrap();
+        assert_eq!(out.as_str(), "redraw 12.003 ms");
+
+        let mut out = TestBuffer::new();
+        write_frame_time(&mut out, "scroll", None).unwrap();
+        assert_eq!(out.as_str(), "scroll n/a");
+    }
+
+    #[test]
+    fn hexdump_line_shows_hex_and_ascii() {
+        let mut out = TestBuffer::new();
+        write_hexdump_line(&mut out, 0x1000, b"Hello, world!\x00\x7f\n").unwrap();
+        assert_eq!(
+            out.as_str(),
+            "000000001000  48 65 6c 6c 6f 2c 20 77 6f 72 6c 64 21 00 7f 0a  Hello, world!..."
+        );
+    }
+
+    #[test]
+    fn hexdump_line_pads_short_lines() {
+        let mut out = TestBuffer::new();
+        write_hexdump_line(&mut out, 0xffff_8000_0000_0010, b"AB").unwrap();
+        assert_eq!(
+            out.as_str(),
+            "ffff800000000010  41 42                                            AB"
+        );
+    }
+}
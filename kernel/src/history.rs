@@ -0,0 +1,162 @@
+// kernel/src/history.rs
+// 命令历史 - 按最大容量静态分配的环形缓冲区，实际保留的条数 (逻辑容量) 可以在运行时调整
+
+/// 最多保存的命令条数 (编译期上限)
+pub const MAX_HISTORY: usize = 32;
+/// 默认保存的命令条数
+pub const DEFAULT_HISTORY: usize = 16;
+/// 每条命令最多保存的字节数，更长的命令在字符边界处截断
+pub const MAX_ENTRY_LEN: usize = 256;
+
+/// 容量超出 1..=MAX_HISTORY
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityOutOfRange;
+
+/// 一条历史记录
+#[derive(Clone, Copy)]
+struct Entry {
+    text: [u8; MAX_ENTRY_LEN],
+    len: usize,
+}
+
+impl Entry {
+    const EMPTY: Entry = Entry { text: [0; MAX_ENTRY_LEN], len: 0 };
+
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.text[..self.len]).unwrap_or("")
+    }
+}
+
+/// 命令历史
+pub struct History {
+    entries: [Entry; MAX_HISTORY],
+    /// 最旧一条所在的槽位
+    start: usize,
+    len: usize,
+    /// 逻辑容量，1..=MAX_HISTORY
+    capacity: usize,
+}
+
+impl History {
+    pub const fn new() -> History {
+        History {
+            entries: [Entry::EMPTY; MAX_HISTORY],
+            start: 0,
+            len: 0,
+            capacity: DEFAULT_HISTORY,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 设置逻辑容量，超出 1..=MAX_HISTORY 时返回 Err 且不做修改
+    /// 缩小时丢弃最旧的记录
+    pub fn set_capacity(&mut self, capacity: usize) -> Result<(), CapacityOutOfRange> {
+        if !(1..=MAX_HISTORY).contains(&capacity) {
+            return Err(CapacityOutOfRange);
+        }
+        // 先按顺序整理到槽位开头，之后的索引计算只需要考虑新的容量
+        let mut ordered = [Entry::EMPTY; MAX_HISTORY];
+        let keep = self.len.min(capacity);
+        let skip = self.len - keep;
+        for (i, slot) in ordered.iter_mut().take(keep).enumerate() {
+            *slot = self.entries[(self.start + skip + i) % MAX_HISTORY];
+        }
+        self.entries = ordered;
+        self.start = 0;
+        self.len = keep;
+        self.capacity = capacity;
+        Ok(())
+    }
+
+    /// 添加一条命令，已满时覆盖最旧的一条；空命令和与上一条相同的命令不记录
+    pub fn push(&mut self, command: &str) {
+        if command.is_empty() || self.iter().last() == Some(command) {
+            return;
+        }
+        let mut len = command.len().min(MAX_ENTRY_LEN);
+        while !command.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        if self.len == self.capacity {
+            self.start = (self.start + 1) % MAX_HISTORY;
+            self.len -= 1;
+        }
+        let entry = &mut self.entries[(self.start + self.len) % MAX_HISTORY];
+        entry.text[..len].copy_from_slice(&command.as_bytes()[..len]);
+        entry.len = len;
+        self.len += 1;
+    }
+
+    /// 从旧到新遍历
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        (0..self.len).map(move |i| self.entries[(self.start + i) % MAX_HISTORY].as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect<'a>(history: &'a History, out: &mut [&'a str; MAX_HISTORY]) -> usize {
+        let mut count = 0;
+        for (slot, entry) in out.iter_mut().zip(history.iter()) {
+            *slot = entry;
+            count += 1;
+        }
+        count
+    }
+
+    #[test]
+    fn keeps_the_newest_entries_up_to_capacity() {
+        let mut history = History::new();
+        history.set_capacity(3).unwrap();
+        for command in ["a", "b", "b", "", "c", "d"] {
+            history.push(command);
+        }
+        let mut out = [""; MAX_HISTORY];
+        let count = collect(&history, &mut out);
+        assert_eq!(&out[..count], &["b", "c", "d"]);
+    }
+
+    #[test]
+    fn shrinking_drops_oldest_and_rejects_out_of_range() {
+        let mut history = History::new();
+        for command in ["1", "2", "3", "4", "5"] {
+            history.push(command);
+        }
+        assert_eq!(history.set_capacity(0), Err(CapacityOutOfRange));
+        assert_eq!(history.set_capacity(MAX_HISTORY + 1), Err(CapacityOutOfRange));
+        assert_eq!(history.capacity(), DEFAULT_HISTORY);
+
+        history.set_capacity(2).unwrap();
+        let mut out = [""; MAX_HISTORY];
+        let count = collect(&history, &mut out);
+        assert_eq!(&out[..count], &["4", "5"]);
+
+        history.set_capacity(MAX_HISTORY).unwrap();
+        history.push("6");
+        assert_eq!(history.len(), 3);
+    }
+
+    #[test]
+    fn long_commands_are_truncated_on_char_boundary() {
+        let mut history = History::new();
+        let mut long = [b'a'; MAX_ENTRY_LEN + 1];
+        // 'é' 占两个字节，跨过上限
+        long[MAX_ENTRY_LEN - 1..].copy_from_slice("é".as_bytes());
+        history.push(core::str::from_utf8(&long).unwrap());
+        assert_eq!(history.iter().next().unwrap().len(), MAX_ENTRY_LEN - 1);
+    }
+}
@@ -0,0 +1,423 @@
+// kernel/src/input.rs
+// 键盘输入队列 - 中断处理程序只负责入队，解码和回显在主循环中完成
+// 这样命令在主循环中执行时中断保持开启，Esc 可以随时请求中止
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+use crate::deferred::{self, Job};
+use crate::event::{self, WorkFlags};
+use crate::ps2;
+use crate::keyboard::{self, Compose, ComposeState, ExtendedKey, Key, KeyEvent, KeyboardState};
+use crate::writer::{Color, Writer};
+use crate::{print, highlight_text, reset_text_color, handle_backspace, handle_shell_char, with_shell};
+use crate::{handle_page_down, handle_page_up};
+use crate::{serial_print, serial_println};
+use crate::scrollback::MAX_COLUMNS;
+use crate::shadow::SHADOW;
+
+/// 扫描码队列容量
+const QUEUE_SIZE: usize = 128;
+
+/// 是否启用软件按键重复 (硬件自己发送重复按下码时会自动让位)
+pub const SOFTWARE_REPEAT: bool = true;
+/// 按住多久之后开始重复
+pub const REPEAT_DELAY_MS: u64 = 500;
+/// 开始重复后每次重复的间隔
+pub const REPEAT_INTERVAL_MS: u64 = 50;
+
+/// Esc 键的扫描码
+pub const ESCAPE_SCANCODE: u8 = 0x01;
+
+/// 扫描码环形队列
+struct ScancodeQueue {
+    buffer: [u8; QUEUE_SIZE],
+    head: usize,
+    len: usize,
+}
+
+impl ScancodeQueue {
+    const fn new() -> ScancodeQueue {
+        ScancodeQueue {
+            buffer: [0; QUEUE_SIZE],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// 入队，队列已满时丢弃并返回 false
+    fn push(&mut self, scancode: u8) -> bool {
+        if self.len == QUEUE_SIZE {
+            return false;
+        }
+        self.buffer[(self.head + self.len) % QUEUE_SIZE] = scancode;
+        self.len += 1;
+        true
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        let scancode = self.buffer[self.head];
+        self.head = (self.head + 1) % QUEUE_SIZE;
+        self.len -= 1;
+        Some(scancode)
+    }
+}
+
+/// 软件按键重复状态 (中断处理程序中访问，主循环只在初始化时关中断设置 tick 数)
+/// 时间用 tick 计数表示，中断处理程序不需要加锁读取运行时间
+struct KeyRepeat {
+    /// 当前按住的可重复键 (按下码)
+    key: Option<u8>,
+    /// 下一次重复的 tick 计数
+    next_at_tick: u64,
+    /// 按下后开始重复的延迟 (tick 数)
+    delay_ticks: u64,
+    /// 重复间隔 (tick 数)
+    interval_ticks: u64,
+    /// 硬件已经在发送重复按下码，软件不再重复
+    hardware: bool,
+    /// 上一个字节是 0xE0 扩展前缀
+    after_prefix: bool,
+}
+
+impl KeyRepeat {
+    const fn new() -> KeyRepeat {
+        KeyRepeat {
+            key: None,
+            next_at_tick: 0,
+            // 时间系统初始化之前按 100Hz (每 tick 10ms) 估算
+            delay_ticks: REPEAT_DELAY_MS / 10,
+            interval_ticks: REPEAT_INTERVAL_MS / 10,
+            hardware: false,
+            after_prefix: false,
+        }
+    }
+
+    /// 根据键盘发来的原始扫描码更新按住的键
+    fn observe(&mut self, scancode: u8, now_tick: u64) {
+        if scancode == keyboard::EXTENDED_PREFIX {
+            self.after_prefix = true;
+            return;
+        }
+        if self.after_prefix {
+            // 扩展键不重复；按下新的扩展键时停止当前重复
+            self.after_prefix = false;
+            if !keyboard::is_break_code(scancode) {
+                self.key = None;
+            }
+            return;
+        }
+
+        if keyboard::is_break_code(scancode) {
+            if self.key == Some(keyboard::make_code(scancode)) {
+                self.key = None;
+            }
+        } else if self.key == Some(scancode) {
+            // 没有释放就再次收到按下码，说明硬件在自动重复
+            self.hardware = true;
+        } else if keyboard::is_repeatable(scancode) {
+            self.key = Some(scancode);
+            self.next_at_tick = now_tick + self.delay_ticks;
+            self.hardware = false;
+        }
+    }
+
+    /// 到了重复时间时返回需要重复的按下码
+    fn poll(&mut self, now_tick: u64) -> Option<u8> {
+        let key = self.key?;
+        if !SOFTWARE_REPEAT || self.hardware || now_tick < self.next_at_tick {
+            return None;
+        }
+        self.next_at_tick = now_tick + self.interval_ticks;
+        Some(key)
+    }
+}
+
+/// 扫描码队列 (中断处理程序写入，主循环关中断后读取)
+static QUEUE: Mutex<ScancodeQueue> = Mutex::new(ScancodeQueue::new());
+
+/// 按键重复状态
+static REPEAT: Mutex<KeyRepeat> = Mutex::new(KeyRepeat::new());
+
+/// 中止请求标志 (Esc 按下时设置)
+static ABORT_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// 正在执行命令 (Shell 在执行期间设置)
+static COMMAND_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 全局键盘状态 (只在主循环中访问)
+static KEYBOARD_STATE: Mutex<KeyboardState> = Mutex::new(KeyboardState::new());
+
+/// 在中断处理程序中调用: 扫描码入队
+pub fn enqueue_scancode(scancode: u8) {
+    REPEAT.lock().observe(scancode, crate::time::get_tick_count());
+
+    if scancode == ESCAPE_SCANCODE {
+        // Esc 不进入队列，直接请求中止当前命令
+        ABORT_REQUESTED.store(true, Ordering::SeqCst);
+        return;
+    }
+    if QUEUE.lock().push(scancode) {
+        event::raise(WorkFlags::INPUT);
+    } else {
+        // 串口输出较慢，推迟到主循环中记录
+        deferred::defer(Job::new(report_dropped_scancode, scancode as u64));
+    }
+}
+
+/// 延迟任务: 记录因队列已满而丢弃的扫描码
+fn report_dropped_scancode(scancode: u64) {
+    crate::log_warn!("Keyboard queue full, dropped scancode {:#04x}", scancode);
+}
+
+/// 时间系统初始化之后调用: 按实际的 tick 周期换算按键重复的延迟和间隔
+pub fn init_repeat() {
+    let delay_ticks = crate::time::ms_to_ticks(REPEAT_DELAY_MS);
+    let interval_ticks = crate::time::ms_to_ticks(REPEAT_INTERVAL_MS);
+    interrupts::without_interrupts(|| {
+        let mut repeat = REPEAT.lock();
+        repeat.delay_ticks = delay_ticks;
+        repeat.interval_ticks = interval_ticks;
+    });
+}
+
+/// 在定时器中断处理程序中调用: 按住的键到时间后再次入队
+pub fn repeat_tick(now_tick: u64) {
+    if let Some(scancode) = REPEAT.lock().poll(now_tick) {
+        if QUEUE.lock().push(scancode) {
+            event::raise(WorkFlags::INPUT);
+        }
+    }
+}
+
+/// 队列中是否还有待处理的扫描码
+pub fn has_pending() -> bool {
+    interrupts::without_interrupts(|| QUEUE.lock().len > 0)
+}
+
+/// 标记命令开始或结束执行
+pub fn set_command_running(running: bool) {
+    COMMAND_RUNNING.store(running, Ordering::SeqCst);
+}
+
+/// 在主循环中调用 (处理 INPUT 工作): 处理所有待处理的按键事件
+/// 命令执行期间不处理 (即使命令里间接调用到这里)，按键留在队列中不回显，
+/// 命令结束、提示符显示之后再依次回显到新的输入行
+pub fn process_pending() {
+    if COMMAND_RUNNING.load(Ordering::SeqCst) {
+        return;
+    }
+    while let Some(event) = next_event() {
+        handle_event(event);
+    }
+}
+
+/// 从队列中取出下一个按键事件 (按下和释放都会返回)
+/// 修饰键状态在这里更新，所以直接读取事件的调用者 (例如游戏) 也能得到正确的 Shift/Caps 状态
+pub fn next_event() -> Option<KeyEvent> {
+    loop {
+        // 关中断取出，避免与中断处理程序争用队列锁
+        let scancode = interrupts::without_interrupts(|| QUEUE.lock().pop())?;
+        let mut keyboard_state = KEYBOARD_STATE.lock();
+        if let Some(event) = keyboard::decode_event(&mut keyboard_state, scancode) {
+            keyboard::handle_modifier_key(&mut keyboard_state, event);
+            return Some(event);
+        }
+    }
+}
+
+/// 取出下一次按键 (忽略释放事件和修饰键)，没有按键时立即返回 None
+pub fn poll_key() -> Option<KeyEvent> {
+    while let Some(event) = next_event() {
+        if event.pressed && !keyboard::is_modifier(event) {
+            return Some(event);
+        }
+    }
+    None
+}
+
+/// 阻塞等待一次按键 (忽略释放事件和修饰键)，按 Esc 请求中止时返回 None
+/// 必须在中断开启时调用
+pub fn wait_key() -> Option<KeyEvent> {
+    // 提示的最后一个单词可能还在自动换行的缓冲区中，等待之前先显示出来
+    crate::with_writer(Writer::prepare_for_input);
+    loop {
+        if let Some(event) = poll_key() {
+            return Some(event);
+        }
+        if abort_requested() {
+            return None;
+        }
+
+        // 与主循环相同：关中断后再检查，避免错过检查与 hlt 之间到达的按键
+        interrupts::disable();
+        if has_pending() || abort_requested() {
+            interrupts::enable();
+        } else {
+            interrupts::enable_and_hlt();
+        }
+    }
+}
+
+/// 当前的 Shift 和 Caps Lock 状态
+pub fn shift_and_caps() -> (bool, bool) {
+    let keyboard_state = KEYBOARD_STATE.lock();
+    (keyboard_state.shift_pressed, keyboard_state.caps_lock)
+}
+
+/// Ctrl 和 Alt 是否同时按住
+fn ctrl_alt_pressed() -> bool {
+    let keyboard_state = KEYBOARD_STATE.lock();
+    keyboard_state.ctrl_pressed && keyboard_state.alt_pressed
+}
+
+/// Ctrl+Alt+D: 把影子网格记录的屏幕文字输出到串口，前后加标记行，方便从串口日志中截取
+fn dump_screen_to_serial() {
+    let Some((rows, columns)) = crate::with_writer(|writer| (writer.text_rows(), writer.text_columns())) else {
+        return;
+    };
+    let mut text = [' '; MAX_COLUMNS];
+    let columns = columns.min(MAX_COLUMNS);
+    serial_println!("----- BEGIN SCREEN DUMP ({}x{}) -----", columns, rows);
+    for row in 0..rows {
+        // 每行复制出来后再输出，不在持有影子网格锁时访问串口
+        let len = SHADOW.lock().row_text(row, &mut text[..columns]);
+        for &ch in &text[..len] {
+            serial_print!("{}", ch);
+        }
+        serial_println!();
+    }
+    serial_println!("----- END SCREEN DUMP -----");
+}
+
+/// 把普通字符交给组合键状态机 (见 keyboard::compose_char)
+fn compose(ch: char) -> Compose {
+    keyboard::compose_char(&mut KEYBOARD_STATE.lock(), ch)
+}
+
+/// 取消进行中的组合序列 (组合期间按了回车、退格等控制键)
+fn cancel_compose() {
+    KEYBOARD_STATE.lock().compose = ComposeState::Idle;
+}
+
+/// 是否有中止请求 (长时间运行的命令应定期检查)
+pub fn abort_requested() -> bool {
+    ABORT_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// 清除中止请求 (每条命令开始前调用)
+pub fn clear_abort() {
+    ABORT_REQUESTED.store(false, Ordering::SeqCst);
+}
+
+/// 把一个按键事件交给 Shell (Shell 只处理按下事件)
+fn handle_event(event: KeyEvent) {
+    if !event.pressed {
+        return;
+    }
+
+    let scancode = match event.key {
+        Key::Extended(code) => {
+            match keyboard::extended_scancode_to_key(code) {
+                Some(ExtendedKey::PageUp) => handle_page_up(),
+                Some(ExtendedKey::PageDown) => handle_page_down(),
+                None => {} // 暂不支持的扩展键
+            }
+            return;
+        },
+        Key::Normal(code) => code,
+        // Pause 键暂时没有用途，直接忽略
+        Key::Pause => return,
+    };
+
+    // 调试热键，不交给 Shell
+    if ctrl_alt_pressed() && keyboard::scancode_to_char(scancode, false, false) == Some('d') {
+        dump_screen_to_serial();
+        return;
+    }
+
+    // 执行命令前释放键盘状态锁，命令本身可能需要读取按键
+    let (shift_pressed, caps_lock) = shift_and_caps();
+
+    // 修饰键的状态已经在 next_event 中更新
+    if keyboard::is_modifier(event) {
+        // 如果是 Caps Lock，显示状态变化
+        if scancode == keyboard::CAPS_LOCK {
+            highlight_text(Color::YELLOW);
+            if caps_lock {
+                print!(" [CAPS ON] ");
+            } else {
+                print!(" [CAPS OFF] ");
+            }
+            reset_text_color();
+            // 同步键盘上的指示灯 (键盘的 ACK 由中断处理程序截下，不会变成按键)
+            let leds = if caps_lock { ps2::LED_CAPS_LOCK } else { 0 };
+            if let Err(error) = ps2::set_leds(leds) {
+                crate::log_warn!("Failed to update keyboard LEDs: {}", error.describe());
+            }
+        }
+        return;
+    }
+
+    // 尝试转换为字符，考虑 Shift 和 Caps Lock 状态
+    if let Some(ch) = keyboard::scancode_to_char(scancode, shift_pressed, caps_lock) {
+        if ch.is_control() {
+            cancel_compose();
+        }
+        // 处理特殊字符
+        match ch {
+            '\x08' => { // 退格键
+                // 检查 Shell 是否允许退格
+                if with_shell(|shell| shell.can_backspace()).unwrap_or(false) {
+                    // 发送给 Shell 处理
+                    handle_shell_char('\x08');
+                    // 同时在屏幕上执行退格
+                    handle_backspace();
+                }
+                // 如果不能退格，忽略这个按键
+            },
+            '\n' => { // 回车键
+                // 发送给 Shell 处理命令
+                handle_shell_char('\n');
+            },
+            '\t' => { // Tab 键
+                // 由 Shell 插入空格并负责回显
+                handle_shell_char('\t');
+            },
+            ch => { // 普通字符
+                // 组合键序列中的字符先由组合表处理，未知序列只响铃
+                let ch = match compose(ch) {
+                    Compose::Char(ch) => ch,
+                    Compose::Pending => return,
+                    Compose::Unknown => {
+                        print!("\x07");
+                        return;
+                    },
+                };
+                // 发送给 Shell 缓冲区，被拒绝的字符 (缓冲区已满) 不回显
+                if !handle_shell_char(ch) {
+                    return;
+                }
+
+                // 在屏幕上显示字符（带颜色）
+                if caps_lock && ch.is_ascii_alphabetic() {
+                    highlight_text(Color::RED);   // Caps Lock 字母用红色
+                } else if shift_pressed {
+                    highlight_text(Color::BLUE);  // Shift + 字符用蓝色
+                } else {
+                    highlight_text(Color::GREEN); // 普通字符用绿色
+                }
+                print!("{}", ch);
+                reset_text_color();
+            }
+        }
+    } else {
+        // 未知键，显示扫描码
+        highlight_text(Color::YELLOW);
+        print!("[{}]", scancode);
+        reset_text_color();
+    }
+}
@@ -1,12 +1,50 @@
 // kernel/src/interrupts.rs
 
+use core::sync::atomic::{AtomicU64, Ordering};
 use x86_64::structures::idt::{InterruptDescriptorTable, InterruptStackFrame};
 use lazy_static::lazy_static;
-use spin::Mutex;
 use crate::pic::{self, KEYBOARD_INTERRUPT_ID, TIMER_INTERRUPT_ID}; // ✨ 新增 TIMER_INTERRUPT_ID
-use crate::keyboard::{self, KeyboardState};
-use crate::{print, println, set_text_color, handle_backspace, handle_shell_char, SHELL};
-use crate::writer::Color;
+use crate::{log_info, println};
+
+/// PIC 的 IRQ 线数
+pub const IRQ_COUNT: usize = 16;
+
+/// 各 IRQ 线的常见用途 (PC 标准分配)
+pub const IRQ_NAMES: [&str; IRQ_COUNT] = [
+    "Timer", "Keyboard", "Cascade", "COM2", "COM1", "LPT2", "Floppy", "LPT1",
+    "RTC", "Free", "Free", "Free", "PS/2 mouse", "FPU", "Primary ATA", "Secondary ATA",
+];
+
+/// 每条 IRQ 线的中断次数 (不含伪中断)
+static IRQ_COUNTS: [AtomicU64; IRQ_COUNT] = [const { AtomicU64::new(0) }; IRQ_COUNT];
+/// IRQ7 / IRQ15 上的伪中断次数
+static SPURIOUS_COUNT: AtomicU64 = AtomicU64::new(0);
+/// 没有驱动处理的中断次数
+static UNHANDLED_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// 为没有驱动的 IRQ 生成处理程序: 计数、识别伪中断并发送 EOI
+macro_rules! unhandled_irq_handler {
+    ($name:ident, $irq:expr) => {
+        extern "x86-interrupt" fn $name(_stack_frame: InterruptStackFrame) {
+            handle_unhandled_irq($irq);
+        }
+    };
+}
+
+unhandled_irq_handler!(irq2_handler, 2);
+unhandled_irq_handler!(irq3_handler, 3);
+unhandled_irq_handler!(irq4_handler, 4);
+unhandled_irq_handler!(irq5_handler, 5);
+unhandled_irq_handler!(irq6_handler, 6);
+unhandled_irq_handler!(irq7_handler, 7);
+unhandled_irq_handler!(irq8_handler, 8);
+unhandled_irq_handler!(irq9_handler, 9);
+unhandled_irq_handler!(irq10_handler, 10);
+unhandled_irq_handler!(irq11_handler, 11);
+unhandled_irq_handler!(irq12_handler, 12);
+unhandled_irq_handler!(irq13_handler, 13);
+unhandled_irq_handler!(irq14_handler, 14);
+unhandled_irq_handler!(irq15_handler, 15);
 
 lazy_static! {
     static ref IDT: InterruptDescriptorTable = {
@@ -18,33 +56,41 @@ lazy_static! {
         // 硬件中断处理
         idt[KEYBOARD_INTERRUPT_ID as usize].set_handler_fn(keyboard_interrupt_handler);
         idt[TIMER_INTERRUPT_ID as usize].set_handler_fn(timer_interrupt_handler); // ✨ 新增定时器中断
+
+        // 其余 IRQ 线只计数，伪中断 (IRQ7 / IRQ15) 也会落在这里
+        let unhandled: [(u8, extern "x86-interrupt" fn(InterruptStackFrame)); 14] = [
+            (2, irq2_handler), (3, irq3_handler), (4, irq4_handler), (5, irq5_handler),
+            (6, irq6_handler), (7, irq7_handler), (8, irq8_handler), (9, irq9_handler),
+            (10, irq10_handler), (11, irq11_handler), (12, irq12_handler), (13, irq13_handler),
+            (14, irq14_handler), (15, irq15_handler),
+        ];
+        for (irq, handler) in unhandled {
+            idt[pic::irq_vector(irq) as usize].set_handler_fn(handler);
+        }
         
         idt
     };
-    
-    // 全局键盘状态
-    static ref KEYBOARD_STATE: Mutex<KeyboardState> = Mutex::new(KeyboardState::new());
 }
 
 /// 初始化中断系统
 pub fn init() {
-    println!("Setting up IDT...");
+    log_info!("Setting up IDT...");
     IDT.load();
     
-    println!("Initializing PIC...");
+    log_info!("Initializing PIC...");
     pic::init();
     
-    println!("Enabling keyboard interrupt...");
+    log_info!("Enabling keyboard interrupt...");
     pic::enable_keyboard();
     
     // ✨ 启用定时器中断
-    println!("Enabling timer interrupt...");
+    log_info!("Enabling timer interrupt...");
     pic::enable_timer();
     
-    println!("Enabling interrupts...");
+    log_info!("Enabling interrupts...");
     x86_64::instructions::interrupts::enable();
     
-    println!("Interrupt system ready!");
+    log_info!("Interrupt system ready!");
 }
 
 /// 断点异常处理程序
@@ -54,92 +100,64 @@ extern "x86-interrupt" fn breakpoint_handler(_stack_frame: InterruptStackFrame)
 
 /// ✨ 定时器中断处理程序 - 新增
 extern "x86-interrupt" fn timer_interrupt_handler(_stack_frame: InterruptStackFrame) {
+    count_irq(0);
+
     // 更新系统时间
     crate::time::tick();
+
+    // 软件按键重复
+    crate::input::repeat_tick(crate::time::get_tick_count());
     
     // 发送中断结束信号
     pic::end_of_interrupt(TIMER_INTERRUPT_ID);
 }
 
-/// 键盘中断处理程序 - Shell 模式
+/// 键盘中断处理程序 - 只读取扫描码并入队，解码在主循环中进行
 extern "x86-interrupt" fn keyboard_interrupt_handler(_stack_frame: InterruptStackFrame) {
     use x86_64::instructions::port::Port;
+    count_irq(1);
     
     // 从键盘控制器读取扫描码
     let mut port = Port::new(0x60);
     let scancode: u8 = unsafe { port.read() };
     
-    // 获取键盘状态
-    let mut keyboard_state = KEYBOARD_STATE.lock();
-    
-    // 处理修饰键 (Shift, Ctrl, Alt, Caps Lock)
-    if keyboard::handle_modifier_key(&mut keyboard_state, scancode) {
-        // 如果是 Caps Lock，显示状态变化
-        if scancode == 0x3A { // Caps Lock 键
-            set_text_color(Color::YELLOW, Color::BLACK);
-            if keyboard_state.caps_lock {
-                print!(" [CAPS ON] ");
-            } else {
-                print!(" [CAPS OFF] ");
-            }
-            set_text_color(Color::WHITE, Color::BLACK);
-        }
-        
-        // 修饰键处理完成，发送中断结束信号并返回
-        pic::end_of_interrupt(KEYBOARD_INTERRUPT_ID);
-        return;
-    }
-    
-    // 只处理按下的键（忽略释放事件）
-    if scancode < 0x80 {
-        // 尝试转换为字符，考虑 Shift 和 Caps Lock 状态
-        if let Some(ch) = keyboard::scancode_to_char(scancode, keyboard_state.shift_pressed, keyboard_state.caps_lock) {
-            // 处理特殊字符
-            match ch {
-                '\x08' => { // 退格键
-                    // 检查 Shell 是否允许退格
-                    if SHELL.lock().can_backspace() {
-                        // 发送给 Shell 处理
-                        handle_shell_char('\x08');
-                        // 同时在屏幕上执行退格
-                        handle_backspace();
-                    }
-                    // 如果不能退格，忽略这个按键
-                },
-                '\n' => { // 回车键
-                    // 发送给 Shell 处理命令
-                    handle_shell_char('\n');
-                },
-                '\t' => { // Tab 键
-                    // Tab 仍然直接输出，不加入缓冲区
-                    set_text_color(Color::YELLOW, Color::BLACK);
-                    print!(">   "); // > + 3 个空格 = 4 个字符宽度的缩进
-                    set_text_color(Color::WHITE, Color::BLACK);
-                },
-                ch => { // 普通字符
-                    // 发送给 Shell 缓冲区
-                    handle_shell_char(ch);
-                    
-                    // 在屏幕上显示字符（带颜色）
-                    if keyboard_state.caps_lock && ch.is_ascii_alphabetic() {
-                        set_text_color(Color::RED, Color::BLACK);   // Caps Lock 字母用红色
-                    } else if keyboard_state.shift_pressed {
-                        set_text_color(Color::BLUE, Color::BLACK);  // Shift + 字符用蓝色
-                    } else {
-                        set_text_color(Color::GREEN, Color::BLACK); // 普通字符用绿色
-                    }
-                    print!("{}", ch);
-                    set_text_color(Color::WHITE, Color::BLACK);
-                }
-            }
-        } else {
-            // 未知键，显示扫描码
-            set_text_color(Color::YELLOW, Color::BLACK);
-            print!("[{}]", scancode);
-            set_text_color(Color::WHITE, Color::BLACK);
-        }
+    // 键盘对命令的应答 (ACK / 重发) 不是按键
+    if !crate::ps2::take_response(scancode) {
+        crate::input::enqueue_scancode(scancode);
     }
     
     // 发送中断结束信号
     pic::end_of_interrupt(KEYBOARD_INTERRUPT_ID);
-}
\ No newline at end of file
+}
+
+/// 没有驱动的 IRQ: 伪中断单独计数，其它中断记为未处理
+fn handle_unhandled_irq(irq: u8) {
+    if pic::is_spurious(irq) {
+        SPURIOUS_COUNT.fetch_add(1, Ordering::Relaxed);
+        pic::acknowledge_spurious(irq);
+        return;
+    }
+    count_irq(irq);
+    UNHANDLED_COUNT.fetch_add(1, Ordering::Relaxed);
+    pic::end_of_interrupt(pic::irq_vector(irq));
+}
+
+/// 在中断处理程序中调用: IRQ 计数加一
+fn count_irq(irq: u8) {
+    IRQ_COUNTS[irq as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+/// IRQ 线的中断次数
+pub fn irq_count(irq: usize) -> u64 {
+    IRQ_COUNTS[irq].load(Ordering::Relaxed)
+}
+
+/// 伪中断次数
+pub fn spurious_count() -> u64 {
+    SPURIOUS_COUNT.load(Ordering::Relaxed)
+}
+
+/// 未处理的中断次数
+pub fn unhandled_count() -> u64 {
+    UNHANDLED_COUNT.load(Ordering::Relaxed)
+}
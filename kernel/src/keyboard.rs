@@ -3,9 +3,13 @@
 /// 鍵盤狀態 - 跟蹤修飾鍵狀態
 pub struct KeyboardState {
     pub shift_pressed: bool,
-    pub ctrl_pressed: bool,  // 為將來擴展預留
-    pub alt_pressed: bool,   // 為將來擴展預留
+    pub ctrl_pressed: bool,  // 左 Ctrl 或右 Ctrl
+    pub alt_pressed: bool,   // 左 Alt 或右 Alt
+    pub altgr_pressed: bool, // 右 Alt (AltGr)，供需要第三層字符的佈局使用
     pub caps_lock: bool,     // Caps Lock 狀態
+    pub extended: bool,      // 上一個字節是 0xE0 擴展前綴
+    pub compose: ComposeState, // 組合鍵序列進行到哪一步
+    pub pause_progress: usize, // 已收到的 Pause 序列字節數，0 表示不在序列中
 }
 
 impl KeyboardState {
@@ -14,27 +18,240 @@ impl KeyboardState {
             shift_pressed: false,
             ctrl_pressed: false,
             alt_pressed: false,
+            altgr_pressed: false,
             caps_lock: false,
+            extended: false,
+            compose: ComposeState::Idle,
+            pause_progress: 0,
         }
     }
 }
 
-/// 處理修飾鍵的按下和釋放
-pub fn handle_modifier_key(state: &mut KeyboardState, scancode: u8) -> bool {
+/// 組合鍵序列的狀態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComposeState {
+    /// 沒有進行中的組合
+    Idle,
+    /// 已按下組合鍵，等待第一個字符
+    Armed,
+    /// 已收到第一個字符，等待第二個
+    First(char),
+}
+
+/// 組合鍵 (右 Alt)：按下後再輸入兩個字符，按組合表合成一個字符
+pub const COMPOSE_KEY: Key = Key::Extended(0x38);
+
+/// 組合表: (第一個字符, 第二個字符, 結果)，兩個字符的順序可以互換
+/// 結果必須在字體的附加字形表中
+pub const COMPOSE_TABLE: [(char, char, char); 22] = [
+    ('\'', 'a', 'á'), ('`', 'a', 'à'), ('^', 'a', 'â'), ('"', 'a', 'ä'),
+    ('\'', 'e', 'é'), ('`', 'e', 'è'), ('^', 'e', 'ê'), ('"', 'e', 'ë'),
+    ('\'', 'i', 'í'), ('`', 'i', 'ì'), ('^', 'i', 'î'), ('"', 'i', 'ï'),
+    ('\'', 'o', 'ó'), ('`', 'o', 'ò'), ('^', 'o', 'ô'), ('"', 'o', 'ö'),
+    ('\'', 'u', 'ú'), ('`', 'u', 'ù'), ('^', 'u', 'û'), ('"', 'u', 'ü'),
+    ('~', 'n', 'ñ'), (',', 'c', 'ç'),
+];
+
+/// 組合一個字符序列的結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compose {
+    /// 直接輸出這個字符 (沒有進行中的組合，或組合成功)
+    Char(char),
+    /// 字符被組合序列吞掉，等待下一個字符
+    Pending,
+    /// 組合表中沒有這個序列，什麼也不輸出
+    Unknown,
+}
+
+/// 在組合表中查找兩個字符 (不分先後)
+pub fn compose_pair(first: char, second: char) -> Option<char> {
+    COMPOSE_TABLE
+        .iter()
+        .find(|&&(a, b, _)| (a, b) == (first, second) || (b, a) == (first, second))
+        .map(|&(_, _, result)| result)
+}
+
+/// 把一個輸入字符送入組合狀態機
+pub fn compose_char(state: &mut KeyboardState, ch: char) -> Compose {
+    match state.compose {
+        ComposeState::Idle => Compose::Char(ch),
+        ComposeState::Armed => {
+            state.compose = ComposeState::First(ch);
+            Compose::Pending
+        },
+        ComposeState::First(first) => {
+            state.compose = ComposeState::Idle;
+            match compose_pair(first, ch) {
+                Some(result) => Compose::Char(result),
+                None => Compose::Unknown,
+            }
+        },
+    }
+}
+
+/// 擴展掃描碼前綴
+pub const EXTENDED_PREFIX: u8 = 0xE0;
+
+/// Pause/Break 鍵序列的前綴
+pub const PAUSE_PREFIX: u8 = 0xE1;
+
+/// Pause/Break 鍵按下時發送的完整序列 (掃描碼集 1)
+/// E1 1D 45 是按下，E1 9D C5 是緊接著的釋放，鬆開按鍵時不再發送任何字節，也不會自動重複
+pub const PAUSE_SEQUENCE: [u8; 6] = [PAUSE_PREFIX, 0x1D, 0x45, PAUSE_PREFIX, 0x9D, 0xC5];
+
+/// 一個按鍵事件最多對應的掃描碼字節數 (Pause 序列)
+pub const MAX_SEQUENCE_LEN: usize = PAUSE_SEQUENCE.len();
+
+/// 釋放碼 (break code) 的標誌位：釋放碼 = 按下碼 | 0x80
+pub const BREAK_BIT: u8 = 0x80;
+
+/// 是否為按鍵釋放碼
+pub fn is_break_code(scancode: u8) -> bool {
+    scancode & BREAK_BIT != 0
+}
+
+/// 去掉釋放標誌，得到對應的按下碼
+pub fn make_code(scancode: u8) -> u8 {
+    scancode & !BREAK_BIT
+}
+
+/// 按住時是否應該自動重複 (產生字符的鍵才重複，修飾鍵不重複)
+pub fn is_repeatable(make_code: u8) -> bool {
+    scancode_to_char(make_code, false, false).is_some()
+}
+
+/// Caps Lock 鍵的按下碼
+pub const CAPS_LOCK: u8 = 0x3A;
+
+/// 一個物理按鍵，以按下碼表示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// 普通鍵
+    Normal(u8),
+    /// 0xE0 前綴之後的擴展鍵
+    Extended(u8),
+    /// Pause/Break 鍵 (只有按下事件)
+    Pause,
+}
+
+/// 按鍵事件 (按下或釋放)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub pressed: bool,
+}
+
+/// 把一個掃描碼字節解碼為按鍵事件
+/// 0xE0 前綴本身不產生事件，只記錄在狀態中，下一個字節按擴展鍵解碼
+/// 0xE1 開始的 Pause 序列 (見 PAUSE_SEQUENCE) 全部收齊後只產生一個 Key::Pause 按下事件；
+/// 序列中途出現不符的字節時放棄該序列，這個字節按普通掃描碼解碼
+pub fn decode_event(state: &mut KeyboardState, scancode: u8) -> Option<KeyEvent> {
+    if state.pause_progress > 0 {
+        if scancode == PAUSE_SEQUENCE[state.pause_progress] {
+            state.pause_progress += 1;
+            if state.pause_progress < PAUSE_SEQUENCE.len() {
+                return None;
+            }
+            state.pause_progress = 0;
+            return Some(KeyEvent { key: Key::Pause, pressed: true });
+        }
+        state.pause_progress = 0;
+    }
+    if scancode == PAUSE_PREFIX {
+        state.pause_progress = 1;
+        return None;
+    }
+
+    if scancode == EXTENDED_PREFIX {
+        state.extended = true;
+        return None;
+    }
+
+    let code = make_code(scancode);
+    let key = if state.extended {
+        state.extended = false;
+        Key::Extended(code)
+    } else {
+        Key::Normal(code)
+    };
+    Some(KeyEvent { key, pressed: !is_break_code(scancode) })
+}
+
+/// 把按鍵事件還原為原始掃描碼字節 (擴展鍵帶 0xE0 前綴)，返回字節和有效長度
+pub fn encode_event(event: KeyEvent) -> ([u8; MAX_SEQUENCE_LEN], usize) {
+    let mut bytes = [0; MAX_SEQUENCE_LEN];
+    let (code, extended) = match event.key {
+        Key::Normal(code) => (code, false),
+        Key::Extended(code) => (code, true),
+        Key::Pause => return (PAUSE_SEQUENCE, PAUSE_SEQUENCE.len()),
+    };
+    let code = if event.pressed { code } else { code | BREAK_BIT };
+    if extended {
+        bytes[..2].copy_from_slice(&[EXTENDED_PREFIX, code]);
+        (bytes, 2)
+    } else {
+        bytes[0] = code;
+        (bytes, 1)
+    }
+}
+
+/// 擴展鍵 (0xE0 前綴之後的掃描碼)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtendedKey {
+    PageUp,
+    PageDown,
+}
+
+/// 將 0xE0 之後的掃描碼轉換為擴展鍵 (只處理按下)
+pub fn extended_scancode_to_key(scancode: u8) -> Option<ExtendedKey> {
     match scancode {
-        // Shift 鍵按下
-        0x2A | 0x36 => { // 左 Shift (0x2A) 或右 Shift (0x36)
-            state.shift_pressed = true;
+        0x49 => Some(ExtendedKey::PageUp),
+        0x51 => Some(ExtendedKey::PageDown),
+        _ => None,
+    }
+}
+
+/// 事件是否屬於修飾鍵 (Shift, Ctrl, Alt, Caps Lock)
+pub fn is_modifier(event: KeyEvent) -> bool {
+    matches!(
+        event.key,
+        Key::Normal(0x2A | 0x36 | 0x1D | 0x38 | CAPS_LOCK) | Key::Extended(0x1D | 0x38)
+    )
+}
+
+/// 處理修飾鍵的按下和釋放，返回事件是否屬於修飾鍵
+pub fn handle_modifier_key(state: &mut KeyboardState, event: KeyEvent) -> bool {
+    // 按下組合鍵時開始一個新的組合序列 (組合鍵同時也是修飾鍵)
+    if event.key == COMPOSE_KEY && event.pressed {
+        state.compose = ComposeState::Armed;
+    }
+    match event.key {
+        // 左 Shift (0x2A) 或右 Shift (0x36)
+        Key::Normal(0x2A) | Key::Normal(0x36) => {
+            state.shift_pressed = event.pressed;
             true // 表示這是修飾鍵
         },
-        // Shift 鍵釋放
-        0xAA | 0xB6 => { // 左 Shift 釋放 (0x2A + 0x80) 或右 Shift 釋放 (0x36 + 0x80)
-            state.shift_pressed = false;
+        // 左 Ctrl (0x1D) 或右 Ctrl (E0 1D)
+        Key::Normal(0x1D) | Key::Extended(0x1D) => {
+            state.ctrl_pressed = event.pressed;
             true
         },
-        // Caps Lock 按下（切換狀態）
-        0x3A => { // Caps Lock 鍵
-            state.caps_lock = !state.caps_lock; // 切換 Caps Lock 狀態
+        // 左 Alt (0x38)
+        Key::Normal(0x38) => {
+            state.alt_pressed = event.pressed;
+            true
+        },
+        // 右 Alt / AltGr (E0 38)，擴展前綴已由 decode_event 處理
+        Key::Extended(0x38) => {
+            state.alt_pressed = event.pressed;
+            state.altgr_pressed = event.pressed;
+            true
+        },
+        // Caps Lock 按下時切換狀態，釋放時忽略
+        Key::Normal(CAPS_LOCK) => {
+            if event.pressed {
+                state.caps_lock = !state.caps_lock;
+            }
             true
         },
         _ => false // 不是修飾鍵
@@ -145,4 +362,128 @@ fn get_pipe_char() -> char {
 /// 返回反斜杠字符
 fn get_backslash_char() -> char {
     '\\'
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(state: &mut KeyboardState, key: Key) {
+        handle_modifier_key(state, KeyEvent { key, pressed: true });
+    }
+
+    #[test]
+    fn compose_sequence_produces_accented_char() {
+        let mut state = KeyboardState::new();
+        assert_eq!(compose_char(&mut state, 'e'), Compose::Char('e'));
+
+        press(&mut state, COMPOSE_KEY);
+        assert_eq!(compose_char(&mut state, '\''), Compose::Pending);
+        assert_eq!(compose_char(&mut state, 'e'), Compose::Char('é'));
+        // 組合完成後恢復普通輸入
+        assert_eq!(compose_char(&mut state, 'e'), Compose::Char('e'));
+
+        // 兩個字符順序可以互換
+        press(&mut state, COMPOSE_KEY);
+        compose_char(&mut state, 'n');
+        assert_eq!(compose_char(&mut state, '~'), Compose::Char('ñ'));
+    }
+
+    #[test]
+    fn pause_sequence_decodes_to_single_event() {
+        let mut state = KeyboardState::new();
+        let events: [Option<KeyEvent>; 6] = PAUSE_SEQUENCE.map(|byte| decode_event(&mut state, byte));
+        assert_eq!(events[..5], [None; 5]);
+        assert_eq!(events[5], Some(KeyEvent { key: Key::Pause, pressed: true }));
+        assert_eq!(encode_event(events[5].unwrap()), (PAUSE_SEQUENCE, 6));
+
+        // 之後的字節照常解碼
+        assert_eq!(decode_event(&mut state, 0x1E), Some(KeyEvent { key: Key::Normal(0x1E), pressed: true }));
+    }
+
+    #[test]
+    fn broken_pause_sequence_falls_back_to_normal_decoding() {
+        let mut state = KeyboardState::new();
+        assert_eq!(decode_event(&mut state, PAUSE_PREFIX), None);
+        assert_eq!(decode_event(&mut state, 0x1D), None);
+        assert_eq!(decode_event(&mut state, 0x1E), Some(KeyEvent { key: Key::Normal(0x1E), pressed: true }));
+        assert_eq!(state.pause_progress, 0);
+    }
+
+    #[test]
+    fn unknown_compose_sequence_emits_nothing() {
+        let mut state = KeyboardState::new();
+        press(&mut state, COMPOSE_KEY);
+        assert_eq!(compose_char(&mut state, 'x'), Compose::Pending);
+        assert_eq!(compose_char(&mut state, 'y'), Compose::Unknown);
+        assert_eq!(state.compose, ComposeState::Idle);
+    }
+
+    #[test]
+    fn letter_case_is_caps_lock_xor_shift() {
+        assert_eq!(letter_case('a', 'A', false, false), 'a');
+        assert_eq!(letter_case('a', 'A', true, false), 'A');
+        assert_eq!(letter_case('a', 'A', false, true), 'A');
+        assert_eq!(letter_case('a', 'A', true, true), 'a');
+    }
+
+    #[test]
+    fn letters_follow_shift_and_caps_lock() {
+        // 每一行字母的第一個和最後一個鍵
+        for (scancode, lower, upper) in [(0x10, 'q', 'Q'), (0x19, 'p', 'P'), (0x1E, 'a', 'A'), (0x26, 'l', 'L'), (0x2C, 'z', 'Z'), (0x32, 'm', 'M')] {
+            assert_eq!(scancode_to_char(scancode, false, false), Some(lower));
+            assert_eq!(scancode_to_char(scancode, true, false), Some(upper));
+            assert_eq!(scancode_to_char(scancode, false, true), Some(upper));
+            assert_eq!(scancode_to_char(scancode, true, true), Some(lower));
+        }
+    }
+
+    #[test]
+    fn number_row_shifts_to_symbols() {
+        let digits = "1234567890";
+        let symbols = "!@#$%^&*()";
+        for ((scancode, digit), symbol) in (0x02..=0x0B).zip(digits.chars()).zip(symbols.chars()) {
+            assert_eq!(scancode_to_char(scancode, false, false), Some(digit));
+            assert_eq!(scancode_to_char(scancode, true, false), Some(symbol));
+            // Caps Lock 不影響數字行
+            assert_eq!(scancode_to_char(scancode, false, true), Some(digit));
+            assert_eq!(scancode_to_char(scancode, true, true), Some(symbol));
+        }
+    }
+
+    #[test]
+    fn punctuation_shifts_ignore_caps_lock() {
+        let keys = [
+            (0x0C, '-', '_'),
+            (0x0D, '=', '+'),
+            (0x1A, '[', '{'),
+            (0x1B, ']', '}'),
+            (0x27, ';', ':'),
+            (0x28, '\'', '"'),
+            (0x29, '`', '~'),
+            (0x2B, '\\', '|'),
+            (0x33, ',', '<'),
+            (0x34, '.', '>'),
+            (0x35, '/', '?'),
+        ];
+        for (scancode, plain, shifted) in keys {
+            assert_eq!(scancode_to_char(scancode, false, false), Some(plain));
+            assert_eq!(scancode_to_char(scancode, true, false), Some(shifted));
+            assert_eq!(scancode_to_char(scancode, false, true), Some(plain));
+            assert_eq!(scancode_to_char(scancode, true, true), Some(shifted));
+        }
+    }
+
+    #[test]
+    fn special_keys_and_unknown_scancodes() {
+        for shift in [false, true] {
+            assert_eq!(scancode_to_char(0x39, shift, false), Some(' '));
+            assert_eq!(scancode_to_char(0x1C, shift, false), Some('\n'));
+            assert_eq!(scancode_to_char(0x0E, shift, false), Some('\x08'));
+            assert_eq!(scancode_to_char(0x0F, shift, false), Some('\t'));
+        }
+        // Esc、修飾鍵和斷開碼不產生字符
+        assert_eq!(scancode_to_char(0x01, false, false), None);
+        assert_eq!(scancode_to_char(0x2A, false, false), None);
+        assert_eq!(scancode_to_char(0x9E, false, false), None);
+    }
+}
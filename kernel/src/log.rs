@@ -0,0 +1,92 @@
+// kernel/src/log.rs
+// 日志 - 带运行时间戳和级别标签，输出到串口，不分配内存
+
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// 日志级别 (从低到高)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum Level {
+    Info = 0,
+    Warn = 1,
+    Error = 2,
+}
+
+impl Level {
+    /// 级别名称
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+
+    /// 日志中显示的标签 (等宽)
+    fn tag(self) -> &'static str {
+        match self {
+            Level::Info => "INFO ",
+            Level::Warn => "WARN ",
+            Level::Error => "ERROR",
+        }
+    }
+
+    fn from_u8(value: u8) -> Level {
+        match value {
+            0 => Level::Info,
+            1 => Level::Warn,
+            _ => Level::Error,
+        }
+    }
+}
+
+/// 默认最低级别: release 构建中不输出 info
+const DEFAULT_MIN_LEVEL: Level = if cfg!(debug_assertions) { Level::Info } else { Level::Warn };
+
+/// 当前最低级别，低于它的日志被丢弃
+static MIN_LEVEL: AtomicU8 = AtomicU8::new(DEFAULT_MIN_LEVEL as u8);
+
+/// 设置最低日志级别
+pub fn set_min_level(level: Level) {
+    MIN_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// 当前最低日志级别
+pub fn min_level() -> Level {
+    Level::from_u8(MIN_LEVEL.load(Ordering::Relaxed))
+}
+
+/// 日志宏的内部实现
+#[doc(hidden)]
+pub fn _log(level: Level, args: fmt::Arguments) {
+    if level < min_level() {
+        return;
+    }
+    let uptime_ms = crate::time::get_uptime_ms();
+    crate::serial_println!(
+        "[{:>3}.{:03}] {} {}",
+        uptime_ms / 1000,
+        uptime_ms % 1000,
+        level.tag(),
+        args
+    );
+}
+
+/// log_info! 宏
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => ($crate::log::_log($crate::log::Level::Info, format_args!($($arg)*)));
+}
+
+/// log_warn! 宏
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => ($crate::log::_log($crate::log::Level::Warn, format_args!($($arg)*)));
+}
+
+/// log_error! 宏
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => ($crate::log::_log($crate::log::Level::Error, format_args!($($arg)*)));
+}
@@ -1,38 +1,143 @@
-#![no_std]
-#![no_main]
+// 在主机上运行 `cargo test` 时按普通的 std 程序编译，由测试框架提供 main 和恐慌处理
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
+#![cfg_attr(test, allow(dead_code))]
 #![feature(abi_x86_interrupt)] 
 
-use bootloader_api::{entry_point, BootInfo};
+use bootloader_api::config::{BootloaderConfig, Mapping};
+use bootloader_api::info::FrameBufferInfo;
+use bootloader_api::BootInfo;
 use spin::Mutex;
 
+mod serial;
+mod log;
 mod font;
+mod bigfont;
+mod ansi;
 mod writer;
+mod scrollback;
+mod shadow;
 mod interrupts; 
 mod pic;
 mod keyboard;
+mod ps2;
 mod shell;
 mod pit;   // ✨ 新增 PIT 模块
 mod time;  // ✨ 新增 时间模块
+mod stopwatch;
+mod format;
+mod cpu;
+mod alias;
+mod history;
+mod vars;
+mod input;
+mod deferred;
+mod event;
+mod pager;
+mod memory;
+mod theme;
+mod rtc;
+mod ata;
+mod speaker;
 
 use writer::{Writer, Color};
 use shell::Shell;
+use event::WorkFlags;
 
-entry_point!(kernel_main);
+/// 引导程序配置: 映射整个物理内存，memtest 通过这个映射访问物理地址
+pub static BOOTLOADER_CONFIG: BootloaderConfig = {
+    let mut config = BootloaderConfig::new_default();
+    config.mappings.physical_memory = Some(Mapping::Dynamic);
+    config
+};
 
-// 全局 Writer 实例
+#[cfg(not(test))]
+bootloader_api::entry_point!(kernel_main, config = &BOOTLOADER_CONFIG);
+
+/// 有帧缓冲区时是否同时把屏幕输出复制到串口
+/// 没有帧缓冲区时 (例如 QEMU -nographic) 输出总是走串口
+const MIRROR_TO_SERIAL: bool = false;
+
+/// 文字光标闪烁的间隔 (毫秒)
+const CURSOR_BLINK_MS: u64 = 500;
+
+// 全局 Writer 实例 (通过 with_writer 关中断访问；锁的顺序见 SHELL)
 pub static WRITER: Mutex<Option<Writer>> = Mutex::new(None);
 
+/// 双缓冲的后备缓冲区，按 1920x1080、每像素 4 字节预留 (约 8 MiB)
+/// 只有启用 `double-buffer` feature 时才会占用内存
+#[cfg(feature = "double-buffer")]
+static mut BACK_BUFFER: [u8; 1920 * 1080 * 4] = [0; 1920 * 1080 * 4];
+
+/// 启动时记录的帧缓冲区参数，没有帧缓冲区时为 None
+pub static FRAMEBUFFER_INFO: Mutex<Option<FrameBufferInfo>> = Mutex::new(None);
+
 // 全局 Shell 实例
+// 锁的顺序: SHELL → WRITER → SCROLLBACK / SHADOW (后两者只由 Writer 内部加锁)
+// 持有 WRITER 时不能再锁 SHELL。SHELL 只在主循环中访问 (键盘中断处理程序只把扫描码放进输入队列)，
+// 命令在持有 SHELL 期间执行并且需要中断 (sleep、等待按键)，所以访问 SHELL 时不关中断
 pub static SHELL: Mutex<Shell> = Mutex::new(Shell::new());
 
 /// 初始化全局 Writer
 fn init_writer(boot_info: &'static mut BootInfo) {
     if let Some(framebuffer) = boot_info.framebuffer.as_mut() {
         let info = framebuffer.info();
+        *FRAMEBUFFER_INFO.lock() = Some(info);
         let buffer = framebuffer.buffer_mut();
         let mut writer = Writer::new(buffer, info);
+        #[cfg(feature = "double-buffer")]
+        {
+            // SAFETY: init_writer 只调用一次，这是 BACK_BUFFER 唯一的引用
+            let back = unsafe { &mut *core::ptr::addr_of_mut!(BACK_BUFFER) };
+            // 分辨率超出预留大小时退回直接绘制
+            writer.enable_double_buffering(back);
+        }
         writer.clear_screen();
+        writer.present();
+        log_info!(
+            "Framebuffer: {}x{}, stride {}, {} bytes/pixel, {:?}",
+            info.width, info.height, info.stride, info.bytes_per_pixel, info.pixel_format
+        );
         *WRITER.lock() = Some(writer);
+    } else {
+        log_warn!("No framebuffer available, falling back to serial output");
+    }
+}
+
+/// 关中断后锁住 WRITER 并运行 f，没有帧缓冲区时返回 None
+/// 中断处理程序中也可能输出，持锁期间关中断避免死锁；f 中不要再锁 WRITER
+#[cfg(not(test))]
+pub fn with_writer<T>(f: impl FnOnce(&mut Writer) -> T) -> Option<T> {
+    x86_64::instructions::interrupts::without_interrupts(|| WRITER.lock().as_mut().map(f))
+}
+
+/// 测试在用户态运行，不能执行 cli，直接加锁
+#[cfg(test)]
+pub fn with_writer<T>(f: impl FnOnce(&mut Writer) -> T) -> Option<T> {
+    WRITER.lock().as_mut().map(f)
+}
+
+/// 可视响铃的闪烁时长
+const BELL_FLASH_MS: u64 = 80;
+
+/// 输出过 BEL 时把屏幕反色闪烁一下
+/// 等待期间不持有 WRITER 锁；中断关闭时无法等待，响铃留到之后再闪
+pub fn flash_bell() {
+    if !x86_64::instructions::interrupts::are_enabled() {
+        return;
+    }
+    let flashing = with_writer(|writer| {
+        if !writer.take_bell() {
+            return false;
+        }
+        // 先把尚未显示的内容送到屏幕，反转的是用户实际看到的画面
+        writer.present();
+        writer.invert_screen();
+        true
+    });
+    if flashing == Some(true) {
+        time::sleep_ms(BELL_FLASH_MS, || false);
+        with_writer(Writer::invert_screen);
     }
 }
 
@@ -40,10 +145,18 @@ fn init_writer(boot_info: &'static mut BootInfo) {
 #[doc(hidden)]
 pub fn _print(args: core::fmt::Arguments) {
     use core::fmt::Write;
-    
-    if let Some(ref mut writer) = WRITER.lock().as_mut() {
+
+    let written = with_writer(|writer| {
         writer.write_fmt(args).unwrap();
+        if MIRROR_TO_SERIAL {
+            serial::_serial_print(args);
+        }
+    });
+    if written.is_none() {
+        serial::_serial_print(args);
     }
+    // 输出中有 BEL 时，在离开关中断区域之后再闪烁
+    flash_bell();
 }
 
 /// print! 宏
@@ -61,49 +174,132 @@ macro_rules! println {
 
 /// 设置文字颜色
 pub fn set_text_color(fg: Color, bg: Color) {
-    if let Some(ref mut writer) = WRITER.lock().as_mut() {
+    with_writer(|writer| {
         writer.set_fg_color(fg);
         writer.set_bg_color(bg);
-    }
+    });
+}
+
+/// 临时用高亮前景色输出 (例如错误提示)，之后用 reset_text_color 恢复用户的颜色
+pub fn highlight_text(fg: Color) {
+    with_writer(|writer| writer.highlight(fg));
+}
+
+/// 恢复高亮之前的文字颜色
+pub fn reset_text_color() {
+    with_writer(|writer| writer.restore_colors());
 }
 
 /// 处理退格键 - 删除前一个字符
 pub fn handle_backspace() {
-    if let Some(ref mut writer) = WRITER.lock().as_mut() {
+    let erased = with_writer(|writer| {
         writer.backspace();
+        writer.present();
+    });
+    // 串口终端: 退格、覆盖空格、再退格
+    if erased.is_none() || MIRROR_TO_SERIAL {
+        serial_print!("\x08 \x08");
+    }
+}
+
+/// 向上翻页查看历史输出
+pub fn handle_page_up() {
+    with_writer(|writer| {
+        let lines = writer.page_lines();
+        writer.scroll_view_up(lines);
+        writer.present();
+    });
+}
+
+/// 向下翻页，回到最新输出
+pub fn handle_page_down() {
+    with_writer(|writer| {
+        let lines = writer.page_lines();
+        writer.scroll_view_down(lines);
+        writer.present();
+    });
+}
+
+/// 锁住 SHELL 并运行 f
+/// SHELL 已被占用说明有代码在执行命令时重入了 Shell (例如在命令中处理输入队列)，
+/// 这时等待只会死锁，记录错误并返回 None
+pub fn with_shell<T>(f: impl FnOnce(&mut Shell) -> T) -> Option<T> {
+    match SHELL.try_lock() {
+        Some(mut shell) => Some(f(&mut shell)),
+        None => {
+            log_error!("Reentrant shell access, input dropped");
+            None
+        }
     }
 }
 
 /// Shell 字符处理函数
-pub fn handle_shell_char(ch: char) {
-    SHELL.lock().handle_char(ch);
+/// 返回普通字符是否被 Shell 接受
+pub fn handle_shell_char(ch: char) -> bool {
+    with_shell(|shell| shell.handle_char(ch)).unwrap_or(false)
 }
 
 fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
+    // 串口最先初始化，之后的日志都能输出
+    serial::init();
+    log_info!(
+        "Rust OS v{} ({}, {}) booting",
+        shell::VERSION,
+        shell::GIT_HASH,
+        shell::BUILD_PROFILE
+    );
+
+    // 在 init_writer 取走 boot_info 之前记录内存布局
+    memory::init(boot_info);
+    match memory::physical_memory_offset() {
+        Some(offset) => log_info!("Physical memory mapped at {:#x}", offset),
+        None => log_warn!("Physical memory not mapped, identity mapping only"),
+    }
+
     // 初始化显示系统
+    log_info!("Initializing framebuffer writer...");
     init_writer(boot_info);
     
-    set_text_color(Color::CYAN, Color::BLACK);
-    println!("=== Rust OS v0.3.0 - Time System ===");
-    set_text_color(Color::WHITE, Color::BLACK);
+    shell::print_banner();
     
     // 分步初始化系统
-    println!("Initializing interrupt system...");
+    // PS/2 控制器使用轮询初始化，必须在开中断之前完成
+    log_info!("Initializing PS/2 controller...");
+    match ps2::init() {
+        Ok(set) if set.effective() == 1 => log_info!(
+            "Keyboard scancode set {}{}",
+            set.keyboard,
+            if set.translated { " (translated to set 1)" } else { "" }
+        ),
+        Ok(set) => log_warn!("Keyboard uses scancode set {}, key mapping will be wrong", set.effective()),
+        Err(error) => log_error!("PS/2 initialization failed: {} ({:x?})", error.describe(), error),
+    }
+
+    log_info!("Initializing interrupt system...");
     interrupts::init();
     
     // ✨ 初始化时间系统
-    println!("Initializing PIT (Programmable Interval Timer)...");
+    log_info!("Initializing PIT (Programmable Interval Timer)...");
     pit::init();
     
     let (frequency, interval_ms) = pit::get_info();
-    println!("PIT configured: {} Hz, {} ms per tick", frequency, interval_ms);
+    log_info!("PIT configured: {} Hz, {} ms per tick", frequency, interval_ms);
     
-    println!("Initializing time management...");
-    time::init(interval_ms);
+    log_info!("Initializing time management...");
+    let (base_hz, divisor) = pit::tick_period();
+    time::init(base_hz, divisor);
+    input::init_repeat();
     
-    set_text_color(Color::GREEN, Color::BLACK);
+    match time::calibrate_tsc() {
+        Some(khz) => log_info!("TSC calibrated: {}.{:03} MHz", khz / 1000, khz % 1000),
+        None => log_warn!("TSC calibration failed"),
+    }
+    log_info!("Boot time (RTC): {}", time::seed_boot_time_from_rtc());
+
+    log_info!("All systems initialized");
+    highlight_text(Color::GREEN);
     println!("✓ All systems initialized!");
-    set_text_color(Color::WHITE, Color::BLACK);
+    reset_text_color();
     
     println!();
     println!("Welcome to Rust OS Interactive Shell!");
@@ -112,23 +308,64 @@ fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
     println!("- Timer now running at {} Hz", frequency);
     println!();
     
-    set_text_color(Color::YELLOW, Color::BLACK);
+    highlight_text(Color::YELLOW);
     println!("Type 'help' to see available commands.");
-    set_text_color(Color::WHITE, Color::BLACK);
+    reset_text_color();
     println!();
     
     // 显示第一个提示符
-    SHELL.lock().show_prompt();
+    with_shell(|shell| shell.show_prompt());
+    if time::set_interval(CURSOR_BLINK_MS, |_| blink_cursor()).is_none() {
+        log_warn!("No free timer slot, cursor will not blink");
+    }
     
-    // 主循环 - 等待键盘中断
+    // 主循环 - 事件循环: 中断处理程序只登记工作标志，这里等待并分派
     loop {
-        x86_64::instructions::hlt(); // 等待中断
+        let work = event::wait();
+        if work.contains(WorkFlags::TICK) {
+            // 登记的 TIMER 工作在下一轮分派
+            time::poll_tick();
+        }
+        if work.contains(WorkFlags::DEFERRED) {
+            deferred::run_deferred();
+        }
+        if work.contains(WorkFlags::SECOND) {
+            time::run_second_callback();
+        }
+        if work.contains(WorkFlags::TIMER) {
+            time::run_expired_timers();
+        }
+        if work.contains(WorkFlags::INPUT) {
+            input::process_pending();
+            // 输入处理时光标被擦掉，立刻在新位置显示，不等下一次闪烁
+            with_writer(|writer| {
+                writer.show_cursor();
+                writer.present();
+            });
+        }
+        flash_bell();
     }
 }
 
+/// 定时器回调: 切换光标的显示状态 (命令执行期间主循环不分派定时器，光标只在等待输入时闪烁)
+fn blink_cursor() {
+    with_writer(|writer| {
+        writer.toggle_cursor();
+        writer.present();
+    });
+}
+
+#[cfg(not(test))]
 #[panic_handler]
 fn panic(info: &core::panic::PanicInfo) -> ! {
-    set_text_color(Color::RED, Color::BLACK);
+    // 恐慌可能发生在持有 WRITER 或串口锁的代码中，之后不会再回到那段代码，强制解锁才能输出恐慌信息
+    x86_64::instructions::interrupts::disable();
+    unsafe {
+        serial::SERIAL.force_unlock();
+        WRITER.force_unlock();
+    }
+    log_error!("KERNEL PANIC: {}", info);
+    highlight_text(Color::RED);
     println!();
     println!("KERNEL PANIC!");
     println!("=============");
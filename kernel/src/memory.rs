@@ -0,0 +1,277 @@
+// kernel/src/memory.rs
+// 物理内存布局 (来自引导程序的内存映射) 和内存测试辅助函数
+
+use bootloader_api::info::MemoryRegionKind;
+use bootloader_api::BootInfo;
+use spin::Mutex;
+use x86_64::registers::control::Cr3;
+use x86_64::structures::paging::{OffsetPageTable, PageTable, Translate};
+use x86_64::VirtAddr;
+
+/// 最多记录的可用内存区域数 (更多的区域被忽略)
+const MAX_REGIONS: usize = 32;
+
+/// 一段物理地址范围 [start, end)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Region {
+    const EMPTY: Region = Region { start: 0, end: 0 };
+
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+
+    fn contains(&self, other: &Region) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    fn overlaps(&self, other: &Region) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+/// 启动时记录的内存布局
+#[derive(Clone, Copy)]
+pub struct MemoryMap {
+    regions: [Region; MAX_REGIONS],
+    count: usize,
+    /// 整个物理内存映射到的虚拟地址偏移，引导程序没有映射时为 None
+    physical_memory_offset: Option<u64>,
+    /// 内核映像所在的物理地址范围
+    kernel: Region,
+}
+
+impl MemoryMap {
+    const fn new() -> MemoryMap {
+        MemoryMap {
+            regions: [Region::EMPTY; MAX_REGIONS],
+            count: 0,
+            physical_memory_offset: None,
+            kernel: Region::EMPTY,
+        }
+    }
+
+    /// 整个物理内存映射到的虚拟地址偏移 (物理地址 + 偏移 = 虚拟地址)
+    /// None 表示引导程序没有映射物理内存，只有引导程序建立的恒等映射和内核自身的映射可用，
+    /// 物理地址不能直接访问
+    pub fn physical_memory_offset(&self) -> Option<u64> {
+        self.physical_memory_offset
+    }
+
+    /// 可用 (未被内核和引导程序占用) 的内存区域
+    pub fn usable_regions(&self) -> &[Region] {
+        &self.regions[..self.count]
+    }
+
+    fn add_usable(&mut self, region: Region) {
+        // 引导程序给出的相邻区域合并为一个，方便测试跨区域的范围
+        if let Some(last) = self.regions[..self.count].last_mut() {
+            if last.end == region.start {
+                last.end = region.end;
+                return;
+            }
+        }
+        if self.count < MAX_REGIONS {
+            self.regions[self.count] = region;
+            self.count += 1;
+        }
+    }
+}
+
+/// 内存范围不能测试的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeError {
+    /// 大小为 0
+    Empty,
+    /// 起点或大小不是 8 字节对齐
+    Misaligned,
+    /// 范围超出地址空间
+    Overflow,
+    /// 范围不完全位于某个可用区域内
+    NotUsable,
+    /// 范围与内核映像重叠
+    OverlapsKernel,
+    /// 引导程序没有映射物理内存
+    NotMapped,
+}
+
+impl RangeError {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            RangeError::Empty => "size must not be zero",
+            RangeError::Misaligned => "start and size must be 8-byte aligned",
+            RangeError::Overflow => "range exceeds the address space",
+            RangeError::NotUsable => "range is not inside a usable memory region",
+            RangeError::OverlapsKernel => "range overlaps the kernel image",
+            RangeError::NotMapped => "physical memory is not mapped",
+        }
+    }
+}
+
+static MEMORY_MAP: Mutex<MemoryMap> = Mutex::new(MemoryMap::new());
+
+/// 记录引导程序提供的内存布局 (启动时调用一次)
+pub fn init(boot_info: &BootInfo) {
+    let mut map = MEMORY_MAP.lock();
+    for region in boot_info.memory_regions.iter() {
+        if region.kind == MemoryRegionKind::Usable && region.end > region.start {
+            map.add_usable(Region { start: region.start, end: region.end });
+        }
+    }
+    map.physical_memory_offset = boot_info.physical_memory_offset.into_option();
+    map.kernel = Region {
+        start: boot_info.kernel_addr,
+        end: boot_info.kernel_addr.saturating_add(boot_info.kernel_len),
+    };
+}
+
+/// 当前内存布局的副本
+pub fn memory_map() -> MemoryMap {
+    *MEMORY_MAP.lock()
+}
+
+/// 启动时记录的物理内存映射偏移，见 MemoryMap::physical_memory_offset
+pub fn physical_memory_offset() -> Option<u64> {
+    MEMORY_MAP.lock().physical_memory_offset
+}
+
+/// 检查 [start, start + size) 能否安全地做破坏性测试
+/// 只允许完全位于可用区域内的范围：内核、栈、页表和帧缓冲区都在引导程序标记为占用的区域里
+pub fn check_test_range(map: &MemoryMap, start: u64, size: u64) -> Result<Region, RangeError> {
+    if size == 0 {
+        return Err(RangeError::Empty);
+    }
+    if !start.is_multiple_of(8) || !size.is_multiple_of(8) {
+        return Err(RangeError::Misaligned);
+    }
+    let end = start.checked_add(size).ok_or(RangeError::Overflow)?;
+    let range = Region { start, end };
+
+    if map.kernel.overlaps(&range) {
+        return Err(RangeError::OverlapsKernel);
+    }
+    if !map.usable_regions().iter().any(|region| region.contains(&range)) {
+        return Err(RangeError::NotUsable);
+    }
+    if map.physical_memory_offset.is_none() {
+        return Err(RangeError::NotMapped);
+    }
+    Ok(range)
+}
+
+/// 物理地址对应的虚拟地址 (通过引导程序的物理内存映射)
+pub fn physical_to_virtual(map: &MemoryMap, physical: u64) -> Option<*mut u64> {
+    let offset = map.physical_memory_offset?;
+    Some(offset.checked_add(physical)? as *mut u64)
+}
+
+/// 页大小 (只用于按页检查映射，大页同样适用)
+const PAGE_SIZE: u64 = 4096;
+
+/// 虚拟地址范围 [start, start + len) 是否全部已映射，读取前用来避免缺页异常
+/// 需要物理内存映射才能遍历页表，没有映射时返回 false
+pub fn is_range_mapped(map: &MemoryMap, start: u64, len: u64) -> bool {
+    let offset = match map.physical_memory_offset {
+        Some(offset) => offset,
+        None => return false,
+    };
+    let end = match start.checked_add(len) {
+        Some(end) => end,
+        None => return false,
+    };
+    if VirtAddr::try_new(start).is_err() || VirtAddr::try_new(end.saturating_sub(1)).is_err() {
+        return false;
+    }
+
+    // 安全性: 引导程序把整个物理内存映射到 offset，CR3 指向当前的四级页表；
+    // 这里只读取页表，不修改
+    let page_table = unsafe {
+        let (frame, _) = Cr3::read();
+        let table = (offset + frame.start_address().as_u64()) as *mut PageTable;
+        OffsetPageTable::new(&mut *table, VirtAddr::new(offset))
+    };
+
+    let mut page = start & !(PAGE_SIZE - 1);
+    while page < end {
+        if page_table.translate_addr(VirtAddr::new(page)).is_none() {
+            return false;
+        }
+        page += PAGE_SIZE;
+    }
+    true
+}
+
+/// 对一个 64 位字做走 1 测试，依次写入只有一位为 1 的值并读回
+/// 结束后恢复原来的内容；发现错误时返回 (期望值, 实际值)
+///
+/// # Safety
+/// `word` 必须指向可读写、没有被其它代码使用的内存
+pub unsafe fn walking_ones(word: *mut u64) -> Option<(u64, u64)> {
+    let original = core::ptr::read_volatile(word);
+    let mut failure = None;
+    for bit in 0..64 {
+        let pattern = 1u64 << bit;
+        core::ptr::write_volatile(word, pattern);
+        let got = core::ptr::read_volatile(word);
+        if got != pattern {
+            failure = Some((pattern, got));
+            break;
+        }
+    }
+    core::ptr::write_volatile(word, original);
+    failure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_map() -> MemoryMap {
+        let mut map = MemoryMap::new();
+        map.add_usable(Region { start: 0x1000, end: 0x9000 });
+        map.add_usable(Region { start: 0x100000, end: 0x200000 });
+        map.physical_memory_offset = Some(0x1_0000_0000);
+        map.kernel = Region { start: 0x200000, end: 0x300000 };
+        map
+    }
+
+    #[test]
+    fn adjacent_regions_are_merged() {
+        let mut map = MemoryMap::new();
+        map.add_usable(Region { start: 0x1000, end: 0x2000 });
+        map.add_usable(Region { start: 0x2000, end: 0x3000 });
+        map.add_usable(Region { start: 0x4000, end: 0x5000 });
+        assert_eq!(
+            map.usable_regions(),
+            &[Region { start: 0x1000, end: 0x3000 }, Region { start: 0x4000, end: 0x5000 }]
+        );
+    }
+
+    #[test]
+    fn accepts_range_inside_usable_region() {
+        let map = test_map();
+        assert_eq!(check_test_range(&map, 0x1000, 0x8000), Ok(Region { start: 0x1000, end: 0x9000 }));
+        assert!(check_test_range(&map, 0x150000, 0x1000).is_ok());
+    }
+
+    #[test]
+    fn rejects_unsafe_ranges() {
+        let map = test_map();
+        assert_eq!(check_test_range(&map, 0x1000, 0), Err(RangeError::Empty));
+        assert_eq!(check_test_range(&map, 0x1004, 0x10), Err(RangeError::Misaligned));
+        assert_eq!(check_test_range(&map, u64::MAX - 7, 0x10), Err(RangeError::Overflow));
+        assert_eq!(check_test_range(&map, 0x8000, 0x2000), Err(RangeError::NotUsable));
+        assert_eq!(check_test_range(&map, 0x1ff000, 0x2000), Err(RangeError::OverlapsKernel));
+    }
+
+    #[test]
+    fn rejects_ranges_without_physical_mapping() {
+        let mut map = test_map();
+        map.physical_memory_offset = None;
+        assert_eq!(check_test_range(&map, 0x1000, 0x1000), Err(RangeError::NotMapped));
+    }
+}
@@ -0,0 +1,106 @@
+// kernel/src/pager.rs
+// 分页输出 - 每输出一屏暂停，等待按键后继续
+
+use core::fmt;
+use crate::input;
+use crate::keyboard::Key;
+use crate::writer::Color;
+use crate::{print, println, set_text_color};
+
+/// 空格键的扫描码
+const SPACE_SCANCODE: u8 = 0x39;
+/// 暂停时显示的提示
+const MORE_PROMPT: &str = "-- more -- (press space)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PagerState {
+    /// 每满一屏暂停一次
+    Paging,
+    /// 不再暂停，输出剩余全部内容
+    All,
+    /// 用户按了 Esc，丢弃剩余内容
+    Quit,
+}
+
+/// 分页器：用 `line` 代替 `println!` 逐行输出
+/// 空格翻到下一屏，其它键输出剩余全部内容，Esc 停止输出
+pub struct Pager {
+    state: PagerState,
+    page_rows: usize,
+    columns: usize,
+    rows_left: usize,
+}
+
+impl Pager {
+    /// 按当前滚动区域的大小创建分页器 (留一行给提示)
+    /// 分屏模式下只有输出区参与滚动
+    pub fn new() -> Pager {
+        let (rows, columns) = crate::with_writer(|writer| {
+            let (top, bottom) = writer.scroll_region();
+            (bottom + 1 - top, writer.text_columns())
+        })
+        .unwrap_or((0, 0));
+        let page_rows = rows.saturating_sub(1);
+        Pager {
+            // 屏幕太小时不分页
+            state: if page_rows == 0 || columns == 0 { PagerState::All } else { PagerState::Paging },
+            page_rows,
+            columns,
+            rows_left: page_rows,
+        }
+    }
+
+    /// 输出一行 (以当前颜色)，需要时先暂停等待按键
+    pub fn line(&mut self, text: &str) {
+        self.line_fmt(format_args!("{}", text));
+    }
+
+    /// 输出一行格式化文本
+    pub fn line_fmt(&mut self, args: fmt::Arguments) {
+        if self.state == PagerState::Paging {
+            // 长行会自动换行，占用多行
+            let mut counter = CharCounter(0);
+            let _ = fmt::write(&mut counter, args);
+            let rows = counter.0.div_ceil(self.columns).max(1);
+            if rows > self.rows_left {
+                self.wait();
+                self.rows_left = self.page_rows;
+            }
+            self.rows_left = self.rows_left.saturating_sub(rows);
+        }
+        if self.state != PagerState::Quit {
+            println!("{}", args);
+        }
+    }
+
+    /// 显示提示并等待按键，之后擦掉提示
+    fn wait(&mut self) {
+        let colors = crate::with_writer(|writer| writer.current_colors());
+
+        if let Some((_, bg)) = colors {
+            set_text_color(Color::YELLOW, bg);
+        }
+        print!("{}", MORE_PROMPT);
+        let key = input::wait_key();
+        print!("\r{:width$}\r", "", width = MORE_PROMPT.len());
+        if let Some((fg, bg)) = colors {
+            set_text_color(fg, bg);
+        }
+
+        self.state = match key {
+            Some(event) if event.key == Key::Normal(SPACE_SCANCODE) => PagerState::Paging,
+            Some(_) => PagerState::All,
+            None => PagerState::Quit,
+        };
+    }
+}
+
+/// 只统计字符数的 fmt::Write
+struct CharCounter(usize);
+
+impl fmt::Write for CharCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.chars().count();
+        Ok(())
+    }
+}
@@ -13,6 +13,15 @@ const PIC2_DATA: u16 = 0xA1;
 const ICW1_INIT: u8 = 0x11;
 const ICW4_8086: u8 = 0x01;
 
+/// 非特定 EOI 命令
+const PIC_EOI: u8 = 0x20;
+
+/// 从 PIC 连接在主 PIC 的 IRQ2 上
+const CASCADE_IRQ: u8 = 2;
+
+/// OCW3: 下一次读命令端口返回中断服务寄存器 (ISR)
+const OCW3_READ_ISR: u8 = 0x0B;
+
 /// 中断向量偏移
 pub const PIC1_OFFSET: u8 = 32;  // 主 PIC 中断号从 32 开始
 pub const PIC2_OFFSET: u8 = 40;  // 从 PIC 中断号从 40 开始
@@ -73,25 +82,57 @@ impl Pics {
         self.pic2_data.write(0xFF);
     }
 
-    /// 启用特定中断
+    /// 启用特定中断 (IRQ0-15)
+    /// 启用从 PIC 上的中断 (IRQ8-15) 时同时启用主 PIC 的级联线 IRQ2，否则从 PIC 的中断到不了 CPU
     pub unsafe fn enable_interrupt(&mut self, irq: u8) {
-        if irq < 8 {
-            let mask = self.pic1_data.read();
-            self.pic1_data.write(mask & !(1 << irq));
-        } else {
-            let mask = self.pic2_data.read();
-            self.pic2_data.write(mask & !(1 << (irq - 8)));
-        }
+        let (master, slave) = masks_after_enable(self.pic1_data.read(), self.pic2_data.read(), irq);
+        self.pic1_data.write(master);
+        self.pic2_data.write(slave);
+
+        debug_assert!(
+            irq >= 8 || self.pic1_data.read() & (1 << irq) == 0,
+            "IRQ{} still masked on master PIC",
+            irq
+        );
+        debug_assert!(
+            irq < 8 || self.pic1_data.read() & (1 << CASCADE_IRQ) == 0,
+            "cascade IRQ2 masked while enabling slave IRQ{}",
+            irq
+        );
+    }
+
+    /// 读取两个 PIC 的中断服务寄存器，低 8 位是主 PIC，高 8 位是从 PIC
+    pub unsafe fn read_isr(&mut self) -> u16 {
+        self.pic1_command.write(OCW3_READ_ISR);
+        self.pic2_command.write(OCW3_READ_ISR);
+        let master = self.pic1_command.read() as u16;
+        let slave = self.pic2_command.read() as u16;
+        (slave << 8) | master
     }
 
     /// 发送 EOI (End of Interrupt) 信号
     pub unsafe fn end_of_interrupt(&mut self, interrupt_id: u8) {
-        if interrupt_id >= PIC2_OFFSET {
-            // 如果是从 PIC 的中断，两个 PIC 都要发送 EOI
-            self.pic2_command.write(0x20);
+        match interrupt_id {
+            // 从 PIC 的中断经过主 PIC 的 IRQ2 级联，两个 PIC 都要发送 EOI (先从后主)
+            id if (PIC2_OFFSET..PIC2_OFFSET + 8).contains(&id) => {
+                self.pic2_command.write(PIC_EOI);
+                self.pic1_command.write(PIC_EOI);
+            }
+            id if (PIC1_OFFSET..PIC1_OFFSET + 8).contains(&id) => {
+                self.pic1_command.write(PIC_EOI);
+            }
+            // 不是 PIC 的中断，不需要 EOI
+            _ => {}
         }
-        // 总是向主 PIC 发送 EOI
-        self.pic1_command.write(0x20);
+    }
+}
+
+/// 计算启用 `irq` 之后主/从 PIC 的屏蔽字 (位为 1 表示屏蔽)
+fn masks_after_enable(master: u8, slave: u8, irq: u8) -> (u8, u8) {
+    match irq {
+        0..=7 => (master & !(1 << irq), slave),
+        8..=15 => (master & !(1 << CASCADE_IRQ), slave & !(1 << (irq - 8))),
+        _ => (master, slave), // 无效的 IRQ 号，保持不变
     }
 }
 
@@ -129,4 +170,61 @@ pub fn end_of_interrupt(interrupt_id: u8) {
     unsafe {
         PICS.lock().end_of_interrupt(interrupt_id);
     }
-}
\ No newline at end of file
+}
+
+/// IRQ 号对应的中断向量
+pub fn irq_vector(irq: u8) -> u8 {
+    if irq < 8 {
+        PIC1_OFFSET + irq
+    } else {
+        PIC2_OFFSET + (irq - 8)
+    }
+}
+
+/// IRQ7 / IRQ15 是否为伪中断: PIC 发出了中断但 ISR 中对应位没有置位
+pub fn is_spurious(irq: u8) -> bool {
+    if irq != 7 && irq != 15 {
+        return false;
+    }
+    let isr = unsafe { PICS.lock().read_isr() };
+    isr & (1 << irq) == 0
+}
+
+/// 处理伪中断的 EOI: 伪 IRQ7 不需要 EOI；伪 IRQ15 经过主 PIC 的级联线是真的，只给主 PIC 发送
+pub fn acknowledge_spurious(irq: u8) {
+    if irq >= 8 {
+        unsafe {
+            PICS.lock().pic1_command.write(PIC_EOI);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enabling_master_irq_clears_only_its_bit() {
+        assert_eq!(masks_after_enable(0xFF, 0xFF, 0), (0xFE, 0xFF));
+        assert_eq!(masks_after_enable(0xFE, 0xFF, 1), (0xFC, 0xFF));
+    }
+
+    #[test]
+    fn enabling_slave_irq_unmasks_cascade() {
+        // IRQ12 (PS/2 鼠标): 从 PIC 第 4 位 + 主 PIC 的 IRQ2
+        let (master, slave) = masks_after_enable(0xFF, 0xFF, 12);
+        assert_eq!(master & (1 << CASCADE_IRQ), 0);
+        assert_eq!(master, 0xFB);
+        assert_eq!(slave, 0xEF);
+
+        // IRQ8 (RTC)，已经启用的位保持不变
+        let (master, slave) = masks_after_enable(0xFC, 0xEF, 8);
+        assert_eq!(master, 0xF8);
+        assert_eq!(slave, 0xEE);
+    }
+
+    #[test]
+    fn invalid_irq_leaves_masks_unchanged() {
+        assert_eq!(masks_after_enable(0xAB, 0xCD, 16), (0xAB, 0xCD));
+    }
+}
@@ -7,11 +7,11 @@ use spin::Mutex;
 /// PIT 端口地址
 const PIT_CHANNEL_0: u16 = 0x40;  // 通道0数据端口
 const PIT_CHANNEL_1: u16 = 0x41;  // 通道1数据端口 (未使用)
-const PIT_CHANNEL_2: u16 = 0x42;  // 通道2数据端口 (未使用)
-const PIT_COMMAND: u16 = 0x43;    // 命令寄存器
+pub const PIT_CHANNEL_2: u16 = 0x42;  // 通道2数据端口 (PC 扬声器)
+pub const PIT_COMMAND: u16 = 0x43;    // 命令寄存器
 
 /// PIT 基础频率 (1.193182 MHz)
-const PIT_BASE_FREQUENCY: u32 = 1193182;
+pub const PIT_BASE_FREQUENCY: u32 = 1193182;
 
 /// 目标频率 (100 Hz = 每秒100次中断)
 const TARGET_FREQUENCY: u32 = 100;
@@ -27,6 +27,9 @@ const DIVISOR: u16 = (PIT_BASE_FREQUENCY / TARGET_FREQUENCY) as u16;
 /// BCD = 0 (二进制模式)
 const PIT_COMMAND_BYTE: u8 = 0x34;
 
+/// 锁存命令: SC=00 (通道0), RW=00 (计数锁存), 其余位忽略
+const PIT_LATCH_COMMAND: u8 = 0x00;
+
 /// PIT 控制器结构
 pub struct Pit {
     channel_0: Port<u8>,
@@ -65,6 +68,22 @@ impl Pit {
         self.initialized = true;
     }
 
+    /// 读取通道0当前的倒计数值
+    ///
+    /// 回读协议:
+    /// 1. 向命令端口写入锁存命令 (0x00)，PIT 把当前计数复制到输出锁存器，
+    ///    计数器本身继续递减
+    /// 2. 从通道0数据端口读两次: 先低字节，后高字节
+    /// 3. 两次读取都完成后锁存器才会释放
+    ///
+    /// 调用方必须关中断，否则两次读取之间可能插入其他访问 PIT 的代码
+    pub unsafe fn read_count(&mut self) -> u16 {
+        self.command.write(PIT_LATCH_COMMAND);
+        let low = self.channel_0.read() as u16;
+        let high = self.channel_0.read() as u16;
+        (high << 8) | low
+    }
+
     /// 检查是否已初始化
     pub fn is_initialized(&self) -> bool {
         self.initialized
@@ -102,6 +121,25 @@ pub fn get_info() -> (u32, u32) {
     (pit.get_frequency(), pit.get_interval_ms())
 }
 
+/// 实际的 tick 周期: DIVISOR / PIT_BASE_FREQUENCY 秒，返回 (基础频率, 分频值)
+/// 分频值取整后实际频率不是整 100Hz，计时应使用这个周期而不是 get_info 的毫秒数
+pub fn tick_period() -> (u32, u32) {
+    (PIT_BASE_FREQUENCY, DIVISOR as u32)
+}
+
+/// 读取通道0当前的倒计数值 (DIVISOR 递减到 1)
+pub fn read_count() -> u16 {
+    x86_64::instructions::interrupts::without_interrupts(|| unsafe {
+        PIT.lock().read_count()
+    })
+}
+
+/// 把倒计数值换算成本 tick 内已经过去的微秒数
+pub fn count_to_elapsed_us(count: u16) -> u64 {
+    let remaining = count.min(DIVISOR) as u64;
+    (DIVISOR as u64 - remaining) * 1_000_000 / PIT_BASE_FREQUENCY as u64
+}
+
 /// 检查 PIT 是否已初始化
 pub fn is_initialized() -> bool {
     PIT.lock().is_initialized()
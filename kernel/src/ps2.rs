@@ -0,0 +1,427 @@
+// kernel/src/ps2.rs
+// PS/2 控制器 (8042) 初始化和自检
+// 初始化在开中断之前调用，全部使用轮询，不依赖键盘中断
+// 开中断之后发给键盘的命令由 IRQ1 收到应答 (ACK / 重发)，这些字节在中断处理程序中被截下，不会进入扫描码队列
+
+use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use x86_64::instructions::port::Port;
+
+/// 数据端口 (读写)
+const DATA_PORT: u16 = 0x60;
+/// 状态寄存器 (读) / 命令寄存器 (写)
+const STATUS_COMMAND_PORT: u16 = 0x64;
+
+/// 状态位: 输出缓冲区有数据可读
+const STATUS_OUTPUT_FULL: u8 = 0x01;
+/// 状态位: 输入缓冲区满，控制器还没取走上一个字节
+const STATUS_INPUT_FULL: u8 = 0x02;
+
+/// 控制器命令
+const CMD_READ_CONFIG: u8 = 0x20;
+const CMD_WRITE_CONFIG: u8 = 0x60;
+const CMD_DISABLE_PORT2: u8 = 0xA7;
+const CMD_SELF_TEST: u8 = 0xAA;
+const CMD_TEST_PORT1: u8 = 0xAB;
+const CMD_DISABLE_PORT1: u8 = 0xAD;
+const CMD_ENABLE_PORT1: u8 = 0xAE;
+
+/// 控制器自检通过时的应答
+const SELF_TEST_PASSED: u8 = 0x55;
+/// 端口测试通过时的应答
+const PORT_TEST_PASSED: u8 = 0x00;
+
+/// 键盘命令: 复位并自检
+const KEYBOARD_RESET: u8 = 0xFF;
+/// 键盘命令: 查询/设置扫描码集，后跟参数 0 (查询) 或 1..=3 (设置)
+const KEYBOARD_SCANCODE_SET: u8 = 0xF0;
+/// 键盘命令: 设置指示灯，后跟指示灯位掩码
+const KEYBOARD_SET_LEDS: u8 = 0xED;
+/// 键盘应答: 命令已接收
+const KEYBOARD_ACK: u8 = 0xFA;
+/// 键盘应答: 没有正确收到，请重发上一个字节
+const KEYBOARD_RESEND: u8 = 0xFE;
+/// 键盘应答: 自检通过
+const KEYBOARD_SELF_TEST_PASSED: u8 = 0xAA;
+/// 键盘复位后默认使用的扫描码集
+const KEYBOARD_DEFAULT_SET: u8 = 2;
+
+/// 配置字节: 端口1中断 (IRQ1)
+const CONFIG_PORT1_IRQ: u8 = 0x01;
+/// 配置字节: 端口2中断 (IRQ12)
+const CONFIG_PORT2_IRQ: u8 = 0x02;
+/// 配置字节: 端口1时钟关闭
+const CONFIG_PORT1_CLOCK_DISABLED: u8 = 0x10;
+/// 配置字节: 控制器把键盘发来的扫描码集 2 转换为扫描码集 1
+const CONFIG_PORT1_TRANSLATION: u8 = 0x40;
+
+/// 等待状态位变化的轮询次数上限 (此时还没有定时器可用)
+const POLL_LIMIT: u32 = 1_000_000;
+/// 清空输出缓冲区时最多丢弃的字节数
+const FLUSH_LIMIT: usize = 16;
+/// 键盘要求重发时最多重发的次数
+const MAX_RESENDS: usize = 3;
+/// 开中断后等待键盘应答的时间上限 (毫秒)
+const RESPONSE_TIMEOUT_MS: u64 = 20;
+
+/// 指示灯位掩码: Caps Lock (Scroll Lock 为 0x01，Num Lock 为 0x02)
+pub const LED_CAPS_LOCK: u8 = 0x04;
+
+/// 收到的 ACK 次数 (诊断用)
+static ACK_COUNT: AtomicU32 = AtomicU32::new(0);
+/// 收到的重发请求次数 (诊断用)
+static RESEND_COUNT: AtomicU32 = AtomicU32::new(0);
+/// 中断处理程序收到、还没被等待应答的一方取走的应答字节，0 表示没有
+static PENDING_RESPONSE: AtomicU8 = AtomicU8::new(0);
+
+/// 初始化过程中的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ps2Error {
+    /// 控制器没有在限定时间内接收或返回数据
+    Timeout,
+    /// 控制器自检失败 (附带实际应答)
+    SelfTestFailed(u8),
+    /// 端口1测试失败 (附带实际应答)
+    PortTestFailed(u8),
+    /// 键盘复位失败 (附带实际应答)
+    KeyboardResetFailed(u8),
+    /// 键盘没有应答 0xFA (附带实际应答)
+    CommandRejected(u8),
+    /// 键盘报告了无法识别的扫描码集 (附带实际应答)
+    UnknownScancodeSet(u8),
+    /// 重发 MAX_RESENDS 次之后键盘仍然要求重发
+    TooManyResends,
+}
+
+impl Ps2Error {
+    /// 错误的简短说明
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Ps2Error::Timeout => "controller timed out",
+            Ps2Error::SelfTestFailed(_) => "controller self-test failed",
+            Ps2Error::PortTestFailed(_) => "port 1 test failed",
+            Ps2Error::KeyboardResetFailed(_) => "keyboard reset failed",
+            Ps2Error::CommandRejected(_) => "keyboard rejected a command",
+            Ps2Error::UnknownScancodeSet(_) => "keyboard reported an unknown scancode set",
+            Ps2Error::TooManyResends => "keyboard kept asking for a resend",
+        }
+    }
+}
+
+/// 键盘的扫描码集
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScancodeSet {
+    /// 键盘自身使用的扫描码集 (1..=3)
+    pub keyboard: u8,
+    /// 控制器是否开启了转换
+    pub translated: bool,
+}
+
+impl ScancodeSet {
+    /// 驱动实际收到的扫描码集: 转换只对扫描码集 2 有意义
+    pub fn effective(&self) -> u8 {
+        if self.translated && self.keyboard == 2 {
+            1
+        } else {
+            self.keyboard
+        }
+    }
+}
+
+/// 解码"查询扫描码集"的应答
+/// 开启转换时应答本身也会被转换: 1/2/3 分别变成 0x43/0x41/0x3F
+fn decode_scancode_set(response: u8) -> Option<u8> {
+    match response {
+        1..=3 => Some(response),
+        0x43 => Some(1),
+        0x41 => Some(2),
+        0x3F => Some(3),
+        _ => None,
+    }
+}
+
+/// PS/2 控制器
+struct Controller {
+    data: Port<u8>,
+    status_command: Port<u8>,
+}
+
+impl Controller {
+    const fn new() -> Controller {
+        Controller {
+            data: Port::new(DATA_PORT),
+            status_command: Port::new(STATUS_COMMAND_PORT),
+        }
+    }
+
+    fn status(&mut self) -> u8 {
+        unsafe { self.status_command.read() }
+    }
+
+    /// 等待输入缓冲区空闲后写入命令
+    fn command(&mut self, command: u8) -> Result<(), Ps2Error> {
+        self.wait_writable()?;
+        unsafe { self.status_command.write(command) };
+        Ok(())
+    }
+
+    /// 等待输入缓冲区空闲后写入数据端口
+    fn write_data(&mut self, byte: u8) -> Result<(), Ps2Error> {
+        self.wait_writable()?;
+        unsafe { self.data.write(byte) };
+        Ok(())
+    }
+
+    /// 等待输出缓冲区有数据后读取
+    fn read_data(&mut self) -> Result<u8, Ps2Error> {
+        for _ in 0..POLL_LIMIT {
+            if self.status() & STATUS_OUTPUT_FULL != 0 {
+                return Ok(unsafe { self.data.read() });
+            }
+            core::hint::spin_loop();
+        }
+        Err(Ps2Error::Timeout)
+    }
+
+    fn wait_writable(&mut self) -> Result<(), Ps2Error> {
+        for _ in 0..POLL_LIMIT {
+            if self.status() & STATUS_INPUT_FULL == 0 {
+                return Ok(());
+            }
+            core::hint::spin_loop();
+        }
+        Err(Ps2Error::Timeout)
+    }
+
+    /// 丢弃输出缓冲区中残留的字节
+    fn flush(&mut self) {
+        for _ in 0..FLUSH_LIMIT {
+            if self.status() & STATUS_OUTPUT_FULL == 0 {
+                return;
+            }
+            unsafe { self.data.read() };
+        }
+    }
+
+    fn read_config(&mut self) -> Result<u8, Ps2Error> {
+        self.command(CMD_READ_CONFIG)?;
+        self.read_data()
+    }
+
+    fn write_config(&mut self, config: u8) -> Result<(), Ps2Error> {
+        self.command(CMD_WRITE_CONFIG)?;
+        self.write_data(config)
+    }
+
+    /// 向键盘发送一个字节并轮询等待 0xFA 应答 (只在关中断的初始化期间使用)
+    fn keyboard_command(&mut self, byte: u8) -> Result<(), Ps2Error> {
+        exchange(|| {
+            self.write_data(byte)?;
+            self.read_data()
+        })
+    }
+
+    /// 查询键盘当前的扫描码集
+    fn query_scancode_set(&mut self) -> Result<u8, Ps2Error> {
+        self.keyboard_command(KEYBOARD_SCANCODE_SET)?;
+        self.keyboard_command(0)?;
+        let response = self.read_data()?;
+        decode_scancode_set(response).ok_or(Ps2Error::UnknownScancodeSet(response))
+    }
+
+    fn set_scancode_set(&mut self, set: u8) -> Result<(), Ps2Error> {
+        self.keyboard_command(KEYBOARD_SCANCODE_SET)?;
+        self.keyboard_command(set)
+    }
+}
+
+/// 发送一个字节直到键盘应答 ACK: send 负责发送并返回键盘的应答
+/// 收到重发请求时最多重发 MAX_RESENDS 次，应答计入诊断计数
+fn exchange(mut send: impl FnMut() -> Result<u8, Ps2Error>) -> Result<(), Ps2Error> {
+    for _ in 0..=MAX_RESENDS {
+        match send()? {
+            KEYBOARD_ACK => {
+                ACK_COUNT.fetch_add(1, Ordering::Relaxed);
+                return Ok(());
+            },
+            KEYBOARD_RESEND => {
+                RESEND_COUNT.fetch_add(1, Ordering::Relaxed);
+            },
+            response => return Err(Ps2Error::CommandRejected(response)),
+        }
+    }
+    Err(Ps2Error::TooManyResends)
+}
+
+/// 在键盘中断处理程序中调用: 截下 ACK 和重发请求，返回 true 表示该字节不是扫描码
+/// (扫描码集 1 中 0xFA / 0xFE 是不存在的键的释放码，不会与真实按键冲突)
+pub fn take_response(byte: u8) -> bool {
+    if byte == KEYBOARD_ACK || byte == KEYBOARD_RESEND {
+        PENDING_RESPONSE.store(byte, Ordering::SeqCst);
+        true
+    } else {
+        false
+    }
+}
+
+/// 开中断后向键盘发送命令 (命令字节及参数)，每个字节都等待键盘应答
+/// 必须在中断开启时调用，应答由键盘中断处理程序通过 take_response 交过来
+pub fn send_keyboard_command(bytes: &[u8]) -> Result<(), Ps2Error> {
+    let mut controller = Controller::new();
+    for &byte in bytes {
+        exchange(|| {
+            PENDING_RESPONSE.store(0, Ordering::SeqCst);
+            controller.write_data(byte)?;
+            wait_response()
+        })?;
+    }
+    Ok(())
+}
+
+/// 等待中断处理程序收到应答，超时返回 Timeout
+fn wait_response() -> Result<u8, Ps2Error> {
+    let deadline = crate::time::get_uptime_ms() + RESPONSE_TIMEOUT_MS;
+    loop {
+        match PENDING_RESPONSE.swap(0, Ordering::SeqCst) {
+            0 if crate::time::get_uptime_ms() >= deadline => return Err(Ps2Error::Timeout),
+            0 => x86_64::instructions::hlt(),
+            response => return Ok(response),
+        }
+    }
+}
+
+/// 设置键盘指示灯 (指示灯位掩码的组合)
+pub fn set_leds(leds: u8) -> Result<(), Ps2Error> {
+    send_keyboard_command(&[KEYBOARD_SET_LEDS, leds])
+}
+
+/// 到目前为止收到的 (ACK 次数, 重发请求次数)
+pub fn response_counts() -> (u32, u32) {
+    (ACK_COUNT.load(Ordering::Relaxed), RESEND_COUNT.load(Ordering::Relaxed))
+}
+
+/// 初始化 PS/2 控制器和键盘 (必须在开中断之前调用)
+/// 键盘驱动依赖扫描码集 1: 保留固件设置的转换位，开启转换时让键盘使用扫描码集 2，否则直接使用扫描码集 1
+/// 失败时尽量恢复固件原来的配置并重新启用端口1，键盘至少还能按固件的设置工作
+pub fn init() -> Result<ScancodeSet, Ps2Error> {
+    let mut controller = Controller::new();
+
+    // 初始化期间关闭两个端口，避免设备数据混入应答
+    let original = controller
+        .command(CMD_DISABLE_PORT1)
+        .and_then(|()| controller.command(CMD_DISABLE_PORT2))
+        .and_then(|()| {
+            controller.flush();
+            controller.read_config()
+        });
+    let original = match original {
+        Ok(config) => config,
+        Err(error) => {
+            // 还没读到配置字节，只能重新启用端口1
+            let _ = controller.command(CMD_ENABLE_PORT1);
+            return Err(error);
+        },
+    };
+
+    configure(&mut controller, original).inspect_err(|_| {
+        let _ = controller.write_config(original);
+        let _ = controller.command(CMD_ENABLE_PORT1);
+        controller.flush();
+    })
+}
+
+/// 控制器自检、启用端口1并复位键盘，`original` 是固件设置的配置字节
+fn configure(controller: &mut Controller, original: u8) -> Result<ScancodeSet, Ps2Error> {
+    // 自检期间关闭中断
+    let config = original & !(CONFIG_PORT1_IRQ | CONFIG_PORT2_IRQ);
+    controller.write_config(config)?;
+
+    controller.command(CMD_SELF_TEST)?;
+    let response = controller.read_data()?;
+    if response != SELF_TEST_PASSED {
+        return Err(Ps2Error::SelfTestFailed(response));
+    }
+    // 部分控制器自检后会复位配置字节，重新写入
+    controller.write_config(config)?;
+
+    controller.command(CMD_TEST_PORT1)?;
+    let response = controller.read_data()?;
+    if response != PORT_TEST_PASSED {
+        return Err(Ps2Error::PortTestFailed(response));
+    }
+
+    // 启用端口1和 IRQ1
+    controller.command(CMD_ENABLE_PORT1)?;
+    let config = (config | CONFIG_PORT1_IRQ) & !CONFIG_PORT1_CLOCK_DISABLED;
+    controller.write_config(config)?;
+
+    // 复位键盘: 先应答 0xFA，自检完成后再发送 0xAA
+    controller.write_data(KEYBOARD_RESET)?;
+    let mut response = controller.read_data()?;
+    if response == KEYBOARD_ACK {
+        response = controller.read_data()?;
+    }
+    if response != KEYBOARD_SELF_TEST_PASSED {
+        return Err(Ps2Error::KeyboardResetFailed(response));
+    }
+
+    let translated = config & CONFIG_PORT1_TRANSLATION != 0;
+    let keyboard = select_scancode_set(controller, if translated { 2 } else { 1 });
+
+    controller.flush();
+    Ok(ScancodeSet { keyboard, translated })
+}
+
+/// 让键盘改用扫描码集 `wanted`，返回键盘实际使用的扫描码集
+/// 有些键盘不支持查询或设置扫描码集，失败时只记录日志: 查询失败时假定是复位后的默认值，
+/// 设置失败时保留原来的扫描码集，设置后再查询失败时假定已经生效
+fn select_scancode_set(controller: &mut Controller, wanted: u8) -> u8 {
+    let current = match controller.query_scancode_set() {
+        Ok(set) => set,
+        Err(error) => return scancode_set_fallback(controller, error, KEYBOARD_DEFAULT_SET),
+    };
+    if current == wanted {
+        return current;
+    }
+    if let Err(error) = controller.set_scancode_set(wanted) {
+        return scancode_set_fallback(controller, error, current);
+    }
+    controller
+        .query_scancode_set()
+        .unwrap_or_else(|error| scancode_set_fallback(controller, error, wanted))
+}
+
+/// 记录扫描码集查询/设置失败，丢弃残留的应答并返回假定的扫描码集
+fn scancode_set_fallback(controller: &mut Controller, error: Ps2Error, assumed: u8) -> u8 {
+    crate::log_warn!(
+        "Scancode set query/set failed: {} ({:x?}), assuming set {}",
+        error.describe(), error, assumed
+    );
+    controller.flush();
+    assumed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exchange_resends_until_acknowledged() {
+        let mut replies = [KEYBOARD_RESEND, KEYBOARD_RESEND, KEYBOARD_ACK].into_iter();
+        assert_eq!(exchange(|| Ok(replies.next().unwrap())), Ok(()));
+        assert_eq!(replies.next(), None);
+
+        assert_eq!(exchange(|| Ok(KEYBOARD_RESEND)), Err(Ps2Error::TooManyResends));
+        assert_eq!(exchange(|| Ok(0x12)), Err(Ps2Error::CommandRejected(0x12)));
+        assert_eq!(exchange(|| Err(Ps2Error::Timeout)), Err(Ps2Error::Timeout));
+    }
+
+    #[test]
+    fn decodes_raw_and_translated_scancode_set_replies() {
+        assert_eq!(decode_scancode_set(2), Some(2));
+        assert_eq!(decode_scancode_set(0x41), Some(2));
+        assert_eq!(decode_scancode_set(0x43), Some(1));
+        assert_eq!(decode_scancode_set(0xFA), None);
+        assert_eq!(ScancodeSet { keyboard: 2, translated: true }.effective(), 1);
+        assert_eq!(ScancodeSet { keyboard: 2, translated: false }.effective(), 2);
+    }
+}
@@ -0,0 +1,259 @@
+// kernel/src/rtc.rs
+// CMOS 实时时钟 (RTC) 读取
+
+use core::fmt;
+use x86_64::instructions::interrupts;
+use x86_64::instructions::port::Port;
+
+/// CMOS 寄存器选择端口 (最高位同时控制 NMI，写入时保持为 0)
+const CMOS_ADDRESS: u16 = 0x70;
+/// CMOS 数据端口
+const CMOS_DATA: u16 = 0x71;
+
+/// CMOS 寄存器
+const REG_SECONDS: u8 = 0x00;
+const REG_MINUTES: u8 = 0x02;
+const REG_HOURS: u8 = 0x04;
+const REG_DAY: u8 = 0x07;
+const REG_MONTH: u8 = 0x08;
+const REG_YEAR: u8 = 0x09;
+const REG_STATUS_A: u8 = 0x0A;
+const REG_STATUS_B: u8 = 0x0B;
+
+/// 状态 A: 正在更新时间，此时读取的值可能不一致
+const STATUS_A_UPDATE_IN_PROGRESS: u8 = 0x80;
+/// 状态 B: 24 小时制
+const STATUS_B_24_HOUR: u8 = 0x02;
+/// 状态 B: 二进制格式 (否则为 BCD)
+const STATUS_B_BINARY: u8 = 0x04;
+/// 12 小时制时小时寄存器的 PM 标志
+const HOUR_PM: u8 = 0x80;
+
+/// RTC 只存两位年份，按 21 世纪解释
+const CENTURY: u16 = 2000;
+
+/// 等待 RTC 秒边沿的超时时间
+const SECOND_EDGE_TIMEOUT_MS: u64 = 1500;
+
+/// 日期和时间 (RTC 的时区，一般是 UTC 或本地时间，取决于固件)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// 自 1970-01-01 00:00:00 以来的秒数，用于计算两个时间的间隔
+    pub fn to_epoch_seconds(self) -> u64 {
+        days_from_civil(self.year as i64, self.month as i64, self.day as i64) as u64 * 86_400
+            + self.hour as u64 * 3600
+            + self.minute as u64 * 60
+            + self.second as u64
+    }
+
+    /// to_epoch_seconds 的逆运算，秒、分、时、日、月、年的进位都在这里处理
+    pub fn from_epoch_seconds(seconds: u64) -> DateTime {
+        let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+        let second_of_day = seconds % 86_400;
+        DateTime {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (second_of_day / 3600) as u8,
+            minute: (second_of_day / 60 % 60) as u8,
+            second: (second_of_day % 60) as u8,
+        }
+    }
+
+    /// 加上若干秒
+    pub fn add_seconds(self, seconds: u64) -> DateTime {
+        DateTime::from_epoch_seconds(self.to_epoch_seconds() + seconds)
+    }
+}
+
+/// 格式为 YYYY-MM-DD HH:MM:SS
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// 公历日期到 1970-01-01 的天数 (Howard Hinnant 的 days_from_civil 算法)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12; // 三月为 0
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// 1970-01-01 之后的天数到公历日期 (Howard Hinnant 的 civil_from_days 算法)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153; // 三月为 0
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// BCD 转二进制
+fn bcd_to_binary(value: u8) -> u8 {
+    (value & 0x0F) + (value >> 4) * 10
+}
+
+/// 按状态 B 的格式把原始寄存器值转换为 DateTime
+fn decode(raw: [u8; 6], status_b: u8) -> DateTime {
+    let [second, minute, hour, day, month, year] = raw;
+    let convert = |value: u8| if status_b & STATUS_B_BINARY != 0 { value } else { bcd_to_binary(value) };
+
+    let pm = hour & HOUR_PM != 0;
+    let mut hour = convert(hour & !HOUR_PM);
+    if status_b & STATUS_B_24_HOUR == 0 {
+        // 12 小时制: 12 AM 是 0 点，12 PM 是 12 点
+        hour %= 12;
+        if pm {
+            hour += 12;
+        }
+    }
+
+    DateTime {
+        year: CENTURY + convert(year) as u16,
+        month: convert(month),
+        day: convert(day),
+        hour,
+        minute: convert(minute),
+        second: convert(second),
+    }
+}
+
+/// 读取一个 CMOS 寄存器
+fn read_register(register: u8) -> u8 {
+    let mut address: Port<u8> = Port::new(CMOS_ADDRESS);
+    let mut data: Port<u8> = Port::new(CMOS_DATA);
+    interrupts::without_interrupts(|| unsafe {
+        address.write(register);
+        data.read()
+    })
+}
+
+fn update_in_progress() -> bool {
+    read_register(REG_STATUS_A) & STATUS_A_UPDATE_IN_PROGRESS != 0
+}
+
+fn read_raw() -> [u8; 6] {
+    [REG_SECONDS, REG_MINUTES, REG_HOURS, REG_DAY, REG_MONTH, REG_YEAR].map(read_register)
+}
+
+/// 读取当前日期时间
+/// 避开更新周期，并连续读两次直到结果一致，防止读到一半时秒进位
+pub fn read() -> DateTime {
+    while update_in_progress() {
+        core::hint::spin_loop();
+    }
+    let mut raw = read_raw();
+    loop {
+        while update_in_progress() {
+            core::hint::spin_loop();
+        }
+        let again = read_raw();
+        if again == raw {
+            break;
+        }
+        raw = again;
+    }
+    decode(raw, read_register(REG_STATUS_B))
+}
+
+/// 等到 RTC 的秒数变化 (秒边沿) 后返回新的时间
+/// 在边沿采样可以把 RTC 只有整秒精度带来的误差缩小到一个定时器 tick；
+/// 必须在中断开启时调用，超时 (RTC 不走) 返回 None
+pub fn wait_for_second_edge() -> Option<DateTime> {
+    let start = read();
+    let deadline = crate::time::get_uptime_ms() + SECOND_EDGE_TIMEOUT_MS;
+    while crate::time::get_uptime_ms() < deadline {
+        let now = read();
+        if now.second != start.second {
+            return Some(now);
+        }
+        x86_64::instructions::hlt();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bcd_values_are_decoded() {
+        assert_eq!(bcd_to_binary(0x00), 0);
+        assert_eq!(bcd_to_binary(0x09), 9);
+        assert_eq!(bcd_to_binary(0x59), 59);
+    }
+
+    #[test]
+    fn decode_handles_bcd_12_hour_format() {
+        // 2024-02-29 12:30:45 AM 和 PM
+        let midnight = decode([0x45, 0x30, 0x12, 0x29, 0x02, 0x24], 0);
+        assert_eq!(midnight.hour, 0);
+        let noon = decode([0x45, 0x30, 0x12 | HOUR_PM, 0x29, 0x02, 0x24], 0);
+        assert_eq!(
+            noon,
+            DateTime { year: 2024, month: 2, day: 29, hour: 12, minute: 30, second: 45 }
+        );
+        let evening = decode([0x00, 0x00, 0x11 | HOUR_PM, 0x01, 0x01, 0x25], 0);
+        assert_eq!(evening.hour, 23);
+    }
+
+    #[test]
+    fn decode_handles_binary_24_hour_format() {
+        let time = decode([59, 7, 23, 31, 12, 99], STATUS_B_BINARY | STATUS_B_24_HOUR);
+        assert_eq!(
+            time,
+            DateTime { year: 2099, month: 12, day: 31, hour: 23, minute: 7, second: 59 }
+        );
+    }
+
+    #[test]
+    fn epoch_seconds_match_known_dates() {
+        let epoch = DateTime { year: 1970, month: 1, day: 1, hour: 0, minute: 0, second: 0 };
+        assert_eq!(epoch.to_epoch_seconds(), 0);
+        let leap_day = DateTime { year: 2024, month: 2, day: 29, hour: 12, minute: 0, second: 0 };
+        assert_eq!(leap_day.to_epoch_seconds(), 1_709_208_000);
+        let new_year = DateTime { year: 2000, month: 1, day: 1, hour: 0, minute: 0, second: 0 };
+        assert_eq!(new_year.to_epoch_seconds(), 946_684_800);
+    }
+
+    #[test]
+    fn adding_seconds_rolls_over_days_months_and_years() {
+        let date = |year, month, day, hour, minute, second| DateTime { year, month, day, hour, minute, second };
+        assert_eq!(date(2023, 12, 31, 23, 59, 59).add_seconds(1), date(2024, 1, 1, 0, 0, 0));
+        assert_eq!(date(2024, 2, 28, 23, 59, 59).add_seconds(1), date(2024, 2, 29, 0, 0, 0));
+        assert_eq!(date(2100, 2, 28, 12, 0, 0).add_seconds(86_400), date(2100, 3, 1, 12, 0, 0));
+        assert_eq!(date(2024, 4, 30, 22, 30, 0).add_seconds(5400), date(2024, 5, 1, 0, 0, 0));
+        assert_eq!(DateTime::from_epoch_seconds(1_709_208_000), date(2024, 2, 29, 12, 0, 0));
+    }
+
+    #[test]
+    fn display_pads_fields() {
+        let time = DateTime { year: 2024, month: 3, day: 5, hour: 7, minute: 8, second: 9 };
+        let mut out = crate::format::LineBuffer::<32>::new();
+        let _ = fmt::Write::write_fmt(&mut out, format_args!("{}", time));
+        assert_eq!(out.as_str(), "2024-03-05 07:08:09");
+    }
+}
@@ -0,0 +1,155 @@
+// kernel/src/scrollback.rs
+// 回滾緩衝區 - 保存已輸出的文字行，供 Page Up/Page Down 瀏覽
+
+use crate::ansi::Attributes;
+use crate::writer::Color;
+use spin::Mutex;
+
+/// 最多保存的行數 (包含當前屏幕上的行)
+pub const SCROLLBACK_LINES: usize = 200;
+/// 每行最多保存的列數，超出部分只顯示不保存
+pub const MAX_COLUMNS: usize = 160;
+
+/// 單個字符格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Color,
+    pub bg: Color,
+    pub attributes: Attributes,
+}
+
+impl Cell {
+    /// 空白格 (全零，使整個緩衝區可以放在 .bss 中)
+    const EMPTY: Cell = Cell { ch: '\0', fg: Color::BLACK, bg: Color::BLACK, attributes: Attributes::NONE };
+}
+
+/// 一行文字
+struct Line {
+    cells: [Cell; MAX_COLUMNS],
+    len: usize,
+}
+
+impl Line {
+    const EMPTY: Line = Line { cells: [Cell::EMPTY; MAX_COLUMNS], len: 0 };
+}
+
+/// 以環形緩衝區保存的文字行
+/// 行號是從清屏開始遞增的邏輯行號，存放在 `行號 % SCROLLBACK_LINES` 的槽位
+pub struct Scrollback {
+    lines: [Line; SCROLLBACK_LINES],
+}
+
+impl Scrollback {
+    pub const fn new() -> Scrollback {
+        Scrollback {
+            lines: [Line::EMPTY; SCROLLBACK_LINES],
+        }
+    }
+
+    /// 清空所有行
+    pub fn reset(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.len = 0;
+        }
+    }
+
+    /// 清空一行 (新行進入屏幕時調用，覆蓋環形緩衝區中最舊的行)
+    pub fn clear_line(&mut self, line: usize) {
+        self.lines[line % SCROLLBACK_LINES].len = 0;
+    }
+
+    /// 把一行的內容複製到另一行 (滾動區域內滾動時使用)
+    pub fn copy_line(&mut self, from: usize, to: usize) {
+        let from = from % SCROLLBACK_LINES;
+        let to = to % SCROLLBACK_LINES;
+        if from != to {
+            let len = self.lines[from].len;
+            let cells = self.lines[from].cells;
+            self.lines[to].cells[..len].copy_from_slice(&cells[..len]);
+            self.lines[to].len = len;
+        }
+    }
+
+    /// 在指定位置記錄一個字符，中間未寫過的列以空格補齊
+    pub fn set(&mut self, line: usize, column: usize, cell: Cell) {
+        if column >= MAX_COLUMNS {
+            return;
+        }
+        let line = &mut self.lines[line % SCROLLBACK_LINES];
+        for gap in line.len..column {
+            line.cells[gap] = Cell { ch: ' ', attributes: Attributes::NONE, ..cell };
+        }
+        line.cells[column] = cell;
+        line.len = line.len.max(column + 1);
+    }
+
+    /// 擦除指定位置的字符 (退格)
+    pub fn erase(&mut self, line: usize, column: usize, bg: Color) {
+        let line = &mut self.lines[line % SCROLLBACK_LINES];
+        if column + 1 == line.len {
+            line.len -= 1;
+        } else if column < line.len {
+            line.cells[column] = Cell { ch: ' ', fg: bg, bg, attributes: Attributes::NONE };
+        }
+    }
+
+    /// 獲取一行已保存的字符
+    pub fn line(&self, line: usize) -> &[Cell] {
+        let line = &self.lines[line % SCROLLBACK_LINES];
+        &line.cells[..line.len]
+    }
+}
+
+/// 在一行字符中從 from 開始查找 pattern，返回第一個匹配的起始列
+/// 不區分大小寫時只折疊 ASCII 字母；空 pattern 不匹配任何位置
+pub fn find_text(text: &[char], pattern: &str, from: usize, case_sensitive: bool) -> Option<usize> {
+    let len = pattern.chars().count();
+    if len == 0 || text.len() < len {
+        return None;
+    }
+    (from..=text.len() - len).find(|&start| {
+        text[start..start + len].iter().zip(pattern.chars()).all(|(&a, b)| {
+            if case_sensitive { a == b } else { a.eq_ignore_ascii_case(&b) }
+        })
+    })
+}
+
+/// 全局回滾緩衝區 (只由 Writer 訪問，始終在持有 WRITER 鎖時加鎖)
+pub static SCROLLBACK: Mutex<Scrollback> = Mutex::new(Scrollback::new());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str, out: &mut [char; MAX_COLUMNS]) -> usize {
+        let mut len = 0;
+        for (slot, ch) in out.iter_mut().zip(text.chars()) {
+            *slot = ch;
+            len += 1;
+        }
+        len
+    }
+
+    #[test]
+    fn find_text_ignores_ascii_case_unless_asked() {
+        let mut buffer = ['\0'; MAX_COLUMNS];
+        let len = chars("Uptime: 0h 01m, UPTIME again", &mut buffer);
+        let line = &buffer[..len];
+        assert_eq!(find_text(line, "uptime", 0, false), Some(0));
+        assert_eq!(find_text(line, "uptime", 1, false), Some(16));
+        assert_eq!(find_text(line, "uptime", 0, true), None);
+        assert_eq!(find_text(line, "UPTIME", 0, true), Some(16));
+    }
+
+    #[test]
+    fn find_text_handles_edges() {
+        let mut buffer = ['\0'; MAX_COLUMNS];
+        let len = chars("abc", &mut buffer);
+        let line = &buffer[..len];
+        assert_eq!(find_text(line, "", 0, false), None);
+        assert_eq!(find_text(line, "abcd", 0, false), None);
+        assert_eq!(find_text(line, "c", 0, false), Some(2));
+        assert_eq!(find_text(line, "a", 3, false), None);
+    }
+}
@@ -0,0 +1,109 @@
+// kernel/src/serial.rs
+// 16550 UART 串口驱动 (COM1) - 用于日志输出和无显示器运行
+
+use core::fmt;
+use spin::Mutex;
+use x86_64::instructions::port::Port;
+
+/// COM1 的基地址
+const COM1_BASE: u16 = 0x3F8;
+
+/// 线路状态寄存器: 发送保持寄存器为空
+const LINE_STATUS_THR_EMPTY: u8 = 0x20;
+
+/// 串口
+pub struct SerialPort {
+    data: Port<u8>,              // +0 数据 / 波特率除数低字节
+    interrupt_enable: Port<u8>,  // +1 中断使能 / 波特率除数高字节
+    fifo_control: Port<u8>,      // +2 FIFO 控制
+    line_control: Port<u8>,      // +3 线路控制
+    modem_control: Port<u8>,     // +4 调制解调器控制
+    line_status: Port<u8>,       // +5 线路状态
+    initialized: bool,
+}
+
+impl SerialPort {
+    pub const fn new(base: u16) -> SerialPort {
+        SerialPort {
+            data: Port::new(base),
+            interrupt_enable: Port::new(base + 1),
+            fifo_control: Port::new(base + 2),
+            line_control: Port::new(base + 3),
+            modem_control: Port::new(base + 4),
+            line_status: Port::new(base + 5),
+            initialized: false,
+        }
+    }
+
+    /// 初始化为 38400 波特、8N1，不使用串口中断
+    pub unsafe fn initialize(&mut self) {
+        self.interrupt_enable.write(0x00); // 关闭串口中断
+        self.line_control.write(0x80);     // 设置 DLAB，访问波特率除数
+        self.data.write(0x03);             // 除数 3 (38400 波特) 低字节
+        self.interrupt_enable.write(0x00); // 除数高字节
+        self.line_control.write(0x03);     // 8 位数据、无校验、1 位停止位
+        self.fifo_control.write(0xC7);     // 启用并清空 FIFO，14 字节阈值
+        self.modem_control.write(0x0B);    // DTR + RTS + OUT2
+        self.initialized = true;
+    }
+
+    /// 发送一个字节 (等待发送寄存器空闲)
+    pub fn send(&mut self, byte: u8) {
+        if !self.initialized {
+            return;
+        }
+        unsafe {
+            while self.line_status.read() & LINE_STATUS_THR_EMPTY == 0 {
+                core::hint::spin_loop();
+            }
+            self.data.write(byte);
+        }
+    }
+}
+
+impl fmt::Write for SerialPort {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            // 终端需要 CRLF 换行
+            if byte == b'\n' {
+                self.send(b'\r');
+            }
+            self.send(byte);
+        }
+        Ok(())
+    }
+}
+
+/// 全局串口 (COM1)
+pub static SERIAL: Mutex<SerialPort> = Mutex::new(SerialPort::new(COM1_BASE));
+
+/// 初始化串口 (应在启动时最先调用)
+pub fn init() {
+    unsafe {
+        SERIAL.lock().initialize();
+    }
+}
+
+/// 串口打印函数的内部实现
+#[doc(hidden)]
+pub fn _serial_print(args: fmt::Arguments) {
+    use core::fmt::Write;
+
+    // 关中断，避免中断处理程序中打印时死锁
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        let _ = SERIAL.lock().write_fmt(args);
+    });
+}
+
+/// serial_print! 宏 - 输出到串口
+#[macro_export]
+macro_rules! serial_print {
+    ($($arg:tt)*) => ($crate::serial::_serial_print(format_args!($($arg)*)));
+}
+
+/// serial_println! 宏
+#[macro_export]
+macro_rules! serial_println {
+    () => ($crate::serial_print!("\n"));
+    ($($arg:tt)*) => ($crate::serial_print!("{}\n", format_args!($($arg)*)));
+}
@@ -0,0 +1,140 @@
+// kernel/src/shadow.rs
+// 影子网格 - 记录屏幕上每个字符格当前画着的内容，内容没有变化时跳过重绘
+
+use crate::scrollback::{Cell, MAX_COLUMNS};
+use core::ops::Range;
+use spin::Mutex;
+
+/// 最多跟踪的行数，超出范围的字符格总是重绘
+pub const MAX_ROWS: usize = 128;
+
+/// 每个字符格上次绘制的内容，None 表示未知 (被图形或填充覆盖过，必须重绘)
+pub struct ShadowGrid {
+    cells: [[Option<Cell>; MAX_COLUMNS]; MAX_ROWS],
+}
+
+impl ShadowGrid {
+    pub const fn new() -> ShadowGrid {
+        ShadowGrid {
+            cells: [[None; MAX_COLUMNS]; MAX_ROWS],
+        }
+    }
+
+    /// 记录即将绘制到 (行, 列) 的内容，返回是否需要真正绘制
+    /// 与上次绘制的内容相同时返回 false
+    pub fn update(&mut self, row: usize, column: usize, cell: Cell) -> bool {
+        match self.cells.get_mut(row).and_then(|cells| cells.get_mut(column)) {
+            Some(slot) if *slot == Some(cell) => false,
+            Some(slot) => {
+                *slot = Some(cell);
+                true
+            },
+            None => true,
+        }
+    }
+
+    /// 把一块区域标记为未知
+    pub fn invalidate(&mut self, rows: Range<usize>, columns: Range<usize>) {
+        let columns = columns.start.min(MAX_COLUMNS)..columns.end.min(MAX_COLUMNS);
+        for cells in self.cells.iter_mut().take(rows.end).skip(rows.start) {
+            cells[columns.clone()].fill(None);
+        }
+    }
+
+    /// 把所有字符格标记为未知，之后每个字符格都会重绘
+    pub fn invalidate_all(&mut self) {
+        self.invalidate(0..MAX_ROWS, 0..MAX_COLUMNS);
+    }
+
+    /// 把第 row 行前 out.len() 列画着的字符复制到 out，返回去掉行尾空白后的长度
+    /// 未知或超出范围的字符格按空格处理
+    pub fn row_text(&self, row: usize, out: &mut [char]) -> usize {
+        let cells = self.cells.get(row);
+        let mut len = 0;
+        for (column, slot) in out.iter_mut().enumerate() {
+            *slot = match cells.and_then(|cells| cells.get(column)).copied().flatten() {
+                Some(cell) if cell.ch != '\0' => cell.ch,
+                _ => ' ',
+            };
+            if *slot != ' ' {
+                len = column + 1;
+            }
+        }
+        len
+    }
+
+    /// 与屏幕一起把 [top, bottom] 行向上滚动 rows 行，最后 rows 行变为未知
+    pub fn scroll_up(&mut self, top: usize, bottom: usize, rows: usize) {
+        let bottom = bottom.min(MAX_ROWS - 1);
+        if top > bottom {
+            return;
+        }
+        let rows = rows.min(bottom + 1 - top);
+        if top + rows <= bottom {
+            self.cells.copy_within(top + rows..=bottom, top);
+        }
+        self.invalidate(bottom + 1 - rows..bottom + 1, 0..MAX_COLUMNS);
+    }
+}
+
+/// 全局影子网格 (只由 Writer 访问，始终在持有 WRITER 锁时加锁)
+pub static SHADOW: Mutex<ShadowGrid> = Mutex::new(ShadowGrid::new());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::Attributes;
+    use crate::writer::Color;
+
+    fn cell(ch: char) -> Cell {
+        Cell { ch, fg: Color::WHITE, bg: Color::BLACK, attributes: Attributes::NONE }
+    }
+
+    #[test]
+    fn unchanged_cell_is_not_redrawn() {
+        let mut grid = ShadowGrid::new();
+        assert!(grid.update(0, 0, cell('a')));
+        assert!(!grid.update(0, 0, cell('a')));
+        assert!(grid.update(0, 0, Cell { fg: Color::RED, ..cell('a') }));
+        assert!(grid.update(0, 0, cell('b')));
+
+        grid.invalidate(0..1, 0..1);
+        assert!(grid.update(0, 0, cell('b')));
+        // 超出范围的字符格总是重绘
+        assert!(grid.update(MAX_ROWS, 0, cell('a')));
+        assert!(grid.update(MAX_ROWS, 0, cell('a')));
+    }
+
+    #[test]
+    fn row_text_trims_trailing_blanks_and_unknown_cells() {
+        let mut grid = ShadowGrid::new();
+        grid.update(0, 0, cell('o'));
+        grid.update(0, 1, cell('k'));
+        grid.update(0, 3, cell('!'));
+        grid.update(0, 4, cell(' '));
+        let mut out = [' '; 8];
+        let len = grid.row_text(0, &mut out);
+        assert_eq!(&out[..len], &['o', 'k', ' ', '!']);
+        assert_eq!(grid.row_text(1, &mut out), 0);
+        assert_eq!(grid.row_text(MAX_ROWS, &mut out), 0);
+    }
+
+    #[test]
+    fn scroll_moves_rows_up() {
+        let mut grid = ShadowGrid::new();
+        grid.update(0, 0, cell('a'));
+        grid.update(1, 0, cell('b'));
+        grid.update(2, 0, cell('c'));
+        grid.scroll_up(0, 2, 1);
+        assert!(!grid.update(0, 0, cell('b')));
+        assert!(!grid.update(1, 0, cell('c')));
+        assert!(grid.update(2, 0, cell('c')));
+
+        // 一次滚动两行
+        grid.update(2, 0, cell('d'));
+        grid.scroll_up(0, 2, 2);
+        assert!(!grid.update(0, 0, cell('d')));
+        assert!(grid.update(1, 0, cell('c')));
+        assert!(grid.update(2, 0, cell('d')));
+    }
+}
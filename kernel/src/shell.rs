@@ -1,19 +1,513 @@
 // kernel/src/shell.rs
 
-use crate::writer::Color;
-use crate::{print, println, set_text_color};
+use crate::alias::{AliasError, AliasTable, MAX_NAME_LEN, MAX_VALUE_LEN};
+use crate::bigfont;
+use crate::format;
+use crate::history::{self, History};
+use crate::keyboard;
+use crate::log;
+use crate::pager::Pager;
+use crate::vars::{self, VariableError, VariableTable};
+use crate::writer::{Color, CursorShape, MAX_INPUT_ROWS};
+use crate::{highlight_text, print, println, reset_text_color};
 
-/// 输入缓冲区最大长度
+/// 版本号 (来自 Cargo.toml)
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// 构建时的 git 短哈希、构建配置和编译器版本 (由 build.rs 注入，获取不到时为 "unknown")
+pub const GIT_HASH: &str = env!("GIT_HASH");
+pub const BUILD_PROFILE: &str = env!("BUILD_PROFILE");
+pub const RUSTC_VERSION: &str = env!("RUSTC_VERSION");
+
+/// 输入缓冲区大小 (一行最多输入 INPUT_BUFFER_SIZE - 1 个字符)，需要更长的命令时直接调大
 const INPUT_BUFFER_SIZE: usize = 256;
+/// `set inputsize=N` 允许的最小输入缓冲区大小
+const MIN_INPUT_SIZE: usize = 16;
+/// `set bell=on` 时命令出错的蜂鸣频率和时长
+const ERROR_BEEP_HZ: u32 = 880;
+const ERROR_BEEP_MS: u64 = 60;
+/// scantest 等待按键的超时时间
+const SCANTEST_TIMEOUT_MS: u64 = 10_000;
+/// hexdump 一次最多显示的字节数
+const HEXDUMP_MAX_LEN: u64 = 4096;
+/// memtest 每测试这么多字节检查一次中止请求并刷新进度条
+const MEMTEST_CHUNK_SIZE: u64 = 4096;
+/// memtest 最多列出的错误地址数
+const MEMTEST_MAX_REPORTED: usize = 8;
 /// 提示符长度（"rust-os> "）
 const PROMPT_LENGTH: usize = 9;
+/// Tab 键对齐的列宽 (相对输入起点)
+const TAB_WIDTH: usize = 4;
+/// count 命令允许的最大值
+const COUNT_MAX: u64 = 1000;
+/// count 命令每个数字之间的间隔
+const COUNT_DELAY_MS: u64 = 200;
+/// progress 演示中进度条的宽度 (字符)
+const PROGRESS_WIDTH: usize = 40;
+/// progress 演示每一步之间的间隔
+const PROGRESS_STEP_MS: u64 = 40;
+/// clear 之后是否默认重新打印横幅 (也可以用 `clear --banner` 单次开启)
+const BANNER_ON_CLEAR: bool = false;
+/// 分屏模式默认的输入区行数
+const SPLIT_INPUT_ROWS: usize = 2;
+/// dialog 弹出时背景调暗的程度 (0-255)
+const DIALOG_DIM_ALPHA: u8 = 160;
+/// dialog 底部的提示
+const DIALOG_HINT: &str = "Press any key";
+/// ascii 表中一项 ("dec hex 字符") 的宽度，之后用制表符对齐
+const ASCII_ENTRY_LEN: usize = 8;
+/// top 的刷新间隔
+const TOP_REFRESH_MS: u64 = 1000;
+/// top 中退出的按键 (Q 的扫描码)
+const TOP_QUIT_SCANCODE: u8 = 0x10;
+
+/// 命令处理函数：参数迭代器 + 命令名之后的原始参数文本
+type Handler = fn(&mut Shell, core::str::SplitWhitespace, &str);
+
+/// 命令表中的一项
+struct Command {
+    /// 命令名
+    name: &'static str,
+    /// 命令的另一个名字
+    alias: Option<&'static str>,
+    /// help 中显示的用法
+    usage: &'static str,
+    /// help 中显示的说明
+    description: &'static str,
+    handler: Handler,
+}
+
+/// 所有内置命令，分发和 help 列表都由这张表生成
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "help",
+        alias: None,
+        usage: "help",
+        description: "Show this help message",
+        handler: |shell, _, _| shell.cmd_help(),
+    },
+    Command {
+        name: "clear",
+        alias: None,
+        usage: "clear [--banner]",
+        description: "Clear the screen",
+        handler: |_, args, _| cmd_clear(args),
+    },
+    Command {
+        name: "banner",
+        alias: None,
+        usage: "banner [text]",
+        description: "Show the OS banner, or text in large letters",
+        handler: |_, _, rest| cmd_banner(rest),
+    },
+    Command {
+        name: "version",
+        alias: None,
+        usage: "version",
+        description: "Show OS version information",
+        handler: |shell, _, _| shell.cmd_version(),
+    },
+    Command {
+        name: "echo",
+        alias: None,
+        usage: "echo <message>",
+        description: "Display a message",
+        handler: |shell, args, _| shell.cmd_echo(args),
+    },
+    Command {
+        name: "uptime",
+        alias: None,
+        usage: "uptime [--watch]",
+        description: "Show system runtime (--watch: live until a key)",
+        handler: |shell, args, _| shell.cmd_uptime(args),
+    },
+    Command {
+        name: "ticks",
+        alias: None,
+        usage: "ticks",
+        description: "Show raw timer counters in one line",
+        handler: |shell, _, _| shell.cmd_ticks(),
+    },
+    Command {
+        name: "sysinfo",
+        alias: None,
+        usage: "sysinfo",
+        description: "Show system information",
+        handler: |shell, _, _| shell.cmd_sysinfo(),
+    },
+    Command {
+        name: "display",
+        alias: None,
+        usage: "display",
+        description: "Show framebuffer mode and text grid",
+        handler: |shell, _, _| shell.cmd_display(),
+    },
+    Command {
+        name: "perf",
+        alias: None,
+        usage: "perf [on|off]",
+        description: "Toggle the scroll/redraw timing overlay",
+        handler: |shell, args, _| shell.cmd_perf(args),
+    },
+    Command {
+        name: "wrap",
+        alias: None,
+        usage: "wrap [on|off]",
+        description: "Toggle word wrap (keep words on one line)",
+        handler: |shell, args, _| shell.cmd_wrap(args),
+    },
+    Command {
+        name: "ascii",
+        alias: None,
+        usage: "ascii",
+        description: "Show printable ASCII characters with their codes",
+        handler: |_, _, _| cmd_ascii(),
+    },
+    Command {
+        name: "dialog",
+        alias: None,
+        usage: "dialog <message>",
+        description: "Show a message in a popup until a key is pressed",
+        handler: |shell, _, rest| shell.cmd_dialog(rest),
+    },
+    Command {
+        name: "split",
+        alias: None,
+        usage: "split [on [rows]|off]",
+        description: "Pin the input area to the bottom of the screen",
+        handler: |shell, args, _| shell.cmd_split(args),
+    },
+    Command {
+        name: "stats",
+        alias: None,
+        usage: "stats",
+        description: "Show shell statistics",
+        handler: |shell, _, _| shell.cmd_stats(),
+    },
+    Command {
+        name: "stopwatch",
+        alias: Some("sw"),
+        usage: "stopwatch <cmd>",
+        description: "start/stop/lap/reset (alias: sw)",
+        handler: |shell, args, _| shell.cmd_stopwatch(args),
+    },
+    Command {
+        name: "cpuinfo",
+        alias: None,
+        usage: "cpuinfo",
+        description: "Show CPU vendor, model and features",
+        handler: |shell, _, _| shell.cmd_cpuinfo(),
+    },
+    Command {
+        name: "draw",
+        alias: None,
+        usage: "draw",
+        description: "Draw a graphics demo in the corner",
+        handler: |shell, _, _| shell.cmd_draw(),
+    },
+    Command {
+        name: "run",
+        alias: None,
+        usage: "run [--keep-going] [script]",
+        description: "List built-in scripts or run one",
+        handler: |shell, args, _| shell.cmd_run(args),
+    },
+    Command {
+        name: "alias",
+        alias: None,
+        usage: "alias [name=cmd]",
+        description: "Define or list command aliases",
+        handler: |shell, _, rest| shell.cmd_alias(rest),
+    },
+    Command {
+        name: "unalias",
+        alias: None,
+        usage: "unalias <name>",
+        description: "Remove a command alias",
+        handler: |shell, args, _| shell.cmd_unalias(args),
+    },
+    Command {
+        name: "count",
+        alias: None,
+        usage: "count <n>",
+        description: "Count to n slowly (Esc to abort)",
+        handler: |shell, args, _| shell.cmd_count(args),
+    },
+    Command {
+        name: "progress",
+        alias: None,
+        usage: "progress",
+        description: "Animate a progress bar (Esc to abort)",
+        handler: |shell, _, _| shell.cmd_progress(),
+    },
+    Command {
+        name: "theme",
+        alias: None,
+        usage: "theme [name]",
+        description: "List color themes or switch to one",
+        handler: |shell, args, _| shell.cmd_theme(args),
+    },
+    Command {
+        name: "font",
+        alias: None,
+        usage: "font [name]",
+        description: "List fonts or switch to one",
+        handler: |shell, args, _| shell.cmd_font(args),
+    },
+    Command {
+        name: "cursor",
+        alias: None,
+        usage: "cursor [block|underline|bar]",
+        description: "Show or change the text cursor shape",
+        handler: |shell, args, _| shell.cmd_cursor(args),
+    },
+    Command {
+        name: "set",
+        alias: None,
+        usage: "set name=value",
+        description: "Set a variable, or history=N / inputsize=N / bell=on|off",
+        handler: |shell, _, rest| shell.cmd_set(rest),
+    },
+    Command {
+        name: "history",
+        alias: None,
+        usage: "history",
+        description: "List recently entered commands",
+        handler: |shell, _, _| shell.cmd_history(),
+    },
+    Command {
+        name: "unset",
+        alias: None,
+        usage: "unset <name>",
+        description: "Remove a shell variable",
+        handler: |shell, args, _| shell.cmd_unset(args),
+    },
+    Command {
+        name: "env",
+        alias: None,
+        usage: "env",
+        description: "List shell variables",
+        handler: |shell, _, _| shell.cmd_env(),
+    },
+    Command {
+        name: "breakpoint",
+        alias: Some("bp"),
+        usage: "breakpoint",
+        description: "Trigger an int3 to test the exception path (alias: bp)",
+        handler: |_, _, _| cmd_breakpoint(),
+    },
+    Command {
+        name: "loglevel",
+        alias: None,
+        usage: "loglevel [level]",
+        description: "Show or set the serial log level (info/warn/error)",
+        handler: |shell, args, _| shell.cmd_loglevel(args),
+    },
+    Command {
+        name: "memtest",
+        alias: None,
+        usage: "memtest [start size]",
+        description: "List usable RAM or walking-ones test a region",
+        handler: |shell, args, _| shell.cmd_memtest(args),
+    },
+    Command {
+        name: "hexdump",
+        alias: None,
+        usage: "hexdump [-p] <addr> <len>",
+        description: "Hex+ASCII dump of memory (-p: physical address)",
+        handler: |shell, args, _| shell.cmd_hexdump(args),
+    },
+    Command {
+        name: "readsec",
+        alias: None,
+        usage: "readsec [-m] <lba>",
+        description: "Hexdump a sector of the data disk (-m: boot disk)",
+        handler: |shell, args, _| shell.cmd_readsec(args),
+    },
+    Command {
+        name: "inb",
+        alias: None,
+        usage: "inb <port>",
+        description: "Read a byte from an I/O port",
+        handler: |shell, args, _| shell.cmd_inb(args),
+    },
+    Command {
+        name: "outb",
+        alias: None,
+        usage: "outb <port> <value> --force",
+        description: "Write a byte to an I/O port",
+        handler: |shell, args, _| shell.cmd_outb(args),
+    },
+    Command {
+        name: "irqstat",
+        alias: None,
+        usage: "irqstat",
+        description: "Show per-IRQ interrupt counts",
+        handler: |_, _, _| cmd_irqstat(),
+    },
+    Command {
+        name: "top",
+        alias: None,
+        usage: "top",
+        description: "Live full-screen view of uptime, IRQs and memory",
+        handler: |shell, _, _| shell.cmd_top(),
+    },
+    Command {
+        name: "drift",
+        alias: None,
+        usage: "drift [reset]",
+        description: "Compare PIT time against the RTC",
+        handler: |shell, args, _| shell.cmd_drift(args),
+    },
+    Command {
+        name: "tsc",
+        alias: None,
+        usage: "tsc",
+        description: "Show the time-stamp counter and CPU frequency",
+        handler: |shell, _, _| shell.cmd_tsc(),
+    },
+    Command {
+        name: "search",
+        alias: None,
+        usage: "search [-i|-c] <text>",
+        description: "Find text in the scrollback (-c: match case)",
+        handler: |shell, _, rest| shell.cmd_search(rest),
+    },
+    Command {
+        name: "scantest",
+        alias: None,
+        usage: "scantest",
+        description: "Print the raw scancode(s) of the next keypress",
+        handler: |_, _, _| cmd_scantest(),
+    },
+];
+
+/// 按命令名 (或别名) 查找命令
+fn find_command(name: &str) -> Option<&'static Command> {
+    COMMANDS
+        .iter()
+        .find(|command| command.name == name || command.alias == Some(name))
+}
+
+/// 命令的执行结果: 成功，或失败及非零退出码 (通过 `$?` 读取)
+pub type CommandResult = Result<(), u8>;
+
+/// 一般错误的退出码 (参数错误、操作失败等)
+const EXIT_FAILURE: u8 = 1;
+/// 命令行语法错误的退出码
+const EXIT_SYNTAX: u8 = 2;
+/// 找不到命令的退出码
+const EXIT_NOT_FOUND: u8 = 127;
+
+/// 条件执行的连接符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Chain {
+    /// `&&`: 前一条命令成功时才执行
+    And,
+    /// `||`: 前一条命令失败时才执行
+    Or,
+}
+
+impl Chain {
+    fn as_str(self) -> &'static str {
+        match self {
+            Chain::And => "&&",
+            Chain::Or => "||",
+        }
+    }
+}
+
+/// 在第一个 `&&` 或 `||` 处切开，返回之前的命令，以及连接符和之后的剩余部分
+fn split_chain(line: &str) -> (&str, Option<(Chain, &str)>) {
+    let operator = [(Chain::And, line.find("&&")), (Chain::Or, line.find("||"))]
+        .into_iter()
+        .filter_map(|(chain, index)| Some((chain, index?)))
+        .min_by_key(|&(_, index)| index);
+    match operator {
+        Some((chain, index)) => (&line[..index], Some((chain, &line[index + 2..]))),
+        None => (line, None),
+    }
+}
+
+/// 检查条件链中是否有空的命令，返回与空命令相邻的连接符
+fn find_empty_operand(line: &str) -> Option<Chain> {
+    let (mut command, mut rest) = split_chain(line);
+    let mut previous = None;
+    while let Some((chain, tail)) = rest {
+        if command.trim().is_empty() {
+            return Some(previous.unwrap_or(chain));
+        }
+        previous = Some(chain);
+        (command, rest) = split_chain(tail);
+    }
+    match previous {
+        Some(chain) if command.trim().is_empty() => Some(chain),
+        _ => None,
+    }
+}
+
+/// 内置脚本: 按顺序执行的一组命令 (每行可以用 && / || 连接)
+struct Script {
+    name: &'static str,
+    description: &'static str,
+    commands: &'static [&'static str],
+}
+
+/// run 命令可以执行的脚本
+const SCRIPTS: &[Script] = &[
+    Script {
+        name: "startup",
+        description: "Show version, uptime and system information",
+        commands: &["version", "uptime", "sysinfo"],
+    },
+    Script {
+        name: "demo",
+        description: "Show off themes, big text and graphics",
+        commands: &["theme matrix", "banner RUST", "draw", "progress", "theme default"],
+    },
+];
+
+/// `set 名称=值` 中不是变量而是 Shell 设置的名称
+const SETTINGS: &[&str] = &["history", "inputsize", "bell"];
+
+/// Shell 设置的值无效
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingError {
+    /// 不是数字
+    InvalidNumber(ParseError),
+    /// 超出允许的范围 [min, max]
+    OutOfRange { min: usize, max: usize },
+    /// 开关设置不是 on 或 off
+    NotOnOff,
+}
+
+/// 字符无法加入输入缓冲区的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputError {
+    /// 缓冲区已满 (放不下这个字符的 UTF-8 编码)
+    BufferFull,
+}
 
 /// Shell 状态
 pub struct Shell {
+    /// 输入缓冲区，按 UTF-8 存储完整的字符 (由 push_char 和 handle_backspace 保证)，因此始终是合法 UTF-8
     input_buffer: [u8; INPUT_BUFFER_SIZE],
     buffer_pos: usize,
     cursor_at_prompt_start: bool,
     command_count: u64, // ✨ 新增：跟踪执行的命令数量
+    aliases: AliasTable,
+    variables: VariableTable,
+    /// 上一条命令的执行结果 (决定提示符颜色和 `$?`)，启动后还没有执行过命令时为 None
+    last_status: Option<CommandResult>,
+    /// 当前提示符所在的逻辑行号，search 只搜索这一行之前的输出
+    prompt_line: usize,
+    /// 输入缓冲区的逻辑大小 (含结尾保留的一个字节)，不超过 INPUT_BUFFER_SIZE
+    input_limit: usize,
+    /// 最近输入的命令
+    history: History,
+    /// 正在执行 run 脚本 (脚本不能再调用 run)
+    in_script: bool,
+    /// 未知命令和语法错误时是否让 PC 扬声器蜂鸣 (默认关闭，QEMU 中保持安静)
+    bell: bool,
 }
 
 impl Shell {
@@ -24,11 +518,19 @@ impl Shell {
             buffer_pos: 0,
             cursor_at_prompt_start: false,
             command_count: 0,
+            aliases: AliasTable::new(),
+            variables: VariableTable::new(),
+            last_status: None,
+            prompt_line: 0,
+            input_limit: INPUT_BUFFER_SIZE,
+            history: History::new(),
+            in_script: false,
+            bell: false,
         }
     }
 
-    /// 处理字符输入
-    pub fn handle_char(&mut self, ch: char) {
+    /// 处理字符输入，返回普通字符是否被接受 (被接受时才需要回显)
+    pub fn handle_char(&mut self, ch: char) -> bool {
         match ch {
             '\n' => {
                 // Enter 键 - 处理当前命令
@@ -38,34 +540,70 @@ impl Shell {
                 // 退格键 - 从缓冲区删除字符
                 self.handle_backspace();
             },
-            ch if ch.is_ascii() && !ch.is_control() => {
-                // 普通字符 - 添加到缓冲区
-                self.add_char(ch);
+            '\t' => {
+                // Tab 键 - 插入空格到下一个制表位
+                self.insert_tab();
+            },
+            ch if !ch.is_control() => {
+                // 普通字符 - 添加到缓冲区 (缓冲区已满时会被拒绝)
+                return self.add_char(ch);
             },
             _ => {
                 // 忽略其他控制字符
             }
         }
+        false
     }
 
-    /// 添加字符到缓冲区
-    fn add_char(&mut self, ch: char) {
-        if self.buffer_pos < INPUT_BUFFER_SIZE - 1 {
-            self.input_buffer[self.buffer_pos] = ch as u8;
-            self.buffer_pos += 1;
-        } else {
-            // 缓冲区已满，显示警告
-            set_text_color(Color::RED, Color::BLACK);
-            print!(" [BUFFER FULL] ");
-            set_text_color(Color::WHITE, Color::BLACK);
+    /// 添加字符到缓冲区，失败时响铃并返回 false
+    /// 不向输入行插入任何提示文字，屏幕上的输入始终与缓冲区一致
+    fn add_char(&mut self, ch: char) -> bool {
+        match self.push_char(ch) {
+            Ok(()) => true,
+            Err(_) => {
+                alert();
+                false
+            }
+        }
+    }
+
+    /// 把字符写入缓冲区 (不做任何输出)
+    /// 按 UTF-8 编码写入 (组合键可以输入非 ASCII 字符)，放不下整个字符时拒绝
+    fn push_char(&mut self, ch: char) -> Result<(), InputError> {
+        let len = ch.len_utf8();
+        if self.buffer_pos + len > self.input_limit - 1 {
+            return Err(InputError::BufferFull);
+        }
+        ch.encode_utf8(&mut self.input_buffer[self.buffer_pos..self.buffer_pos + len]);
+        self.buffer_pos += len;
+        Ok(())
+    }
+
+    /// 当前输入的文字
+    fn input(&self) -> &str {
+        core::str::from_utf8(&self.input_buffer[..self.buffer_pos]).unwrap_or("")
+    }
+
+    /// 插入空格直到下一个制表位，并回显到屏幕
+    fn insert_tab(&mut self) {
+        let spaces = TAB_WIDTH - (self.input().chars().count() % TAB_WIDTH);
+        for _ in 0..spaces {
+            if !self.add_char(' ') {
+                break;
+            }
+            print!(" ");
         }
     }
 
-    /// 处理退格
+    /// 处理退格 - 删除最后一个完整的字符 (可能是多个字节)
     fn handle_backspace(&mut self) {
-        if self.buffer_pos > 0 {
+        while self.buffer_pos > 0 {
             self.buffer_pos -= 1;
-            self.input_buffer[self.buffer_pos] = 0;
+            let byte = core::mem::take(&mut self.input_buffer[self.buffer_pos]);
+            // UTF-8 后续字节的高两位是 10，遇到首字节时停止
+            if byte & 0xC0 != 0x80 {
+                break;
+            }
         }
     }
 
@@ -74,26 +612,68 @@ impl Shell {
         let mut temp_buffer = [0u8; INPUT_BUFFER_SIZE];
         let buffer_len = self.buffer_pos;
         
-        for i in 0..buffer_len {
-            temp_buffer[i] = self.input_buffer[i];
-        }
+        temp_buffer[..buffer_len].copy_from_slice(&self.input_buffer[..buffer_len]);
         
+        // 缓冲区始终是合法 UTF-8 (见 push_char)，转换不会失败
+        let command_str = core::str::from_utf8(&temp_buffer[..buffer_len]).unwrap_or("");
+        self.history.push(command_str.trim());
+
+        // 分屏模式下回到输出区，先把提交的命令行抄到输出区，再输出命令结果
+        let split = crate::with_writer(|writer| writer.focus_output()).unwrap_or(false);
+        if split {
+            self.print_prompt();
+            print!("{}", command_str);
+        }
         println!();
         
-        if buffer_len > 0 {
-            if let Ok(command_str) = core::str::from_utf8(&temp_buffer[..buffer_len]) {
-                let command = command_str.trim();
-                if !command.is_empty() {
-                    self.command_count += 1; // ✨ 增加命令计数
-                    self.execute_command(command);
-                }
+        // 执行期间输入的按键先留在队列里，不与命令输出混在一起
+        crate::input::set_command_running(true);
+        // 用 ';' 分隔的多条命令依次执行，跳过空段
+        for segment in command_str.split(';') {
+            if !segment.trim().is_empty() {
+                let _ = self.execute_chain(segment);
             }
         }
+        crate::input::set_command_running(false);
         
         self.clear_buffer();
         self.show_prompt();
     }
 
+    /// 执行用 `&&` / `||` 连接的命令，从左到右求值 (两者优先级相同)
+    /// 跳过的命令不改变结果；有空命令时整条都不执行
+    fn execute_chain(&mut self, line: &str) -> CommandResult {
+        if let Some(chain) = find_empty_operand(line) {
+            self.alert_error();
+            highlight_text(Color::RED);
+            println!("Syntax error: missing command next to '{}'", chain.as_str());
+            reset_text_color();
+            self.fail_with(EXIT_SYNTAX);
+            return Err(EXIT_SYNTAX);
+        }
+
+        let (command, mut rest) = split_chain(line);
+        let mut result = self.execute_counted(command.trim());
+        while let Some((chain, tail)) = rest {
+            let (command, next) = split_chain(tail);
+            let run = match chain {
+                Chain::And => result.is_ok(),
+                Chain::Or => result.is_err(),
+            };
+            if run {
+                result = self.execute_counted(command.trim());
+            }
+            rest = next;
+        }
+        result
+    }
+
+    /// 执行一条命令并计数
+    fn execute_counted(&mut self, command: &str) -> CommandResult {
+        self.command_count += 1; // ✨ 增加命令计数
+        self.execute_command(command)
+    }
+
     /// 清空输入缓冲区
     fn clear_buffer(&mut self) {
         self.buffer_pos = 0;
@@ -103,36 +683,172 @@ impl Shell {
     }
 
     /// 执行命令
-    fn execute_command(&mut self, command: &str) {
+    /// 返回的结果同时保存在 last_status 中
+    fn execute_command(&mut self, command: &str) -> CommandResult {
+        // 每条命令开始时清除之前的 Esc 中止请求
+        crate::input::clear_abort();
+
+        // 先展开别名 (只展开一层，避免循环)
+        let mut expanded = [0u8; INPUT_BUFFER_SIZE];
+        let command = match self.expand_alias(command, &mut expanded) {
+            Some(len) => core::str::from_utf8(&expanded[..len]).unwrap_or(command),
+            None => command,
+        };
+
+        // 再替换 $变量
+        let mut substituted = [0u8; INPUT_BUFFER_SIZE];
+        let command = if command.contains('$') {
+            match self.expand_variables(command, &mut substituted) {
+                Some(len) => core::str::from_utf8(&substituted[..len]).unwrap_or(command),
+                None => {
+                    highlight_text(Color::RED);
+                    println!("Command too long after variable expansion.");
+                    reset_text_color();
+                    self.fail();
+                    return Err(EXIT_FAILURE);
+                }
+            }
+        } else {
+            command
+        };
+
+        // 展开 `$?` 之后才开始新命令，默认成功，出错的路径调用 fail()
+        self.last_status = Some(Ok(()));
+
         let mut parts = command.split_whitespace();
         
         if let Some(cmd) = parts.next() {
-            match cmd {
-                "help" => self.cmd_help(),
-                "clear" => self.cmd_clear(),
-                "version" => self.cmd_version(),
-                "echo" => self.cmd_echo(parts),
-                "uptime" => self.cmd_uptime(),
-                "sysinfo" => self.cmd_sysinfo(), // ✨ 新增系统信息命令
-                "stats" => self.cmd_stats(),     // ✨ 新增统计信息命令
-                _ => {
-                    set_text_color(Color::RED, Color::BLACK);
+            // 命令名之后的原始参数文本
+            let rest = command[cmd.len()..].trim_start();
+
+            match find_command(cmd) {
+                Some(command) => (command.handler)(self, parts, rest),
+                None => {
+                    self.alert_error();
+                    highlight_text(Color::RED);
                     println!("Unknown command: '{}'", cmd);
-                    set_text_color(Color::YELLOW, Color::BLACK);
+                    highlight_text(Color::YELLOW);
                     println!("Type 'help' for available commands.");
-                    set_text_color(Color::WHITE, Color::BLACK);
+                    reset_text_color();
+                    self.fail_with(EXIT_NOT_FOUND);
                 }
             }
         }
+        self.last_status.unwrap_or(Ok(()))
+    }
+
+    /// 如果第一个单词是别名，把展开结果和剩余参数写入 `out`，返回长度
+    fn expand_alias(&self, command: &str, out: &mut [u8]) -> Option<usize> {
+        let name = command.split_whitespace().next()?;
+        let value = self.aliases.get(name)?;
+        let rest = &command[name.len()..];
+
+        let len = value.len() + rest.len();
+        if len > out.len() {
+            return None;
+        }
+        out[..value.len()].copy_from_slice(value.as_bytes());
+        out[value.len()..len].copy_from_slice(rest.as_bytes());
+        Some(len)
+    }
+
+    /// 把 `$name` 替换为变量值、`$?` 替换为上一条命令的退出码写入 `out`，未定义的变量替换为空
+    /// `$` 后面不是变量名字符时按原样保留；结果超出 `out` 时返回 None
+    fn expand_variables(&self, command: &str, out: &mut [u8]) -> Option<usize> {
+        use core::fmt::Write;
+
+        let mut len = 0;
+        let mut push = |bytes: &[u8], len: &mut usize| -> Option<()> {
+            let end = *len + bytes.len();
+            out.get_mut(*len..end)?.copy_from_slice(bytes);
+            *len = end;
+            Some(())
+        };
+
+        let mut rest = command;
+        while let Some(dollar) = rest.find('$') {
+            push(&rest.as_bytes()[..dollar], &mut len)?;
+            let after = &rest[dollar + 1..];
+            let name_len = after.find(|c: char| !vars::is_name_char(c)).unwrap_or(after.len());
+            if let Some(after_code) = after.strip_prefix('?') {
+                // $? - 上一条命令的退出码
+                let mut code = crate::format::LineBuffer::<4>::new();
+                let _ = write!(code, "{}", self.exit_code());
+                push(code.as_str().as_bytes(), &mut len)?;
+                rest = after_code;
+                continue;
+            }
+            if name_len == 0 {
+                push(b"$", &mut len)?;
+            } else {
+                let value = self.variables.get(&after[..name_len]).unwrap_or("");
+                push(value.as_bytes(), &mut len)?;
+            }
+            rest = &after[name_len..];
+        }
+        push(rest.as_bytes(), &mut len)?;
+        Some(len)
+    }
+
+    /// 未知命令或语法错误: 闪烁屏幕，`set bell=on` 时再蜂鸣一声
+    fn alert_error(&self) {
+        alert();
+        if self.bell {
+            crate::speaker::beep(ERROR_BEEP_HZ, ERROR_BEEP_MS);
+        }
+    }
+
+    /// 统一的数字参数错误提示，和其它语法错误一样闪烁屏幕 (`set bell=on` 时蜂鸣)
+    fn print_invalid_number(&self, arg: &str, error: ParseError) {
+        highlight_text(Color::RED);
+        match error {
+            ParseError::Overflow => println!("Invalid number: '{}' (too large)", arg),
+            ParseError::Empty | ParseError::InvalidDigit => println!("Invalid number: '{}'", arg),
+        }
+        reset_text_color();
+        self.alert_error();
+    }
+
+    /// 把当前命令标记为失败 (退出码 1)
+    fn fail(&mut self) {
+        self.fail_with(EXIT_FAILURE);
+    }
+
+    /// 以指定的非零退出码把当前命令标记为失败
+    fn fail_with(&mut self, code: u8) {
+        self.last_status = Some(Err(code));
+    }
+
+    /// 上一条命令的退出码，成功或还没有执行过命令时为 0
+    fn exit_code(&self) -> u8 {
+        match self.last_status {
+            Some(Err(code)) => code,
+            Some(Ok(())) | None => 0,
+        }
     }
 
-    /// 显示提示符
+    /// 显示提示符 (上一条命令失败时为红色)
     pub fn show_prompt(&mut self) {
-        set_text_color(Color::GREEN, Color::BLACK);
+        // 分屏模式下提示符和回显写在底部的输入区
+        crate::with_writer(|writer| writer.focus_input());
+        self.print_prompt();
+        crate::with_writer(|writer| {
+            writer.mark_input_start();
+            self.prompt_line = writer.current_line();
+        });
+        self.cursor_at_prompt_start = true;
+    }
+
+    /// 输出提示符，失败后用红色
+    fn print_prompt(&self) {
+        let prompt_color = match self.last_status {
+            Some(Err(_)) => Color::RED,
+            Some(Ok(())) | None => crate::theme::active().prompt,
+        };
+        highlight_text(prompt_color);
         print!("rust-os");
-        set_text_color(Color::WHITE, Color::BLACK);
+        reset_text_color();
         print!("> ");
-        self.cursor_at_prompt_start = true;
     }
 
     /// 检查是否可以退格
@@ -144,59 +860,52 @@ impl Shell {
 
     /// help 命令
     fn cmd_help(&self) {
-        set_text_color(Color::CYAN, Color::BLACK);
-        println!("=== Rust OS Shell Commands ===");
-        set_text_color(Color::WHITE, Color::BLACK);
-        println!("help              - Show this help message");
-        println!("clear             - Clear the screen");
-        println!("version           - Show OS version information");
-        println!("echo <message>    - Display a message");
-        println!("uptime            - Show system runtime");
-        println!("sysinfo           - Show system information"); // ✨ 新增
-        println!("stats             - Show shell statistics");   // ✨ 新增
-        println!();
-        set_text_color(Color::YELLOW, Color::BLACK);
-        println!("Examples:");
-        println!("  echo Hello from Rust OS!");
-        println!("  uptime");
-        println!("  sysinfo");
-        println!("  stats");
-        println!();
-        println!("Tips:");
-        println!("- Use Shift/Caps Lock for uppercase");  
-        println!("- Use Backspace to edit your input");
-        println!("- Use Tab for indentation");
-        println!("- All commands are case-sensitive");
-        set_text_color(Color::WHITE, Color::BLACK);
-    }
-
-    /// clear 命令
-    fn cmd_clear(&mut self) {
-        if let Some(ref mut writer) = crate::WRITER.lock().as_mut() {
-            writer.clear_screen();
+        let mut pager = Pager::new();
+        highlight_text(Color::CYAN);
+        pager.line("=== Rust OS Shell Commands ===");
+        reset_text_color();
+        for command in COMMANDS {
+            pager.line_fmt(format_args!("{:<17} - {}", command.usage, command.description));
         }
-        
-        set_text_color(Color::CYAN, Color::BLACK);
-        println!("=== Rust OS v0.3.0 - Time System ===");
-        set_text_color(Color::WHITE, Color::BLACK);
-        println!("Screen cleared. Type 'help' for commands.");
+        pager.line("");
+        highlight_text(Color::YELLOW);
+        pager.line("\x1b[4mExamples:\x1b[24m");
+        pager.line("  echo Hello from Rust OS!");
+        pager.line("  uptime");
+        pager.line("  sysinfo");
+        pager.line("  stats");
+        pager.line("  sw start");
+        pager.line("  clear; version; uptime");
+        pager.line("  set name=World; echo Hello $name");
+        pager.line("");
+        pager.line("\x1b[4mTips:\x1b[24m");
+        pager.line("- Use Shift/Caps Lock for uppercase");  
+        pager.line("- Use Backspace to edit your input");
+        pager.line("- Tab inserts spaces up to the next tab stop");
+        pager.line("- Page Up/Page Down scroll through earlier output");
+        pager.line("- Separate commands with ';' to run them in sequence");
+        pager.line("- Long output pauses: Space for the next page, any key for the rest");
+        pager.line("- Ctrl+Alt+D dumps the screen text to the serial port");
+        pager.line("- All commands are case-sensitive");
+        reset_text_color();
     }
 
     /// version 命令
     fn cmd_version(&self) {
-        set_text_color(Color::CYAN, Color::BLACK);
+        highlight_text(Color::CYAN);
         println!("=== Rust OS Version Information ===");
-        set_text_color(Color::WHITE, Color::BLACK);
+        reset_text_color();
         println!("OS Name:      Rust OS");
-        println!("Version:      0.3.0");
+        println!("Version:      {}", VERSION);
         println!("Codename:     \"Temporal\"");
         println!("Architecture: x86_64");
-        println!("Build:        Debug");
-        println!("Compiler:     rustc (nightly)");
+        println!("Commit:       {}", GIT_HASH);
+        println!("Build:        {}", BUILD_PROFILE);
+        println!("Compiler:     {}", RUSTC_VERSION);
         println!();
-        set_text_color(Color::GREEN, Color::BLACK);
+        highlight_text(Color::GREEN);
         println!("Core Features:");
-        set_text_color(Color::WHITE, Color::BLACK);
+        reset_text_color();
         println!("✓ Graphical framebuffer output");
         println!("✓ 8259 PIC interrupt controller"); 
         println!("✓ PS/2 keyboard driver with full layout");
@@ -208,7 +917,7 @@ impl Shell {
 
     /// echo 命令
     fn cmd_echo(&self, mut args: core::str::SplitWhitespace) {
-        set_text_color(Color::WHITE, Color::BLACK);
+        reset_text_color();
         
         let mut first = true;
         for arg in args {
@@ -222,85 +931,205 @@ impl Shell {
     }
 
     /// uptime 命令
-    fn cmd_uptime(&self) {
+    fn cmd_uptime(&mut self, mut args: core::str::SplitWhitespace) {
         if !crate::time::is_initialized() {
-            set_text_color(Color::RED, Color::BLACK);
+            highlight_text(Color::RED);
             println!("Time system not initialized!");
-            set_text_color(Color::WHITE, Color::BLACK);
+            reset_text_color();
+            self.fail();
             return;
         }
 
-        let uptime = crate::time::get_uptime();
-        let formatted = uptime.format_detailed();
-        let (days, hours, minutes, seconds, milliseconds) = formatted.detailed_format();
-        let total_ms = formatted.total_milliseconds();
+        match args.next() {
+            None => {},
+            Some("--watch") => return watch_uptime(),
+            Some(other) => {
+                highlight_text(Color::RED);
+                println!("Unknown option: '{}' (usage: uptime [--watch])", other);
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        }
+
+        let total_ms = crate::time::get_uptime_ms();
         let tick_count = crate::time::get_tick_count();
+        let (frequency, interval_ms) = crate::pit::get_info();
 
-        set_text_color(Color::CYAN, Color::BLACK);
+        highlight_text(Color::CYAN);
         println!("=== System Uptime ===");
-        set_text_color(Color::WHITE, Color::BLACK);
+        reset_text_color();
 
-        if days > 0 {
-            println!("Uptime: {} days, {:02}:{:02}:{:02}.{:03}", 
-                     days, hours, minutes, seconds, milliseconds);
-        } else {
-            println!("Uptime: {:02}:{:02}:{:02}.{:03}", 
-                     hours, minutes, seconds, milliseconds);
-        }
+        print!("Uptime: ");
+        emit(|out| format::write_uptime(out, total_ms));
+        println!();
 
         println!();
         
-        set_text_color(Color::YELLOW, Color::BLACK);
+        highlight_text(Color::YELLOW);
         println!("Timer Details:");
-        set_text_color(Color::WHITE, Color::BLACK);
-        println!("  Total milliseconds: {}", total_ms);
-        println!("  Timer ticks:        {}", tick_count);
-        println!("  Timer frequency:    100 Hz");
-        println!("  Tick interval:      10 ms");
+        reset_text_color();
+        emit(|out| format::write_timer_details(out, total_ms, tick_count, frequency, interval_ms));
+        println!("  Precise uptime:     {} us", crate::time::now_micros());
+    }
+
+    /// ticks 命令 - 一行显示原始定时器数据，用于调试 PIT
+    fn cmd_ticks(&mut self) {
+        if !crate::time::is_initialized() {
+            highlight_text(Color::RED);
+            println!("Time system not initialized!");
+            reset_text_color();
+            self.fail();
+            return;
+        }
+
+        let ticks = crate::time::get_tick_count();
+        let uptime_ms = crate::time::get_uptime_ms();
+        let (frequency, interval_ms) = crate::pit::get_info();
+        let count = crate::pit::read_count();
+        println!(
+            "ticks={} freq={}Hz ms_per_tick={} uptime_ms={} pit_count={}",
+            ticks, frequency, interval_ms, uptime_ms, count
+        );
+    }
+
+    /// drift 命令 - 比较参考点以来 PIT 和 RTC 走过的时间
+    /// 第一次运行 (或 drift reset) 只设置参考点
+    fn cmd_drift(&mut self, mut args: core::str::SplitWhitespace) {
+        if !crate::time::is_initialized() {
+            highlight_text(Color::RED);
+            println!("Time system not initialized!");
+            reset_text_color();
+            self.fail();
+            return;
+        }
+
+        let reset = match args.next() {
+            Some("reset") => true,
+            Some(other) => {
+                highlight_text(Color::RED);
+                println!("Unknown option: '{}'. Usage: drift [reset]", other);
+                reset_text_color();
+                self.fail();
+                return;
+            },
+            None => false,
+        };
+
+        println!("Waiting for the RTC second boundary...");
+        let result = if reset {
+            crate::time::set_drift_reference().map(|()| None)
+        } else {
+            match crate::time::check_drift() {
+                Ok(report) => Ok(Some(report)),
+                Err(crate::time::DriftError::NoReference) => Ok(None),
+                Err(error) => Err(error),
+            }
+        };
+
+        match result {
+            Ok(Some(report)) => {
+                println!("RTC elapsed:  {} ms", report.rtc_elapsed_ms);
+                println!("PIT elapsed:  {} ms", report.pit_elapsed_ms);
+                println!("Drift:        {:+} ms (PIT - RTC)", report.delta_ms);
+            },
+            Ok(None) => println!("Reference point set. Run 'drift' again later to compare."),
+            Err(_) => {
+                highlight_text(Color::RED);
+                println!("RTC did not tick within the timeout.");
+                reset_text_color();
+                self.fail();
+            },
+        }
+    }
+
+    /// tsc 命令 - 显示 TSC 读数和校准得到的频率 (尚未校准时先校准)
+    fn cmd_tsc(&mut self) {
+        let khz = match crate::time::tsc_khz().or_else(crate::time::calibrate_tsc) {
+            Some(khz) => khz,
+            None => {
+                highlight_text(Color::RED);
+                println!("TSC calibration failed (time system not initialized?)");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
 
-        if tick_count > 0 {
-            let avg_ms_per_tick = total_ms as f32 / tick_count as f32;
-            println!("  Average per tick:   {:.2} ms", avg_ms_per_tick);
+        let tsc = crate::cpu::rdtsc();
+        println!("TSC:         {}", tsc);
+        println!("Frequency:   {}.{:03} MHz", khz / 1000, khz % 1000);
+        if let Some(ns) = crate::time::rdtsc_to_ns(tsc) {
+            println!("Since reset: {}.{:03} s", ns / 1_000_000_000, ns / 1_000_000 % 1000);
         }
     }
 
     /// ✨ sysinfo 命令 - 显示系统信息
     fn cmd_sysinfo(&self) {
-        set_text_color(Color::CYAN, Color::BLACK);
+        highlight_text(Color::CYAN);
         println!("=== System Information ===");
-        set_text_color(Color::WHITE, Color::BLACK);
+        reset_text_color();
         
         // 基本系统信息
-        println!("Kernel:           Rust OS v0.3.0");
+        println!("Kernel:           Rust OS v{} ({})", VERSION, GIT_HASH);
         println!("Architecture:     x86_64");
         println!("Boot Protocol:    UEFI/BIOS (bootloader 0.11)");
         
         // 时间信息
         if crate::time::is_initialized() {
-            let uptime_info = crate::time::get_uptime();
-            let formatted = uptime_info.format_detailed();
-            let (hours, minutes, seconds) = formatted.short_format();
-            println!("Uptime:           {:02}:{:02}:{:02}", hours, minutes, seconds);
+            let uptime_ms = crate::time::get_uptime_ms();
+            print!("Uptime:           ");
+            emit(|out| format::write_hms(out, uptime_ms));
+            println!();
+        }
+        if let (Some(boot), Some(now)) = (crate::time::boot_time(), crate::time::current_datetime()) {
+            println!("Booted at:        {}", boot);
+            println!("Date/time:        {}", now);
         }
         
         println!();
         
         // 硬件信息
-        set_text_color(Color::YELLOW, Color::BLACK);
+        highlight_text(Color::YELLOW);
         println!("Hardware:");
-        set_text_color(Color::WHITE, Color::BLACK);
-        println!("  CPU:            x86_64 compatible");
+        reset_text_color();
+        let cpu = crate::cpu::detect();
+        println!("  CPU:            {}", cpu.brand().unwrap_or(cpu.vendor()));
         println!("  Timer:          Intel 8253 PIT @ 100 Hz");
+        println!(
+            "  Soft timers:    {}/{} active",
+            crate::time::active_timer_count(),
+            crate::time::MAX_TIMERS
+        );
         println!("  Interrupt:      Intel 8259 PIC");
-        println!("  Keyboard:       PS/2 compatible");
-        println!("  Display:        Framebuffer graphics");
+        let (acks, resends) = crate::ps2::response_counts();
+        println!("  Keyboard:       PS/2 compatible ({} ACKs, {} resends)", acks, resends);
+        let double_buffered = crate::WRITER
+            .lock()
+            .as_ref()
+            .is_some_and(|writer| writer.is_double_buffered());
+        match *crate::FRAMEBUFFER_INFO.lock() {
+            Some(info) => println!(
+                "  Display:        {}x{} {:?}, {} bytes/pixel{}",
+                info.width,
+                info.height,
+                info.pixel_format,
+                info.bytes_per_pixel,
+                if double_buffered { " (double-buffered)" } else { "" }
+            ),
+            None => println!("  Display:        None (serial console)"),
+        }
         
         println!();
         
-        // 内存信息 (模拟数据，因为还没有内存管理器)
-        set_text_color(Color::YELLOW, Color::BLACK);
+        // 内存信息 (大小是估计值，因为还没有内存管理器)
+        highlight_text(Color::YELLOW);
         println!("Memory:");
-        set_text_color(Color::WHITE, Color::BLACK);
+        reset_text_color();
+        match crate::memory::physical_memory_offset() {
+            Some(offset) => println!("  Physical map:   offset {:#x}", offset),
+            None => println!("  Physical map:   none (identity mapping only)"),
+        }
         println!("  Kernel size:    ~60 KB");
         println!("  Runtime usage:  < 1 MB");
         println!("  Memory model:   Static allocation");
@@ -308,51 +1137,1857 @@ impl Shell {
         println!();
         
         // 功能状态
-        set_text_color(Color::GREEN, Color::BLACK);
+        highlight_text(Color::GREEN);
         println!("✓ All systems operational");
-        set_text_color(Color::WHITE, Color::BLACK);
+        reset_text_color();
     }
 
     /// ✨ stats 命令 - 显示Shell统计信息
     fn cmd_stats(&self) {
-        set_text_color(Color::CYAN, Color::BLACK);
+        highlight_text(Color::CYAN);
         println!("=== Shell Statistics ===");
-        set_text_color(Color::WHITE, Color::BLACK);
-        
-        println!("Commands executed:    {}", self.command_count);
-        println!("Input buffer size:    {} bytes", INPUT_BUFFER_SIZE);
-        println!("Current buffer used:  {} bytes", self.buffer_pos);
-        println!("Available commands:   7");
+        reset_text_color();
         
         // 计算一些有趣的统计数据
-        if crate::time::is_initialized() {
-            let uptime_ms = crate::time::get_uptime_ms();
-            if uptime_ms > 0 && self.command_count > 0 {
-                let avg_time_between_commands = uptime_ms / self.command_count;
-                println!("Avg time per command: {} ms", avg_time_between_commands);
-            }
-        }
+        let uptime_ms = if crate::time::is_initialized() {
+            Some(crate::time::get_uptime_ms())
+        } else {
+            None
+        };
+        emit(|out| format::write_command_stats(
+            out,
+            self.command_count,
+            INPUT_BUFFER_SIZE,
+            self.buffer_pos,
+            COMMANDS.len(),
+            uptime_ms,
+        ));
         
         println!();
         
-        set_text_color(Color::YELLOW, Color::BLACK);
+        highlight_text(Color::YELLOW);
         println!("Session Information:");
-        set_text_color(Color::WHITE, Color::BLACK);
+        reset_text_color();
         
-        if crate::time::is_initialized() {
-            let uptime_seconds = crate::time::get_uptime().get_uptime_seconds();
-            if uptime_seconds > 0 {
-                let commands_per_minute = (self.command_count * 60) / uptime_seconds;
-                println!("  Commands per minute: {}", commands_per_minute);
-            }
-        }
+        let uptime_seconds = uptime_ms.map(|ms| ms / 1000);
+        emit(|out| format::write_session_rates(out, self.command_count, uptime_seconds));
         
         println!("  Shell status:        Active");
         println!("  Error count:         0"); // 简化版本，假设无错误
         
-        set_text_color(Color::GREEN, Color::BLACK);
+        highlight_text(Color::GREEN);
         println!();
         println!("✓ Shell running smoothly!");
-        set_text_color(Color::WHITE, Color::BLACK);
+        reset_text_color();
+    }
+
+    /// cpuinfo 命令 - 显示 CPUID 检测结果
+    fn cmd_cpuinfo(&self) {
+        let cpu = crate::cpu::detect();
+        let (family, model, stepping) = cpu.family_model_stepping();
+
+        highlight_text(Color::CYAN);
+        println!("=== CPU Information ===");
+        reset_text_color();
+        println!("Vendor:        {}", cpu.vendor());
+        match cpu.brand() {
+            Some(brand) => println!("Brand:         {}", brand),
+            None => println!("Brand:         (extended CPUID leaves unavailable)"),
+        }
+        println!("Family:        {:#x}", family);
+        println!("Model:         {:#x}", model);
+        println!("Stepping:      {}", stepping);
+        println!("Max leaf:      {:#x}", cpu.max_leaf());
+        if cpu.max_extended_leaf() > 0 {
+            println!("Max ext leaf:  {:#x}", cpu.max_extended_leaf());
+        } else {
+            println!("Max ext leaf:  (none)");
+        }
+
+        println!();
+        highlight_text(Color::YELLOW);
+        println!("Features:");
+        reset_text_color();
+
+        // 每行显示 8 个功能名称
+        let mut count = 0;
+        for feature in cpu.features() {
+            if count % 8 == 0 {
+                print!(" ");
+            }
+            print!(" {}", feature);
+            count += 1;
+            if count % 8 == 0 {
+                println!();
+            }
+        }
+        if count % 8 != 0 {
+            println!();
+        }
+        if count == 0 {
+            println!("  (none detected)");
+        }
+    }
+
+    /// draw 命令 - 在右上角演示图形绘制
+    fn cmd_draw(&self) {
+        let size = 120;
+        crate::with_writer(|writer| {
+            let x = writer.width().saturating_sub(size + 16);
+            let y = 16;
+
+            writer.fill_rect(x, y, size, size, Color::BLUE);
+            writer.draw_rect(x, y, size, size, Color::WHITE);
+            writer.draw_line(x, y, x + size - 1, y + size - 1, Color::YELLOW);
+            writer.draw_line(x + size - 1, y, x, y + size - 1, Color::YELLOW);
+            writer.fill_rect(x + size / 4, y + size / 4, size / 2, size / 2, Color::RED);
+            writer.present();
+        });
+        println!("Drew a {}x{} demo rectangle in the top-right corner.", size, size);
+    }
+
+    /// alias 命令 - 无参数时列出所有别名，否则定义 name=command
+    fn cmd_alias(&mut self, definition: &str) {
+        if definition.is_empty() {
+            let mut count = 0;
+            for (name, value) in self.aliases.iter() {
+                println!("alias {}='{}'", name, value);
+                count += 1;
+            }
+            if count == 0 {
+                println!("No aliases defined.");
+            }
+            return;
+        }
+
+        let (name, value) = match definition.split_once('=') {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => {
+                highlight_text(Color::RED);
+                println!("Usage: alias name=command");
+                reset_text_color();
+                self.fail();
+                return;
+            }
+        };
+
+        // 不允许覆盖别名管理命令本身，否则无法恢复
+        if name == "alias" || name == "unalias" {
+            highlight_text(Color::RED);
+            println!("Cannot alias '{}'.", name);
+            reset_text_color();
+            self.fail();
+            return;
+        }
+
+        if let Err(error) = self.aliases.set(name, value) {
+            print_alias_error(error, name);
+            self.fail();
+        }
+    }
+
+    /// unalias 命令
+    fn cmd_unalias(&mut self, mut args: core::str::SplitWhitespace) {
+        match args.next() {
+            Some(name) => {
+                if let Err(error) = self.aliases.remove(name) {
+                    print_alias_error(error, name);
+                    self.fail();
+                }
+            },
+            None => {
+                highlight_text(Color::RED);
+                println!("Usage: unalias <name>");
+                reset_text_color();
+                self.fail();
+            },
+        }
+    }
+
+    /// set 命令 - 设置变量 name=value
+    fn cmd_set(&mut self, assignment: &str) {
+        let (name, value) = match assignment.split_once('=') {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => {
+                highlight_text(Color::RED);
+                println!("Usage: set name=value");
+                reset_text_color();
+                self.fail();
+                return;
+            }
+        };
+
+        if SETTINGS.contains(&name) {
+            if let Err(error) = self.apply_setting(name, value) {
+                match error {
+                    SettingError::InvalidNumber(error) => self.print_invalid_number(value, error),
+                    SettingError::OutOfRange { min, max } => {
+                        highlight_text(Color::RED);
+                        println!("Invalid value for {}: must be {}-{}", name, min, max);
+                        reset_text_color();
+                    },
+                    SettingError::NotOnOff => {
+                        highlight_text(Color::RED);
+                        println!("Invalid value for {}: must be on or off", name);
+                        reset_text_color();
+                    },
+                }
+                self.fail();
+            }
+            return;
+        }
+
+        if let Err(error) = self.variables.set(name, value) {
+            print_variable_error(error, name);
+            self.fail();
+        }
+    }
+
+    /// 修改 SETTINGS 中的一项设置；出错时不做任何修改
+    fn apply_setting(&mut self, name: &str, value: &str) -> Result<(), SettingError> {
+        if name == "bell" {
+            self.bell = match value {
+                "on" => true,
+                "off" => false,
+                _ => return Err(SettingError::NotOnOff),
+            };
+            return Ok(());
+        }
+        let value = parse_u64(value).map_err(SettingError::InvalidNumber)?;
+        let in_range = |min: usize, max: usize| match usize::try_from(value) {
+            Ok(value) if (min..=max).contains(&value) => Ok(value),
+            _ => Err(SettingError::OutOfRange { min, max }),
+        };
+        match name {
+            "history" => {
+                let capacity = in_range(1, history::MAX_HISTORY)?;
+                // 范围已经检查过
+                let _ = self.history.set_capacity(capacity);
+            },
+            "inputsize" => {
+                // 当前输入在命令执行时已经提交，缩小不会截断正在编辑的内容
+                self.input_limit = in_range(MIN_INPUT_SIZE, INPUT_BUFFER_SIZE)?;
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    /// history 命令 - 从旧到新列出最近输入的命令
+    fn cmd_history(&self) {
+        if self.history.is_empty() {
+            println!("No commands in history.");
+            return;
+        }
+        for (number, command) in self.history.iter().enumerate() {
+            println!("{:>4}  {}", number + 1, command);
+        }
+        println!("({} of {} kept)", self.history.len(), self.history.capacity());
+    }
+
+    /// unset 命令
+    fn cmd_unset(&mut self, mut args: core::str::SplitWhitespace) {
+        match args.next() {
+            Some(name) => {
+                if let Err(error) = self.variables.remove(name) {
+                    print_variable_error(error, name);
+                    self.fail();
+                }
+            },
+            None => {
+                highlight_text(Color::RED);
+                println!("Usage: unset <name>");
+                reset_text_color();
+                self.fail();
+            },
+        }
+    }
+
+    /// env 命令 - 列出所有变量
+    fn cmd_env(&self) {
+        let mut count = 0;
+        for (name, value) in self.variables.iter() {
+            println!("{}={}", name, value);
+            count += 1;
+        }
+        if count == 0 {
+            println!("No variables set.");
+        }
+    }
+
+    /// count 命令 - 慢速计数，演示定时器、睡眠和 Esc 中止
+    fn cmd_count(&mut self, mut args: core::str::SplitWhitespace) {
+        let arg = args.next();
+        let n = match arg.map(parse_u64) {
+            Some(Ok(n)) if (1..=COUNT_MAX).contains(&n) => n,
+            Some(Ok(_)) => {
+                highlight_text(Color::RED);
+                println!("Count must be between 1 and {}.", COUNT_MAX);
+                reset_text_color();
+                self.fail();
+                return;
+            },
+            Some(Err(error)) => {
+                self.print_invalid_number(arg.unwrap_or(""), error);
+                self.fail();
+                return;
+            },
+            None => {
+                highlight_text(Color::RED);
+                println!("Usage: count <n>");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+
+        for i in 1..=n {
+            println!("{}", i);
+            if i == n {
+                break;
+            }
+            if !crate::time::sleep_ms(COUNT_DELAY_MS, crate::input::abort_requested) {
+                report_abort();
+                println!("Stopped at {} of {}.", i, n);
+                self.fail();
+                return;
+            }
+        }
+    }
+
+    /// progress 命令 - 在当前行原地刷新进度条
+    fn cmd_progress(&mut self) {
+        let row = match crate::with_writer(|writer| writer.cursor_position().0) {
+            Some(row) => row,
+            None => return,
+        };
+        let draw = |percent: usize| {
+            crate::with_writer(|writer| writer.draw_progress_bar(row, 0, PROGRESS_WIDTH, percent));
+        };
+
+        for percent in (0..=100).step_by(2) {
+            draw(percent);
+            if percent < 100 && !crate::time::sleep_ms(PROGRESS_STEP_MS, crate::input::abort_requested) {
+                println!();
+                report_abort();
+                self.fail();
+                return;
+            }
+        }
+        println!();
+    }
+
+    /// perf 命令 - 开关右上角的渲染耗时显示，没有参数时切换
+    fn cmd_perf(&mut self, mut args: core::str::SplitWhitespace) {
+        let shown = match crate::with_writer(|writer| writer.perf_overlay()) {
+            Some(shown) => shown,
+            None => return,
+        };
+        let enabled = match args.next() {
+            None => !shown,
+            Some("on") => true,
+            Some("off") => false,
+            Some(_) => {
+                highlight_text(Color::RED);
+                println!("Usage: perf [on|off]");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+        let last_frame = crate::with_writer(|writer| {
+            writer.set_perf_overlay(enabled);
+            writer.last_frame()
+        }).flatten();
+
+        println!("Perf overlay {}.", if enabled { "on" } else { "off" });
+        if let Some((kind, ns)) = last_frame {
+            print!("Last frame:   ");
+            emit(|out| format::write_frame_time(out, kind, ns));
+            println!();
+        }
+    }
+
+    /// wrap 命令 - 开关自动换行，没有参数时切换
+    fn cmd_wrap(&mut self, mut args: core::str::SplitWhitespace) {
+        let current = crate::with_writer(|writer| writer.word_wrap()).unwrap_or(false);
+        let enabled = match args.next() {
+            None => !current,
+            Some("on") => true,
+            Some("off") => false,
+            Some(_) => {
+                highlight_text(Color::RED);
+                println!("Usage: wrap [on|off]");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+        crate::with_writer(|writer| writer.set_word_wrap(enabled));
+        println!("Word wrap {}.", if enabled { "on" } else { "off" });
+    }
+
+    /// split 命令 - 开关分屏模式: 输入行固定在屏幕底部，命令输出在上方滚动
+    fn cmd_split(&mut self, mut args: core::str::SplitWhitespace) {
+        let enabled = crate::WRITER
+            .lock()
+            .as_ref()
+            .is_some_and(|writer| writer.split_rows().is_some());
+        let rows = match (args.next(), args.next()) {
+            (None, _) if enabled => None,
+            (None, _) | (Some("on"), None) => Some(SPLIT_INPUT_ROWS),
+            (Some("on"), Some(rows)) => match parse_u64(rows) {
+                Ok(n) if (1..=MAX_INPUT_ROWS as u64).contains(&n) => Some(n as usize),
+                Ok(_) => {
+                    highlight_text(Color::RED);
+                    println!("Input area must be 1-{} rows", MAX_INPUT_ROWS);
+                    reset_text_color();
+                    self.fail();
+                    return;
+                },
+                Err(error) => {
+                    self.print_invalid_number(rows, error);
+                    self.fail();
+                    return;
+                },
+            },
+            (Some("off"), None) => None,
+            _ => {
+                highlight_text(Color::RED);
+                println!("Usage: split [on [rows]|off]");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+
+        let fits = crate::with_writer(|writer| match rows {
+            Some(rows) => writer.enable_split(rows),
+            None => {
+                writer.disable_split();
+                true
+            },
+        });
+        let fits = match fits {
+            Some(fits) => fits,
+            None => {
+                println!("Split mode needs a framebuffer.");
+                self.fail();
+                return;
+            },
+        };
+        match rows {
+            Some(rows) => {
+                if fits {
+                    println!("Split mode on ({} input row{}).", rows, if rows == 1 { "" } else { "s" });
+                } else {
+                    highlight_text(Color::RED);
+                    println!("Screen is too small for a {}-row input area", rows);
+                    reset_text_color();
+                    self.fail();
+                }
+            },
+            None => println!("Split mode off."),
+        }
+    }
+
+    /// dialog 命令 - 调暗屏幕，在中间弹出消息框，按任意键后恢复屏幕
+    fn cmd_dialog(&mut self, rest: &str) {
+        let message = rest.trim();
+        if message.is_empty() {
+            highlight_text(Color::RED);
+            println!("Usage: dialog <message>");
+            reset_text_color();
+            self.fail();
+            return;
+        }
+
+        let shown = crate::with_writer(|writer| show_dialog(writer, message)).unwrap_or(false);
+        if !shown {
+            // 没有帧缓冲区或屏幕放不下方框时直接输出消息
+            println!("{}", message);
+            return;
+        }
+
+        let _ = crate::input::wait_key();
+        crate::with_writer(|writer| writer.force_redraw());
     }
-}
\ No newline at end of file
+
+    /// top 命令 - 全屏实时显示运行时间、tick 频率、各 IRQ 的计数和每秒速率、内存
+    /// 画在覆盖整个屏幕的方框里，不写回滚缓冲区；按 q 或 Esc 退出后重绘原来的屏幕
+    fn cmd_top(&mut self) {
+        use crate::interrupts::{self, IRQ_COUNT};
+
+        if !crate::time::is_initialized() {
+            highlight_text(Color::RED);
+            println!("Time system not initialized!");
+            reset_text_color();
+            self.fail();
+            return;
+        }
+        if crate::with_writer(|_| ()).is_none() {
+            highlight_text(Color::RED);
+            println!("No framebuffer available.");
+            reset_text_color();
+            self.fail();
+            return;
+        }
+
+        let usable_bytes: u64 = crate::memory::memory_map().usable_regions().iter().map(|region| region.size()).sum();
+        let mut previous = [0u64; IRQ_COUNT];
+        let mut previous_ms = crate::time::get_uptime_ms();
+        for (irq, count) in previous.iter_mut().enumerate() {
+            *count = interrupts::irq_count(irq);
+        }
+        let mut rates: Option<[u64; IRQ_COUNT]> = None;
+
+        loop {
+            let drawn = crate::with_writer(|writer| draw_top(writer, usable_bytes, rates.as_ref())).unwrap_or(false);
+            if !drawn {
+                crate::with_writer(|writer| writer.force_redraw());
+                highlight_text(Color::RED);
+                println!("Screen too small for top.");
+                reset_text_color();
+                self.fail();
+                return;
+            }
+
+            let quit = || {
+                while let Some(event) = crate::input::poll_key() {
+                    if event.key == keyboard::Key::Normal(TOP_QUIT_SCANCODE) {
+                        return true;
+                    }
+                }
+                crate::input::abort_requested()
+            };
+            if !crate::time::sleep_ms(TOP_REFRESH_MS, quit) {
+                break;
+            }
+
+            // 按实际经过的时间算每秒速率，命令执行期间 sleep 可能多睡一点
+            let now_ms = crate::time::get_uptime_ms();
+            let elapsed_ms = now_ms.saturating_sub(previous_ms).max(1);
+            let mut current = [0u64; IRQ_COUNT];
+            for (irq, count) in current.iter_mut().enumerate() {
+                *count = interrupts::irq_count(irq);
+            }
+            let mut per_second = [0u64; IRQ_COUNT];
+            for irq in 0..IRQ_COUNT {
+                per_second[irq] = current[irq].saturating_sub(previous[irq]) * 1000 / elapsed_ms;
+            }
+            rates = Some(per_second);
+            previous = current;
+            previous_ms = now_ms;
+        }
+
+        // Esc 在这里只是退出 top，不中止后面的命令
+        crate::input::clear_abort();
+        crate::with_writer(|writer| writer.force_redraw());
+    }
+
+    /// display 命令 - 显示帧缓冲区参数和当前字体下的文字网格
+    fn cmd_display(&mut self) {
+        let info = match *crate::FRAMEBUFFER_INFO.lock() {
+            Some(info) => info,
+            None => {
+                highlight_text(Color::RED);
+                println!("No framebuffer available.");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+
+        println!("Resolution:     {}x{}", info.width, info.height);
+        println!("Stride:         {} pixels", info.stride);
+        println!("Bytes/pixel:    {}", info.bytes_per_pixel);
+        println!("Pixel format:   {:?}", info.pixel_format);
+        println!("Buffer size:    {} bytes", info.byte_len);
+
+        // 字体和网格属于 Writer，先取出数值再打印，避免持锁打印
+        let grid = crate::with_writer(|writer| {
+            let font = writer.font();
+            (
+                font.name(),
+                font.width(),
+                font.height(),
+                writer.scale(),
+                writer.text_columns(),
+                writer.text_rows(),
+                writer.is_double_buffered(),
+            )
+        });
+        if let Some((name, width, height, scale, columns, rows, double_buffered)) = grid {
+            println!("Font:           {} ({}x{}, scale {})", name, width, height, scale);
+            println!("Text grid:      {} columns x {} rows", columns, rows);
+            println!("Double buffer:  {}", if double_buffered { "on" } else { "off" });
+        }
+    }
+
+    /// hexdump 命令 - 以十六进制和 ASCII 显示一段内存
+    /// 地址按十六进制解析；读取前检查页表，未映射的地址直接拒绝，不会触发缺页异常
+    fn cmd_hexdump(&mut self, mut args: core::str::SplitWhitespace) {
+        let mut first = args.next();
+        let physical = first == Some("-p");
+        if physical {
+            first = args.next();
+        }
+        let (addr_arg, len_arg) = match (first, args.next()) {
+            (Some(addr), Some(len)) => (addr, len),
+            _ => {
+                highlight_text(Color::RED);
+                println!("Usage: hexdump [-p] <addr> <len>");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+
+        let address = match parse_hex(addr_arg) {
+            Ok(address) => address,
+            Err(error) => {
+                self.print_invalid_number(addr_arg, error);
+                self.fail();
+                return;
+            },
+        };
+        let len = match parse_u64(len_arg) {
+            Ok(len) => len,
+            Err(error) => {
+                self.print_invalid_number(len_arg, error);
+                self.fail();
+                return;
+            },
+        };
+        if len > HEXDUMP_MAX_LEN {
+            println!("Length clamped to {} bytes.", HEXDUMP_MAX_LEN);
+        }
+        let len = len.min(HEXDUMP_MAX_LEN);
+
+        let map = crate::memory::memory_map();
+        let virtual_address = if physical {
+            crate::memory::physical_to_virtual(&map, address).map(|ptr| ptr as u64)
+        } else {
+            Some(address)
+        };
+        if physical && map.physical_memory_offset().is_none() {
+            highlight_text(Color::RED);
+            println!("Cannot read physical address {:#x}: {}", address, crate::memory::RangeError::NotMapped.describe());
+            reset_text_color();
+            self.fail();
+            return;
+        }
+        let start = match virtual_address {
+            Some(start) if start != 0 && crate::memory::is_range_mapped(&map, start, len) => start,
+            _ => {
+                highlight_text(Color::RED);
+                println!("Refusing to read {:#x}+{:#x}: address is null or not mapped", address, len);
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+
+        let mut pager = Pager::new();
+        let mut line = format::LineBuffer::<96>::new();
+        let mut offset = 0;
+        while offset < len {
+            let count = (len - offset).min(format::HEXDUMP_BYTES_PER_LINE as u64) as usize;
+            let mut bytes = [0u8; format::HEXDUMP_BYTES_PER_LINE];
+            for (i, byte) in bytes[..count].iter_mut().enumerate() {
+                // 安全性: 上面已确认整个范围都已映射
+                *byte = unsafe { core::ptr::read_volatile((start + offset + i as u64) as *const u8) };
+            }
+            line.clear();
+            let _ = format::write_hexdump_line(&mut line, address + offset, &bytes[..count]);
+            pager.line(line.as_str());
+            offset += count as u64;
+        }
+    }
+
+    /// readsec 命令 - 用 ATA PIO 读一个扇区并以 hexdump 显示，地址列是磁盘上的字节偏移
+    /// 默认读主通道从盘 (运行器附加的数据盘)，-m 读主盘 (启动盘)
+    fn cmd_readsec(&mut self, mut args: core::str::SplitWhitespace) {
+        let mut first = args.next();
+        let drive = if first == Some("-m") {
+            first = args.next();
+            crate::ata::Drive::Master
+        } else {
+            crate::ata::Drive::Slave
+        };
+        let lba_arg = match first {
+            Some(lba) => lba,
+            None => {
+                highlight_text(Color::RED);
+                println!("Usage: readsec [-m] <lba>");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+        let lba = match self.parse_bounded(lba_arg, crate::ata::MAX_LBA as u64, "LBA") {
+            Some(lba) => lba as u32,
+            None => return,
+        };
+
+        let mut sector = [0u8; crate::ata::SECTOR_SIZE];
+        if let Err(error) = crate::ata::read_sector(drive, lba, &mut sector) {
+            highlight_text(Color::RED);
+            println!("Cannot read sector {} from {}: {} ({:x?})", lba, drive.name(), error.describe(), error);
+            reset_text_color();
+            self.fail();
+            return;
+        }
+
+        let mut pager = Pager::new();
+        pager.line_fmt(format_args!("Sector {} of {}:", lba, drive.name()));
+        let mut line = format::LineBuffer::<96>::new();
+        let base = lba as u64 * crate::ata::SECTOR_SIZE as u64;
+        for (i, bytes) in sector.chunks(format::HEXDUMP_BYTES_PER_LINE).enumerate() {
+            line.clear();
+            let _ = format::write_hexdump_line(&mut line, base + (i * format::HEXDUMP_BYTES_PER_LINE) as u64, bytes);
+            pager.line(line.as_str());
+        }
+    }
+
+    /// search 命令 - 在回滚缓冲区中查找文字，列出匹配的行并高亮匹配部分
+    /// 默认不区分大小写，-c 区分大小写 (-i 显式指定不区分)
+    fn cmd_search(&mut self, rest: &str) {
+        let (case_sensitive, pattern) = match rest.split_once(char::is_whitespace) {
+            Some(("-c", pattern)) => (true, pattern.trim()),
+            Some(("-i", pattern)) => (false, pattern.trim()),
+            _ => (false, rest),
+        };
+        if pattern.is_empty() {
+            highlight_text(Color::RED);
+            println!("Usage: search [-i|-c] <text>");
+            reset_text_color();
+            self.fail();
+            return;
+        }
+
+        // 只搜索本次命令之前的输出，不包括命令行本身和搜索结果
+        let prompt_line = self.prompt_line;
+        let lines = match crate::with_writer(|writer| writer.oldest_stored_line()..prompt_line.min(writer.current_line())) {
+            Some(lines) => lines,
+            None => return,
+        };
+        let pattern_len = pattern.chars().count();
+        let print_chars = |chars: &[char]| {
+            for ch in chars {
+                print!("{}", ch);
+            }
+        };
+
+        let mut matches = 0;
+        let mut text = ['\0'; crate::scrollback::MAX_COLUMNS];
+        for line in lines {
+            // 输出结果会覆盖最旧的行，每行读取前重新检查是否仍然保存着
+            let len = match crate::with_writer(|writer| writer.copy_stored_line(line, &mut text)).flatten() {
+                Some(len) => len,
+                None => continue,
+            };
+            let text = &text[..len];
+            let mut start = match crate::scrollback::find_text(text, pattern, 0, case_sensitive) {
+                Some(start) => start,
+                None => continue,
+            };
+            matches += 1;
+
+            highlight_text(Color::YELLOW);
+            print!("{:>5}: ", line + 1);
+            reset_text_color();
+            let mut printed = 0;
+            loop {
+                print_chars(&text[printed..start]);
+                highlight_text(Color::CYAN);
+                print_chars(&text[start..start + pattern_len]);
+                reset_text_color();
+                printed = start + pattern_len;
+                start = match crate::scrollback::find_text(text, pattern, printed, case_sensitive) {
+                    Some(start) => start,
+                    None => break,
+                };
+            }
+            print_chars(&text[printed..]);
+            println!();
+        }
+
+        println!("{} matching line{}", matches, if matches == 1 { "" } else { "s" });
+    }
+
+    /// inb 命令 - 从 I/O 端口读取一个字节
+    fn cmd_inb(&mut self, mut args: core::str::SplitWhitespace) {
+        let port = match args.next() {
+            Some(arg) => match self.parse_bounded(arg, u16::MAX as u64, "Port") {
+                Some(port) => port as u16,
+                None => return,
+            },
+            None => {
+                highlight_text(Color::RED);
+                println!("Usage: inb <port>");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+
+        // 安全性: 读取端口可能有副作用 (例如取走键盘数据)，这是用户明确要求的操作
+        let value: u8 = unsafe { x86_64::instructions::port::Port::new(port).read() };
+        println!("inb {:#06x} = {:#04x} ({})", port, value, value);
+    }
+
+    /// outb 命令 - 向 I/O 端口写入一个字节
+    /// 写端口可能让机器死机，必须带 --force 才执行
+    fn cmd_outb(&mut self, args: core::str::SplitWhitespace) {
+        let mut force = false;
+        let mut values = [None; 2];
+        let mut count = 0;
+        for arg in args {
+            if arg == "--force" {
+                force = true;
+                continue;
+            }
+            if count < values.len() {
+                values[count] = Some(arg);
+            }
+            count += 1;
+        }
+
+        let (port_arg, value_arg) = match values {
+            [Some(port), Some(value)] if count == 2 => (port, value),
+            _ => {
+                highlight_text(Color::RED);
+                println!("Usage: outb <port> <value> --force");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+        let port = match self.parse_bounded(port_arg, u16::MAX as u64, "Port") {
+            Some(port) => port as u16,
+            None => return,
+        };
+        let value = match self.parse_bounded(value_arg, u8::MAX as u64, "Value") {
+            Some(value) => value as u8,
+            None => return,
+        };
+
+        if !force {
+            highlight_text(Color::YELLOW);
+            println!("Writing to I/O ports can hang or crash the machine.");
+            println!("Re-run with --force to write {:#04x} to port {:#06x}.", value, port);
+            reset_text_color();
+            self.fail();
+            return;
+        }
+
+        // 安全性: 用户已经用 --force 确认
+        unsafe { x86_64::instructions::port::Port::new(port).write(value) };
+        println!("outb {:#06x} <- {:#04x} ({})", port, value, value);
+    }
+
+    /// 解析不超过 max 的数字参数，出错时打印提示并标记命令失败
+    fn parse_bounded(&mut self, arg: &str, max: u64, what: &str) -> Option<u64> {
+        match parse_u64(arg) {
+            Ok(n) if n <= max => Some(n),
+            Ok(_) => {
+                highlight_text(Color::RED);
+                println!("{} must be at most {:#x}.", what, max);
+                reset_text_color();
+                self.fail();
+                None
+            },
+            Err(error) => {
+                self.print_invalid_number(arg, error);
+                self.fail();
+                None
+            },
+        }
+    }
+
+    /// memtest 命令 - 无参数时列出可用内存区域，否则对指定的物理地址范围做走 1 测试
+    fn cmd_memtest(&mut self, mut args: core::str::SplitWhitespace) {
+        let map = crate::memory::memory_map();
+
+        let (start_arg, size_arg) = match (args.next(), args.next()) {
+            (Some(start), Some(size)) => (start, size),
+            (None, _) => {
+                match map.physical_memory_offset() {
+                    Some(offset) => println!("Physical memory mapped at offset {:#x}", offset),
+                    None => println!("Physical memory is not mapped (identity mapping only)"),
+                }
+                println!("Usable memory regions:");
+                for region in map.usable_regions() {
+                    println!("  {:#012x} - {:#012x}  ({} KiB)", region.start, region.end, region.size() / 1024);
+                }
+                return;
+            },
+            _ => {
+                highlight_text(Color::RED);
+                println!("Usage: memtest <start> <size>");
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+
+        let mut numbers = [0u64; 2];
+        for (value, arg) in numbers.iter_mut().zip([start_arg, size_arg]) {
+            match parse_u64(arg) {
+                Ok(n) => *value = n,
+                Err(error) => {
+                    self.print_invalid_number(arg, error);
+                    self.fail();
+                    return;
+                },
+            }
+        }
+
+        let range = match crate::memory::check_test_range(&map, numbers[0], numbers[1]) {
+            Ok(range) => range,
+            Err(error) => {
+                highlight_text(Color::RED);
+                println!("Refusing to test {:#x}+{:#x}: {}", numbers[0], numbers[1], error.describe());
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+
+        let row = match crate::with_writer(|writer| writer.cursor_position().0) {
+            Some(row) => row,
+            None => return,
+        };
+        let draw = |percent: usize| {
+            crate::with_writer(|writer| writer.draw_progress_bar(row, 0, PROGRESS_WIDTH, percent));
+        };
+
+        // 错误先记下来，测试结束后再打印，避免滚屏打乱进度条所在的行
+        let mut mismatches = [(0u64, 0u64, 0u64); MEMTEST_MAX_REPORTED];
+        let mut mismatch_count = 0;
+        let mut address = range.start;
+        let mut aborted = false;
+        draw(0);
+        while address < range.end {
+            let chunk_end = range.end.min(address + MEMTEST_CHUNK_SIZE);
+            while address < chunk_end {
+                if let Some(word) = crate::memory::physical_to_virtual(&map, address) {
+                    // 安全性: check_test_range 保证地址位于引导程序标记为可用、没有任何代码使用的内存中
+                    if let Some((expected, got)) = unsafe { crate::memory::walking_ones(word) } {
+                        if mismatch_count < MEMTEST_MAX_REPORTED {
+                            mismatches[mismatch_count] = (address, expected, got);
+                        }
+                        mismatch_count += 1;
+                    }
+                }
+                address += 8;
+            }
+
+            draw(((address - range.start) * 100 / range.size()) as usize);
+            if crate::input::abort_requested() {
+                aborted = true;
+                break;
+            }
+        }
+        println!();
+
+        for &(address, expected, got) in &mismatches[..mismatch_count.min(MEMTEST_MAX_REPORTED)] {
+            highlight_text(Color::RED);
+            println!("Mismatch at {:#012x}: expected {:#018x}, got {:#018x}", address, expected, got);
+            reset_text_color();
+        }
+        if mismatch_count > MEMTEST_MAX_REPORTED {
+            println!("... {} more mismatches not shown", mismatch_count - MEMTEST_MAX_REPORTED);
+        }
+
+        let tested = address - range.start;
+        if aborted {
+            report_abort();
+            println!("Tested {} of {} bytes, {} mismatches.", tested, range.size(), mismatch_count);
+            self.fail();
+        } else if mismatch_count > 0 {
+            println!("Tested {} bytes, {} mismatches.", tested, mismatch_count);
+            self.fail();
+        } else {
+            highlight_text(Color::GREEN);
+            println!("Tested {} bytes, no errors.", tested);
+            reset_text_color();
+        }
+    }
+
+    /// theme 命令 - 无参数时列出主题，否则切换主题并用新配色清屏
+    fn cmd_theme(&mut self, mut args: core::str::SplitWhitespace) {
+        let current = crate::theme::active().name;
+        let name = match args.next() {
+            Some(name) => name,
+            None => {
+                println!("Available themes:");
+                for theme in crate::theme::THEMES.iter() {
+                    let marker = if theme.name == current { "*" } else { " " };
+                    println!(" {} {}", marker, theme.name);
+                }
+                return;
+            },
+        };
+
+        let theme = match crate::theme::find(name) {
+            Some(theme) => theme,
+            None => {
+                highlight_text(Color::RED);
+                println!("Unknown theme: '{}'", name);
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+
+        crate::theme::set_active(theme);
+        crate::with_writer(|writer| {
+            // 丢弃可能残留的高亮，之后恢复的是新主题的颜色
+            writer.restore_colors();
+            writer.set_fg_color(theme.fg);
+            writer.set_bg_color(theme.bg);
+            writer.clear_to(theme.bg);
+        });
+        print_banner();
+    }
+
+    /// font 命令 - 无参数时列出字体，否则切换字体
+    fn cmd_font(&mut self, mut args: core::str::SplitWhitespace) {
+        let current = match crate::with_writer(|writer| writer.font().name()) {
+            Some(name) => name,
+            None => return,
+        };
+
+        let name = match args.next() {
+            Some(name) => name,
+            None => {
+                println!("Available fonts:");
+                for font in crate::font::FONTS.iter() {
+                    let marker = if font.name() == current { "*" } else { " " };
+                    println!(" {} {:<6} {}x{}", marker, font.name(), font.width(), font.height());
+                }
+                return;
+            },
+        };
+
+        match crate::font::find(name) {
+            Some(font) => {
+                crate::with_writer(|writer| writer.set_font(font));
+                println!("Font switched to {}.", font.name());
+            },
+            None => {
+                highlight_text(Color::RED);
+                println!("Unknown font: '{}' (type 'font' to list fonts)", name);
+                reset_text_color();
+                self.fail();
+            },
+        }
+    }
+
+    /// run 命令 - 无参数时列出内置脚本，否则按顺序执行脚本中的命令
+    /// 默认遇到第一条失败的命令就停止，--keep-going 时继续执行，最后以第一个失败的退出码结束
+    fn cmd_run(&mut self, args: core::str::SplitWhitespace) {
+        let mut keep_going = false;
+        let mut name = None;
+        for arg in args {
+            match arg {
+                "--keep-going" | "-k" => keep_going = true,
+                _ if name.is_none() => name = Some(arg),
+                _ => {
+                    highlight_text(Color::RED);
+                    println!("Usage: run [--keep-going] [script]");
+                    reset_text_color();
+                    self.fail_with(EXIT_SYNTAX);
+                    return;
+                },
+            }
+        }
+
+        let name = match name {
+            Some(name) => name,
+            None => {
+                println!("Available scripts:");
+                for script in SCRIPTS {
+                    println!("  {:<10} {}", script.name, script.description);
+                }
+                return;
+            },
+        };
+        let script = match SCRIPTS.iter().find(|script| script.name == name) {
+            Some(script) => script,
+            None => {
+                highlight_text(Color::RED);
+                println!("Unknown script: '{}' (type 'run' to list scripts)", name);
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+        if self.in_script {
+            highlight_text(Color::RED);
+            println!("Scripts cannot run other scripts.");
+            reset_text_color();
+            self.fail();
+            return;
+        }
+
+        self.in_script = true;
+        let mut first_failure = None;
+        for &line in script.commands {
+            if crate::input::abort_requested() {
+                report_abort();
+                first_failure = first_failure.or(Some(EXIT_FAILURE));
+                break;
+            }
+            highlight_text(Color::CYAN);
+            println!("+ {}", line);
+            reset_text_color();
+            if let Err(code) = self.execute_chain(line) {
+                first_failure = first_failure.or(Some(code));
+                if !keep_going {
+                    highlight_text(Color::RED);
+                    println!("Script '{}' stopped: '{}' failed (exit {})", script.name, line, code);
+                    reset_text_color();
+                    break;
+                }
+            }
+        }
+        self.in_script = false;
+
+        // 脚本的结果取代最后一条命令的结果
+        self.last_status = Some(match first_failure {
+            Some(code) => Err(code),
+            None => Ok(()),
+        });
+    }
+
+    /// cursor 命令 - 查看或切换文字光标的形状
+    fn cmd_cursor(&mut self, mut args: core::str::SplitWhitespace) {
+        let current = match crate::with_writer(|writer| writer.cursor_shape()) {
+            Some(shape) => shape,
+            None => return,
+        };
+
+        let name = match args.next() {
+            Some(name) => name,
+            None => {
+                println!("Cursor shape: {}", current.name());
+                return;
+            },
+        };
+
+        match CursorShape::from_name(name) {
+            Some(shape) => {
+                crate::with_writer(|writer| writer.set_cursor_shape(shape));
+                println!("Cursor shape set to {}.", shape.name());
+            },
+            None => {
+                highlight_text(Color::RED);
+                println!("Unknown cursor shape: '{}' (use block, underline or bar)", name);
+                reset_text_color();
+                self.fail();
+            },
+        }
+    }
+
+    /// loglevel 命令 - 查看或设置串口日志的最低级别
+    fn cmd_loglevel(&mut self, mut args: core::str::SplitWhitespace) {
+        let level = match args.next() {
+            None => {
+                println!("Log level: {}", log::min_level().name());
+                return;
+            },
+            Some("info") => log::Level::Info,
+            Some("warn") => log::Level::Warn,
+            Some("error") => log::Level::Error,
+            Some(other) => {
+                highlight_text(Color::RED);
+                println!("Unknown log level: '{}' (use info, warn or error)", other);
+                reset_text_color();
+                self.fail();
+                return;
+            },
+        };
+        log::set_min_level(level);
+        println!("Log level set to {}.", level.name());
+    }
+
+    /// stopwatch 命令 - 秒表
+    fn cmd_stopwatch(&mut self, mut args: core::str::SplitWhitespace) {
+        use crate::stopwatch::{StopwatchError, MAX_LAPS, STOPWATCH};
+
+        if !crate::time::is_initialized() {
+            highlight_text(Color::RED);
+            println!("Time system not initialized!");
+            reset_text_color();
+            self.fail();
+            return;
+        }
+
+        let now_ms = crate::time::get_uptime_ms();
+        let mut stopwatch = STOPWATCH.lock();
+
+        let result = match args.next() {
+            Some("start") => stopwatch.start(now_ms).map(|_| {
+                println!("Stopwatch started.");
+            }),
+            Some("stop") => stopwatch.stop(now_ms).map(|elapsed| {
+                print!("Stopwatch stopped at ");
+                print_stopwatch_time(elapsed);
+                println!();
+            }),
+            Some("lap") => stopwatch.lap(now_ms).map(|(index, elapsed)| {
+                print!("Lap {:2}: ", index);
+                print_stopwatch_time(elapsed);
+                println!();
+            }),
+            Some("reset") => {
+                stopwatch.reset();
+                println!("Stopwatch reset.");
+                Ok(())
+            },
+            None => {
+                // 显示当前状态和所有分段
+                print!("Elapsed: ");
+                print_stopwatch_time(stopwatch.elapsed_ms(now_ms));
+                println!(" ({})", if stopwatch.is_running() { "running" } else { "stopped" });
+
+                let mut previous = 0;
+                for (i, &lap) in stopwatch.laps().iter().enumerate() {
+                    print!("  Lap {:2}: ", i + 1);
+                    print_stopwatch_time(lap);
+                    print!("  (+");
+                    print_stopwatch_time(lap - previous);
+                    println!(")");
+                    previous = lap;
+                }
+                Ok(())
+            },
+            Some(other) => {
+                highlight_text(Color::RED);
+                println!("Unknown stopwatch command: '{}'", other);
+                highlight_text(Color::YELLOW);
+                println!("Usage: stopwatch [start|stop|lap|reset]");
+                reset_text_color();
+                self.fail();
+                Ok(())
+            },
+        };
+
+        if let Err(error) = result {
+            highlight_text(Color::RED);
+            match error {
+                StopwatchError::AlreadyRunning => println!("Stopwatch is already running."),
+                StopwatchError::NotRunning => println!("Stopwatch is not running."),
+                StopwatchError::LapsFull => println!("Lap limit reached ({} laps). Use 'sw reset'.", MAX_LAPS),
+            }
+            reset_text_color();
+            self.fail();
+        }
+    }
+}
+
+/// 数字参数解析错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// 空字符串 (或只有 "0x" 前缀)
+    Empty,
+    /// 包含非法字符
+    InvalidDigit,
+    /// 超出 u64 范围
+    Overflow,
+}
+
+/// 解析无符号整数，支持十进制和 0x 前缀的十六进制
+/// 不使用 str::parse，避免引入额外的格式化代码
+pub fn parse_u64(s: &str) -> Result<u64, ParseError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => parse_digits(hex, 16),
+        None => parse_digits(s, 10),
+    }
+}
+
+/// 解析十六进制数 (用于地址)，0x 前缀可省略
+pub fn parse_hex(s: &str) -> Result<u64, ParseError> {
+    parse_digits(s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s), 16)
+}
+
+/// 按指定进制解析数字串 (不含前缀)
+fn parse_digits(digits: &str, radix: u64) -> Result<u64, ParseError> {
+    if digits.is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    let mut value: u64 = 0;
+    for byte in digits.bytes() {
+        let digit = match byte {
+            b'0'..=b'9' => byte - b'0',
+            b'a'..=b'f' if radix == 16 => byte - b'a' + 10,
+            b'A'..=b'F' if radix == 16 => byte - b'A' + 10,
+            _ => return Err(ParseError::InvalidDigit),
+        };
+        value = value
+            .checked_mul(radix)
+            .and_then(|v| v.checked_add(digit as u64))
+            .ok_or(ParseError::Overflow)?;
+    }
+    Ok(value)
+}
+
+/// 命令响应 Esc 中止时打印提示
+fn report_abort() {
+    highlight_text(Color::YELLOW);
+    println!("^[ aborted");
+    reset_text_color();
+}
+
+/// 打印别名操作错误
+fn print_alias_error(error: AliasError, name: &str) {
+    highlight_text(Color::RED);
+    match error {
+        AliasError::InvalidName => println!("Invalid alias name: '{}'", name),
+        AliasError::EmptyValue => println!("Alias '{}' needs a command.", name),
+        AliasError::NameTooLong => println!("Alias name too long (max {} chars).", MAX_NAME_LEN),
+        AliasError::ValueTooLong => println!("Alias command too long (max {} chars).", MAX_VALUE_LEN),
+        AliasError::TableFull => println!("Alias table full ({} max).", crate::alias::MAX_ALIASES),
+        AliasError::NotFound => println!("No such alias: '{}'", name),
+    }
+    reset_text_color();
+}
+
+/// 打印变量操作错误
+fn print_variable_error(error: VariableError, name: &str) {
+    highlight_text(Color::RED);
+    match error {
+        VariableError::InvalidName => println!("Invalid variable name: '{}' (use letters, digits, _)", name),
+        VariableError::NameTooLong => println!("Variable name too long (max {} chars).", vars::MAX_NAME_LEN),
+        VariableError::ValueTooLong => println!("Variable value too long (max {} chars).", vars::MAX_VALUE_LEN),
+        VariableError::TableFull => println!("Variable table full ({} max).", vars::MAX_VARIABLES),
+        VariableError::NotFound => println!("No such variable: '{}'", name),
+    }
+    reset_text_color();
+}
+
+/// banner 命令 - 没有参数时打印系统横幅，否则用大字体拼出文字
+/// 先输出空行留出位置，再逐个字形原地绘制；一行放不下时换到下面继续
+fn cmd_banner(text: &str) {
+    if text.is_empty() {
+        print_banner();
+        return;
+    }
+
+    let columns = match crate::with_writer(|writer| writer.text_columns()) {
+        Some(columns) => columns,
+        None => return,
+    };
+    let cell_width = bigfont::GLYPH_WIDTH + bigfont::GLYPH_SPACING;
+    let per_line = ((columns + bigfont::GLYPH_SPACING) / cell_width).max(1);
+
+    highlight_text(crate::theme::active().prompt);
+    let mut top_row = 0;
+    for (index, ch) in text.chars().enumerate() {
+        let slot = index % per_line;
+        if slot == 0 {
+            // 留出一行大字的高度 (需要时屏幕会滚动)，光标停在其下方
+            for _ in 0..bigfont::GLYPH_HEIGHT {
+                println!();
+            }
+            top_row = match crate::with_writer(|writer| writer.cursor_position().0) {
+                Some(row) => row.saturating_sub(bigfont::GLYPH_HEIGHT),
+                None => return,
+            };
+        }
+
+        let glyph = bigfont::glyph(ch);
+        crate::with_writer(|writer| {
+            for row in 0..bigfont::GLYPH_HEIGHT {
+                let mut line = format::LineBuffer::<{ bigfont::GLYPH_WIDTH * 4 }>::new();
+                for block in bigfont::row_chars(&glyph, row) {
+                    let _ = core::fmt::Write::write_char(&mut line, block);
+                }
+                writer.write_at(top_row + row, slot * cell_width, line.as_str());
+            }
+            writer.present();
+        });
+    }
+    reset_text_color();
+}
+
+/// 打印系统横幅
+pub fn print_banner() {
+    highlight_text(Color::CYAN);
+    println!("=== Rust OS v{} ({}) - Time System ===", VERSION, GIT_HASH);
+    reset_text_color();
+}
+
+/// 把格式化函数的输出交给 print!，没有帧缓冲区时和其他输出一样写到串口
+fn emit(f: impl FnOnce(&mut dyn core::fmt::Write) -> core::fmt::Result) {
+    struct Print;
+
+    impl core::fmt::Write for Print {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            print!("{}", s);
+            Ok(())
+        }
+    }
+
+    let _ = f(&mut Print);
+}
+
+/// 提醒用户输入被拒绝或命令出错: 输出 BEL，由 Writer 闪烁屏幕
+fn alert() {
+    print!("\x07");
+}
+
+/// breakpoint 命令 - 执行 int3 触发断点异常处理程序
+fn cmd_breakpoint() {
+    println!("Triggering breakpoint exception (int3)...");
+    x86_64::instructions::interrupts::int3();
+    println!("Returned from breakpoint handler, continuing.");
+}
+
+/// uptime --watch: 每秒在同一行原地刷新运行时间，直到按下任意键
+fn watch_uptime() {
+    use core::fmt::Write;
+
+    let row = match crate::with_writer(|writer| writer.cursor_position().0) {
+        Some(row) => row,
+        None => return,
+    };
+
+    let mut line = format::LineBuffer::<64>::new();
+    loop {
+        let uptime_ms = crate::time::get_uptime_ms();
+        line.clear();
+        let _ = write!(line, "Uptime: ");
+        let _ = format::write_hms(&mut line, uptime_ms);
+        let _ = write!(line, "  (press any key to stop)");
+        crate::with_writer(|writer| {
+            writer.write_at(row, 0, line.as_str());
+            writer.present();
+        });
+
+        // 对齐到下一个整秒再刷新
+        let until_next_second = 1000 - uptime_ms % 1000;
+        let stop = || crate::input::abort_requested() || crate::input::poll_key().is_some();
+        if !crate::time::sleep_ms(until_next_second, stop) {
+            break;
+        }
+    }
+
+    // write_at 不移动光标，光标仍在这一行的开头
+    println!();
+}
+
+/// 调暗屏幕并在中间画出消息框: 消息、空行、提示，返回是否画出
+fn show_dialog(writer: &mut crate::writer::Writer, message: &str) -> bool {
+    // 内部左右各留一列空白
+    let inner = message.chars().count().max(DIALOG_HINT.len()) + 2;
+    let width = (inner + 2).min(writer.text_columns());
+    let height = 5;
+    let row = writer.text_rows().saturating_sub(height) / 2;
+    let column = writer.text_columns().saturating_sub(width) / 2;
+
+    writer.dim_screen(DIALOG_DIM_ALPHA);
+    match writer.draw_box(row, column, width, height, Some("Message")) {
+        Some(text_box) => {
+            writer.box_text_centered(&text_box, 0, message);
+            writer.box_text_centered(&text_box, 2, DIALOG_HINT);
+            writer.present();
+            true
+        },
+        None => {
+            writer.force_redraw();
+            false
+        },
+    }
+}
+
+/// 画出 top 的一帧，屏幕放不下方框时返回 false
+/// rates 是上一个刷新间隔内各 IRQ 的每秒次数，第一帧还没有时为 None
+fn draw_top(
+    writer: &mut crate::writer::Writer,
+    usable_bytes: u64,
+    rates: Option<&[u64; crate::interrupts::IRQ_COUNT]>,
+) -> bool {
+    use crate::interrupts::{self, IRQ_NAMES};
+    use core::fmt::Write;
+
+    let text_box = match writer.draw_box(0, 0, writer.text_columns(), writer.text_rows(), Some("top")) {
+        Some(text_box) => text_box,
+        None => return false,
+    };
+    let mut line = format::LineBuffer::<96>::new();
+    let mut row = 0;
+    let mut put = |writer: &mut crate::writer::Writer, line: &mut format::LineBuffer<96>| {
+        writer.box_text(&text_box, row, line.as_str());
+        line.clear();
+        row += 1;
+    };
+
+    let _ = write!(line, " Uptime:     ");
+    let _ = format::write_uptime(&mut line, crate::time::get_uptime_ms());
+    put(writer, &mut line);
+    let (frequency, interval_ms) = crate::pit::get_info();
+    let _ = write!(line, " Tick rate:  {} Hz ({} ms per tick), {} ticks", frequency, interval_ms, crate::time::get_tick_count());
+    put(writer, &mut line);
+    let _ = write!(line, " Usable RAM: {} KiB", usable_bytes / 1024);
+    put(writer, &mut line);
+    let _ = write!(line, " Spurious:   {}   Unhandled: {}", interrupts::spurious_count(), interrupts::unhandled_count());
+    put(writer, &mut line);
+    put(writer, &mut line);
+
+    let _ = write!(line, " IRQ  Name              Count   Per sec");
+    put(writer, &mut line);
+    for (irq, name) in IRQ_NAMES.iter().enumerate() {
+        let _ = write!(line, " {:>3}  {:<14} {:>8}", irq, name, interrupts::irq_count(irq));
+        if let Some(rates) = rates {
+            let _ = write!(line, "  {:>8}", rates[irq]);
+        }
+        put(writer, &mut line);
+    }
+    put(writer, &mut line);
+    let _ = write!(line, " Press q or Esc to quit");
+    put(writer, &mut line);
+
+    writer.present();
+    true
+}
+
+/// ascii 命令 - 按列列出 0x20-0x7E 的十进制、十六进制编码和字符，每项之后用制表符对齐
+/// 在当前字体中字形为空白的字符暗淡显示，最后统计字形覆盖情况
+fn cmd_ascii() {
+    use core::fmt::Write;
+
+    let (columns, tab_width, font) = crate::with_writer(|writer| (writer.text_columns(), writer.tab_width(), Some(writer.font())))
+        .unwrap_or((80, 4, None));
+    let entry_width = crate::writer::next_tab_stop(ASCII_ENTRY_LEN, tab_width);
+    let per_row = (columns / entry_width).max(1);
+    let codes = 0x20u8..=0x7E;
+    let rows = codes.len().div_ceil(per_row);
+    let has_glyph = |ch: char| font.is_none_or(|font| font.glyph(ch).iter().any(|&row| row != 0));
+
+    let mut pager = Pager::new();
+    let mut line = format::LineBuffer::<512>::new();
+    for row in 0..rows {
+        line.clear();
+        // 按列排列: 同一列从上到下编码连续
+        for column in 0..per_row {
+            let code = 0x20 + column * rows + row;
+            if code > 0x7E {
+                break;
+            }
+            let ch = code as u8 as char;
+            let (dim, undim) = if has_glyph(ch) { ("", "") } else { ("\x1b[2m", "\x1b[22m") };
+            let _ = write!(line, "{}{:>3} {:02X} {}{}\t", dim, code, code, ch, undim);
+        }
+        pager.line(line.as_str().trim_end_matches('\t'));
+    }
+
+    if let Some(font) = font {
+        let covered = codes.clone().filter(|&code| has_glyph(code as char)).count();
+        pager.line("");
+        pager.line_fmt(format_args!(
+            "{} of {} characters have a visible glyph in font {} (dimmed entries are blank)",
+            covered,
+            codes.len(),
+            font.name()
+        ));
+    }
+}
+
+/// clear 命令 - 只留下空白屏幕和提示符
+/// 只清屏: 别名、变量、命令计数等 Shell 状态都保留，所以这里不接收 Shell
+fn cmd_clear(mut args: core::str::SplitWhitespace) {
+    crate::with_writer(|writer| writer.clear_screen());
+
+    if BANNER_ON_CLEAR || args.next() == Some("--banner") {
+        print_banner();
+    }
+}
+
+/// irqstat 命令 - 显示每条 IRQ 线的中断次数
+/// 定时器额外显示按运行时间算出的平均频率，用来确认 PIT 是否按预期触发
+fn cmd_irqstat() {
+    use crate::interrupts::{self, IRQ_NAMES};
+
+    let uptime_ms = crate::time::get_uptime_ms();
+    println!("IRQ  Name            Count");
+    for (irq, name) in IRQ_NAMES.iter().enumerate() {
+        let count = interrupts::irq_count(irq);
+        print!("{:>3}  {:<14} {:>8}", irq, name, count);
+        if irq == 0 && uptime_ms > 0 {
+            print!("  (~{} Hz)", count * 1000 / uptime_ms);
+        }
+        println!();
+    }
+    println!("Spurious:  {}", interrupts::spurious_count());
+    println!("Unhandled: {}", interrupts::unhandled_count());
+}
+
+/// scantest 命令 - 等待下一次按键，打印原始扫描码和解码结果
+/// 修饰键也会显示；Esc 或超时取消
+fn cmd_scantest() {
+    use core::cell::Cell;
+
+    println!("Press a key (Esc to cancel, {}s timeout)...", SCANTEST_TIMEOUT_MS / 1000);
+
+    // 直接读取事件而不是 poll_key，这样修饰键也能测试
+    let received = Cell::new(None);
+    let stop = || {
+        while let Some(event) = crate::input::next_event() {
+            if event.pressed {
+                received.set(Some(event));
+                return true;
+            }
+        }
+        crate::input::abort_requested()
+    };
+    crate::time::sleep_ms(SCANTEST_TIMEOUT_MS, stop);
+
+    let event = match received.get() {
+        Some(event) => event,
+        None if crate::input::abort_requested() => {
+            println!("Cancelled.");
+            return;
+        }
+        None => {
+            println!("Timed out.");
+            return;
+        }
+    };
+
+    let (bytes, len) = keyboard::encode_event(event);
+    print!("Scancode:");
+    for byte in &bytes[..len] {
+        print!(" {:#04x} ({})", byte, byte);
+    }
+    println!();
+
+    match event.key {
+        keyboard::Key::Normal(code) => {
+            let (shift, caps) = crate::input::shift_and_caps();
+            match keyboard::scancode_to_char(code, shift, caps) {
+                Some(ch) if ch.is_control() => println!("Key: Normal({:#04x}) char={:?}", code, ch),
+                Some(ch) => println!("Key: Normal({:#04x}) char='{}'", code, ch),
+                None => println!("Key: Normal({:#04x}) (no char)", code),
+            }
+        }
+        keyboard::Key::Extended(code) => match keyboard::extended_scancode_to_key(code) {
+            Some(key) => println!("Key: Extended({:#04x}) {:?}", code, key),
+            None => println!("Key: Extended({:#04x}) (unmapped)", code),
+        },
+        keyboard::Key::Pause => println!("Key: Pause"),
+    }
+}
+
+/// 以 HH:MM:SS.mmm 格式打印秒表读数
+fn print_stopwatch_time(ms: u64) {
+    emit(|out| format::write_hms_millis(out, ms));
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_char_stores_multibyte_char_as_utf8() {
+        let mut shell = Shell::new();
+        assert_eq!(shell.push_char('c'), Ok(()));
+        assert_eq!(shell.push_char('a'), Ok(()));
+        assert_eq!(shell.push_char('f'), Ok(()));
+        assert_eq!(shell.push_char('é'), Ok(()));
+        assert_eq!(shell.buffer_pos, 5);
+        assert_eq!(shell.input(), "café");
+
+        // 退格删除整个字符，不会留下半个 UTF-8 序列
+        shell.handle_backspace();
+        assert_eq!(shell.input(), "caf");
+        shell.handle_backspace();
+        assert_eq!(shell.input(), "ca");
+    }
+
+    #[test]
+    fn three_and_four_byte_chars_round_trip() {
+        let mut shell = Shell::new();
+        for ch in "echo 中文 😀".chars() {
+            assert_eq!(shell.push_char(ch), Ok(()));
+        }
+        assert_eq!(shell.input(), "echo 中文 😀");
+        assert_eq!(shell.buffer_pos, "echo 中文 😀".len());
+
+        shell.handle_backspace();
+        assert_eq!(shell.input(), "echo 中文 ");
+        shell.handle_backspace();
+        shell.handle_backspace();
+        assert_eq!(shell.input(), "echo 中");
+    }
+
+    #[test]
+    fn push_char_rejects_multibyte_char_that_does_not_fit() {
+        let mut shell = Shell::new();
+        for _ in 0..INPUT_BUFFER_SIZE - 2 {
+            assert_eq!(shell.push_char('a'), Ok(()));
+        }
+        assert_eq!(shell.push_char('é'), Err(InputError::BufferFull));
+        assert_eq!(shell.buffer_pos, INPUT_BUFFER_SIZE - 2);
+        assert!(core::str::from_utf8(&shell.input_buffer[..shell.buffer_pos]).is_ok());
+    }
+
+    #[test]
+    fn scripts_only_use_known_commands() {
+        for script in SCRIPTS {
+            for line in script.commands {
+                let name = line.split_whitespace().next().unwrap();
+                assert!(find_command(name).is_some(), "script '{}' uses unknown command '{}'", script.name, name);
+                assert_ne!(name, "run");
+            }
+        }
+    }
+
+    #[test]
+    fn settings_adjust_history_and_input_size() {
+        let mut shell = Shell::new();
+        assert_eq!(shell.apply_setting("history", "4"), Ok(()));
+        assert_eq!(shell.history.capacity(), 4);
+        assert_eq!(
+            shell.apply_setting("history", "0"),
+            Err(SettingError::OutOfRange { min: 1, max: history::MAX_HISTORY })
+        );
+        assert_eq!(shell.apply_setting("history", "x"), Err(SettingError::InvalidNumber(ParseError::InvalidDigit)));
+        assert_eq!(shell.history.capacity(), 4);
+
+        assert_eq!(shell.apply_setting("inputsize", "16"), Ok(()));
+        for _ in 0..15 {
+            assert_eq!(shell.push_char('a'), Ok(()));
+        }
+        assert_eq!(shell.push_char('a'), Err(InputError::BufferFull));
+        assert_eq!(
+            shell.apply_setting("inputsize", "257"),
+            Err(SettingError::OutOfRange { min: MIN_INPUT_SIZE, max: INPUT_BUFFER_SIZE })
+        );
+        assert_eq!(shell.input_limit, 16);
+
+        assert_eq!(shell.apply_setting("bell", "on"), Ok(()));
+        assert!(shell.bell);
+        assert_eq!(shell.apply_setting("bell", "1"), Err(SettingError::NotOnOff));
+        assert!(shell.bell);
+        assert_eq!(shell.apply_setting("bell", "off"), Ok(()));
+        assert!(!shell.bell);
+    }
+
+    #[test]
+    fn push_char_stops_when_full() {
+        let mut shell = Shell::new();
+        for _ in 0..INPUT_BUFFER_SIZE - 1 {
+            assert_eq!(shell.push_char('a'), Ok(()));
+        }
+        assert_eq!(shell.push_char('a'), Err(InputError::BufferFull));
+        assert_eq!(shell.buffer_pos, INPUT_BUFFER_SIZE - 1);
+    }
+
+    #[test]
+    fn input_never_overflows_buffer() {
+        let mut shell = Shell::new();
+        for i in 0..INPUT_BUFFER_SIZE * 2 {
+            let _ = shell.push_char((b'a' + (i % 26) as u8) as char);
+            assert!(shell.buffer_pos < INPUT_BUFFER_SIZE);
+        }
+        assert_eq!(shell.buffer_pos, INPUT_BUFFER_SIZE - 1);
+        // 缓冲区里的每个字节都是可以直接显示的 ASCII 字符
+        let input = core::str::from_utf8(&shell.input_buffer[..shell.buffer_pos]).unwrap();
+        assert!(input.bytes().all(|b| b.is_ascii_graphic()));
+        assert!(input.starts_with("abcdefghijklmnopqrstuvwxyzabc"));
+    }
+
+    #[test]
+    fn dollar_question_expands_to_last_exit_code() {
+        let mut shell = Shell::new();
+        let mut out = [0u8; INPUT_BUFFER_SIZE];
+        let len = shell.expand_variables("echo $? $?x", &mut out).unwrap();
+        assert_eq!(core::str::from_utf8(&out[..len]), Ok("echo 0 0x"));
+
+        shell.fail_with(EXIT_NOT_FOUND);
+        let len = shell.expand_variables("echo $?", &mut out).unwrap();
+        assert_eq!(core::str::from_utf8(&out[..len]), Ok("echo 127"));
+        shell.last_status = Some(Ok(()));
+        assert_eq!(shell.exit_code(), 0);
+    }
+
+    #[test]
+    fn clear_keeps_shell_state() {
+        let mut shell = Shell::new();
+        shell.aliases.set("ll", "ls").unwrap();
+        shell.variables.set("name", "value").unwrap();
+        shell.history.push("ls");
+        shell.command_count = 3;
+
+        assert_eq!(shell.execute_command("clear"), Ok(()));
+        assert_eq!(shell.command_count, 3);
+        assert_eq!(shell.history.len(), 1);
+        assert_eq!(shell.history.iter().next(), Some("ls"));
+        assert_eq!(shell.aliases.get("ll"), Some("ls"));
+        assert_eq!(shell.variables.get("name"), Some("value"));
+    }
+
+    #[test]
+    fn split_chain_finds_first_operator() {
+        assert_eq!(split_chain("ls"), ("ls", None));
+        assert_eq!(split_chain("a && b || c"), ("a ", Some((Chain::And, " b || c"))));
+        assert_eq!(split_chain("a || b && c"), ("a ", Some((Chain::Or, " b && c"))));
+    }
+
+    #[test]
+    fn empty_chain_operands_are_reported() {
+        assert_eq!(find_empty_operand("a && b || c"), None);
+        assert_eq!(find_empty_operand("echo a|b"), None);
+        assert_eq!(find_empty_operand("&& b"), Some(Chain::And));
+        assert_eq!(find_empty_operand("a ||"), Some(Chain::Or));
+        assert_eq!(find_empty_operand("a && || b"), Some(Chain::And));
+        assert_eq!(find_empty_operand("a ||  && b"), Some(Chain::Or));
+    }
+
+    #[test]
+    fn parse_u64_decimal() {
+        assert_eq!(parse_u64("0"), Ok(0));
+        assert_eq!(parse_u64("42"), Ok(42));
+        assert_eq!(parse_u64("18446744073709551615"), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn parse_u64_hex() {
+        assert_eq!(parse_u64("0x0"), Ok(0));
+        assert_eq!(parse_u64("0xff"), Ok(255));
+        assert_eq!(parse_u64("0XfF"), Ok(255));
+        assert_eq!(parse_u64("0xFFFFFFFFFFFFFFFF"), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn parse_hex_accepts_optional_prefix() {
+        assert_eq!(parse_hex("1000"), Ok(0x1000));
+        assert_eq!(parse_hex("0x1000"), Ok(0x1000));
+        assert_eq!(parse_hex("DeadBeef"), Ok(0xdead_beef));
+        assert_eq!(parse_hex(""), Err(ParseError::Empty));
+        assert_eq!(parse_hex("0x"), Err(ParseError::Empty));
+        assert_eq!(parse_hex("12g"), Err(ParseError::InvalidDigit));
+    }
+
+    #[test]
+    fn parse_u64_overflow() {
+        assert_eq!(parse_u64("18446744073709551616"), Err(ParseError::Overflow));
+        assert_eq!(parse_u64("0x10000000000000000"), Err(ParseError::Overflow));
+    }
+
+    #[test]
+    fn parse_u64_rejects_bad_input() {
+        assert_eq!(parse_u64(""), Err(ParseError::Empty));
+        assert_eq!(parse_u64("0x"), Err(ParseError::Empty));
+        assert_eq!(parse_u64("-1"), Err(ParseError::InvalidDigit));
+        assert_eq!(parse_u64("12a"), Err(ParseError::InvalidDigit));
+        assert_eq!(parse_u64("0xg"), Err(ParseError::InvalidDigit));
+        assert_eq!(parse_u64(" 1"), Err(ParseError::InvalidDigit));
+    }
+}
@@ -0,0 +1,65 @@
+// kernel/src/speaker.rs
+// PC 扬声器 - PIT 通道 2 产生方波，端口 0x61 的低两位打开通道 2 的门控并把方波送到扬声器
+
+use x86_64::instructions::port::Port;
+
+use crate::pit::{PIT_BASE_FREQUENCY, PIT_CHANNEL_2, PIT_COMMAND};
+
+/// 键盘控制器端口 B，位 0 = PIT 通道 2 门控，位 1 = 扬声器数据使能
+const SPEAKER_PORT: u16 = 0x61;
+const SPEAKER_ENABLE: u8 = 0x03;
+
+/// PIT 命令: 通道 2，先低字节后高字节，模式 3 (方波)，二进制
+const CHANNEL_2_SQUARE_WAVE: u8 = 0xB6;
+
+/// 让扬声器按指定频率持续发声，直到调用 stop
+pub fn play(frequency_hz: u32) {
+    let divisor = divisor_for(frequency_hz);
+    let mut command: Port<u8> = Port::new(PIT_COMMAND);
+    let mut channel_2: Port<u8> = Port::new(PIT_CHANNEL_2);
+    let mut speaker: Port<u8> = Port::new(SPEAKER_PORT);
+    unsafe {
+        command.write(CHANNEL_2_SQUARE_WAVE);
+        channel_2.write(divisor as u8);
+        channel_2.write((divisor >> 8) as u8);
+        let value = speaker.read();
+        if value & SPEAKER_ENABLE != SPEAKER_ENABLE {
+            speaker.write(value | SPEAKER_ENABLE);
+        }
+    }
+}
+
+/// 关闭扬声器
+pub fn stop() {
+    let mut speaker: Port<u8> = Port::new(SPEAKER_PORT);
+    unsafe {
+        let value = speaker.read();
+        speaker.write(value & !SPEAKER_ENABLE);
+    }
+}
+
+/// 发出一声 `duration_ms` 毫秒的蜂鸣 (必须在中断开启时调用)
+pub fn beep(frequency_hz: u32, duration_ms: u64) {
+    play(frequency_hz);
+    crate::time::sleep_ms(duration_ms, || false);
+    stop();
+}
+
+/// 频率对应的通道 2 分频值，夹到 16 位计数器能表示的范围 (约 19Hz 以上)
+fn divisor_for(frequency_hz: u32) -> u16 {
+    (PIT_BASE_FREQUENCY / frequency_hz.max(1)).clamp(1, u16::MAX as u32) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divisor_matches_frequency_and_clamps() {
+        assert_eq!(divisor_for(1000), 1193);
+        assert_eq!(divisor_for(440), 2711);
+        assert_eq!(divisor_for(1), u16::MAX);
+        assert_eq!(divisor_for(0), u16::MAX);
+        assert_eq!(divisor_for(5_000_000), 1);
+    }
+}
@@ -0,0 +1,105 @@
+// kernel/src/stopwatch.rs
+// 秒表 - 基于系统 tick 计时
+
+use spin::Mutex;
+
+/// 最多记录的分段数量
+pub const MAX_LAPS: usize = 16;
+
+/// 秒表操作错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopwatchError {
+    /// 秒表已经在运行
+    AlreadyRunning,
+    /// 秒表没有运行
+    NotRunning,
+    /// 分段记录已满
+    LapsFull,
+}
+
+/// 秒表状态
+pub struct Stopwatch {
+    /// 是否正在运行
+    running: bool,
+    /// 本次启动时的系统毫秒数
+    started_at_ms: u64,
+    /// 之前各次运行累计的毫秒数
+    accumulated_ms: u64,
+    /// 分段时间 (秒表读数，毫秒)
+    laps: [u64; MAX_LAPS],
+    lap_count: usize,
+}
+
+impl Stopwatch {
+    /// 创建新的秒表
+    pub const fn new() -> Stopwatch {
+        Stopwatch {
+            running: false,
+            started_at_ms: 0,
+            accumulated_ms: 0,
+            laps: [0; MAX_LAPS],
+            lap_count: 0,
+        }
+    }
+
+    /// 开始 (或继续) 计时
+    pub fn start(&mut self, now_ms: u64) -> Result<(), StopwatchError> {
+        if self.running {
+            return Err(StopwatchError::AlreadyRunning);
+        }
+        self.started_at_ms = now_ms;
+        self.running = true;
+        Ok(())
+    }
+
+    /// 停止计时，返回当前读数
+    pub fn stop(&mut self, now_ms: u64) -> Result<u64, StopwatchError> {
+        if !self.running {
+            return Err(StopwatchError::NotRunning);
+        }
+        self.accumulated_ms = self.elapsed_ms(now_ms);
+        self.running = false;
+        Ok(self.accumulated_ms)
+    }
+
+    /// 记录一个分段，返回 (分段序号, 读数)
+    pub fn lap(&mut self, now_ms: u64) -> Result<(usize, u64), StopwatchError> {
+        if !self.running {
+            return Err(StopwatchError::NotRunning);
+        }
+        if self.lap_count >= MAX_LAPS {
+            return Err(StopwatchError::LapsFull);
+        }
+        let elapsed = self.elapsed_ms(now_ms);
+        self.laps[self.lap_count] = elapsed;
+        self.lap_count += 1;
+        Ok((self.lap_count, elapsed))
+    }
+
+    /// 归零并清空分段
+    pub fn reset(&mut self) {
+        *self = Stopwatch::new();
+    }
+
+    /// 获取当前读数 (毫秒)
+    pub fn elapsed_ms(&self, now_ms: u64) -> u64 {
+        if self.running {
+            self.accumulated_ms + now_ms.saturating_sub(self.started_at_ms)
+        } else {
+            self.accumulated_ms
+        }
+    }
+
+    /// 是否正在运行
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// 已记录的分段
+    pub fn laps(&self) -> &[u64] {
+        &self.laps[..self.lap_count]
+    }
+}
+
+/// 全局秒表
+pub static STOPWATCH: Mutex<Stopwatch> = Mutex::new(Stopwatch::new());
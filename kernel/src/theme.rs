@@ -0,0 +1,62 @@
+// kernel/src/theme.rs
+// 配色主题 - 协调的前景色、背景色和提示符颜色
+
+use crate::writer::Color;
+use spin::Mutex;
+
+/// 一组配色
+pub struct Theme {
+    pub name: &'static str,
+    /// 普通文字颜色
+    pub fg: Color,
+    /// 背景色
+    pub bg: Color,
+    /// 提示符颜色 (上一条命令成功时)
+    pub prompt: Color,
+}
+
+/// 内置主题，第一个是启动时的默认主题
+pub static THEMES: [Theme; 4] = [
+    Theme {
+        name: "default",
+        fg: Color::WHITE,
+        bg: Color::BLACK,
+        prompt: Color::GREEN,
+    },
+    Theme {
+        name: "solarized",
+        fg: Color { r: 0x83, g: 0x94, b: 0x96 },   // base0
+        bg: Color { r: 0x00, g: 0x2B, b: 0x36 },   // base03
+        prompt: Color { r: 0x26, g: 0x8B, b: 0xD2 }, // blue
+    },
+    Theme {
+        name: "matrix",
+        fg: Color { r: 0x00, g: 0xFF, b: 0x41 },
+        bg: Color::BLACK,
+        prompt: Color { r: 0x00, g: 0x8F, b: 0x11 },
+    },
+    Theme {
+        name: "mono",
+        fg: Color { r: 0xC0, g: 0xC0, b: 0xC0 },
+        bg: Color::BLACK,
+        prompt: Color::WHITE,
+    },
+];
+
+/// 当前主题
+static ACTIVE: Mutex<&'static Theme> = Mutex::new(&THEMES[0]);
+
+/// 按名字查找主题
+pub fn find(name: &str) -> Option<&'static Theme> {
+    THEMES.iter().find(|theme| theme.name == name)
+}
+
+/// 当前主题
+pub fn active() -> &'static Theme {
+    *ACTIVE.lock()
+}
+
+/// 记录当前主题 (不负责重绘屏幕)
+pub fn set_active(theme: &'static Theme) {
+    *ACTIVE.lock() = theme;
+}
@@ -1,14 +1,24 @@
 // kernel/src/time.rs
 // 系统时间管理
 
+use core::sync::atomic::{AtomicU64, Ordering};
 use spin::Mutex;
 
-/// 时间管理器
+use crate::event::{self, WorkFlags};
+use crate::rtc::DateTime;
+
+/// 系统启动以来的 tick 数
+/// 定时器中断处理程序不加锁地递增，只是计数器，不与其它内存同步，用 Relaxed 即可
+static TICKS: AtomicU64 = AtomicU64::new(0);
+
+/// 时间管理器: 保存 tick 周期，把 tick 数换算为运行时间
+/// 这些字段只在初始化时设置一次，中断处理程序不访问
 pub struct TimeManager {
-    /// 系统启动以来的毫秒数
-    system_ticks: u64,
-    /// 每个tick的毫秒数 (由PIT决定)
-    ms_per_tick: u32,
+    /// tick 周期 = divisor / base_hz 秒 (PIT 为 DIVISOR / 1193182Hz)
+    base_hz: u32,
+    divisor: u32,
+    /// 每个tick的整微秒数
+    us_per_tick: u32,
     /// 是否已初始化
     initialized: bool,
 }
@@ -17,64 +27,42 @@ impl TimeManager {
     /// 创建新的时间管理器
     pub const fn new() -> TimeManager {
         TimeManager {
-            system_ticks: 0,
-            ms_per_tick: 10, // 默认10ms (100Hz)
+            base_hz: 100, // 默认100Hz (10ms)
+            divisor: 1,
+            us_per_tick: 10_000,
             initialized: false,
         }
     }
 
-    /// 初始化时间管理器
-    pub fn initialize(&mut self, ms_per_tick: u32) {
-        self.ms_per_tick = ms_per_tick;
-        self.system_ticks = 0;
+    /// 初始化时间管理器，tick 周期为 divisor / base_hz 秒
+    /// 例如 PIT 传入 (1193182, 分频值)，精确的 60Hz 传入 (60, 1)
+    pub fn initialize(&mut self, base_hz: u32, divisor: u32) {
+        self.base_hz = base_hz.max(1);
+        self.divisor = divisor;
+        self.us_per_tick = (divisor as u64 * 1_000_000 / self.base_hz as u64) as u32;
         self.initialized = true;
     }
 
-    /// 系统tick中断时调用 (暂时手动调用用于测试)
-    pub fn tick(&mut self) {
-        if self.initialized {
-            self.system_ticks += 1;
-        }
-    }
-
-    /// 获取系统运行的总毫秒数
-    pub fn get_uptime_ms(&self) -> u64 {
+    /// `ticks` 个 tick 对应的运行微秒数
+    /// 直接按精确周期相乘再截断 (例如 60Hz 每 tick 16666.67us)，长时间运行也不漂移
+    pub fn get_uptime_us(&self, ticks: u64) -> u64 {
         if self.initialized {
-            self.system_ticks * (self.ms_per_tick as u64)
+            (ticks as u128 * self.divisor as u128 * 1_000_000 / self.base_hz as u128) as u64
         } else {
             0
         }
     }
 
-    /// 获取系统运行的秒数
-    pub fn get_uptime_seconds(&self) -> u64 {
-        self.get_uptime_ms() / 1000
-    }
-
-    /// 获取格式化的运行时间 (天:小时:分钟:秒)
-    pub fn get_uptime_formatted(&self) -> UptimeInfo {
-        let total_seconds = self.get_uptime_seconds();
-        
-        let days = total_seconds / 86400;
-        let hours = (total_seconds % 86400) / 3600;
-        let minutes = (total_seconds % 3600) / 60;
-        let seconds = total_seconds % 60;
-        let milliseconds = (self.get_uptime_ms() % 1000) as u16;
-
-        UptimeInfo {
-            days,
-            hours,
-            minutes,
-            seconds,
-            milliseconds,
-            total_ms: self.get_uptime_ms(),
-            total_seconds: self.get_uptime_seconds(), // ✨ 新增字段
-        }
+    /// 获取系统运行的总毫秒数
+    pub fn get_uptime_ms(&self, ticks: u64) -> u64 {
+        self.get_uptime_us(ticks) / 1000
     }
 
-    /// 获取tick计数
-    pub fn get_tick_count(&self) -> u64 {
-        self.system_ticks
+    /// 至少经过 `ms` 毫秒所需的 tick 数 (向上取整，至少 1)
+    pub fn ms_to_ticks(&self, ms: u64) -> u64 {
+        let period = self.divisor as u128 * 1000;
+        let ticks = (ms as u128 * self.base_hz as u128).div_ceil(period.max(1));
+        (ticks as u64).max(1)
     }
 
     /// 检查是否已初始化
@@ -91,22 +79,28 @@ pub struct UptimeInfo {
     pub minutes: u64,
     pub seconds: u64,
     pub milliseconds: u16,
-    pub total_ms: u64,
-    pub total_seconds: u64, // ✨ 新增字段
 }
 
 impl UptimeInfo {
+    /// 从毫秒数拆分出天/时/分/秒/毫秒
+    pub fn from_ms(total_ms: u64) -> UptimeInfo {
+        let total_seconds = total_ms / 1000;
+
+        UptimeInfo {
+            days: total_seconds / 86400,
+            hours: (total_seconds % 86400) / 3600,
+            minutes: (total_seconds % 3600) / 60,
+            seconds: total_seconds % 60,
+            milliseconds: (total_ms % 1000) as u16,
+        }
+    }
+
     /// 格式化为字符串显示
     pub fn format_detailed(&self) -> FormattedUptime {
         FormattedUptime {
             uptime_info: *self,
         }
     }
-
-    /// 直接获取总秒数
-    pub fn get_uptime_seconds(&self) -> u64 {
-        self.total_seconds
-    }
 }
 
 /// 用于格式化显示的包装器
@@ -123,62 +117,594 @@ impl FormattedUptime {
             self.uptime_info.seconds,
         )
     }
+}
 
-    /// 获取详细格式的各个组件
-    pub fn detailed_format(&self) -> (u64, u64, u64, u64, u16) {
-        (
-            self.uptime_info.days,
-            self.uptime_info.hours,
-            self.uptime_info.minutes,
-            self.uptime_info.seconds,
-            self.uptime_info.milliseconds,
-        )
+/// 全局时间管理器
+/// 中断处理程序 (例如记录日志时) 也会读取运行时间，所以总是在关中断时加锁，
+/// 否则持锁期间到来的中断再次加锁会永远自旋
+static TIME_MANAGER: Mutex<TimeManager> = Mutex::new(TimeManager::new());
+
+/// 关中断访问时间管理器
+#[cfg(not(test))]
+fn with_manager<R>(f: impl FnOnce(&mut TimeManager) -> R) -> R {
+    x86_64::instructions::interrupts::without_interrupts(|| f(&mut TIME_MANAGER.lock()))
+}
+
+/// 测试在用户态运行，不能执行 cli，直接加锁
+#[cfg(test)]
+fn with_manager<R>(f: impl FnOnce(&mut TimeManager) -> R) -> R {
+    f(&mut TIME_MANAGER.lock())
+}
+
+/// 初始化时间系统，tick 周期为 divisor / base_hz 秒
+/// 初始化之前的 tick 不计入运行时间
+pub fn init(base_hz: u32, divisor: u32) {
+    let ticks_per_second = with_manager(|manager| {
+        manager.initialize(base_hz, divisor);
+        manager.ms_to_ticks(1000)
+    });
+    TICKS.store(0, Ordering::Relaxed);
+    TICKS_PER_SECOND.store(ticks_per_second, Ordering::Relaxed);
+}
+
+/// 每秒的 tick 数，由 init 按 tick 周期换算；0 表示尚未初始化，tick() 不登记 SECOND 工作
+static TICKS_PER_SECOND: AtomicU64 = AtomicU64::new(0);
+
+/// 每秒回调 (总是在关中断时加锁)
+static ON_SECOND: Mutex<Option<fn(u64)>> = Mutex::new(None);
+
+/// 系统tick (在定时器中断处理程序中调用)
+/// 只递增计数并登记工作，不加任何锁: 每个 tick 登记 TICK，每 TICKS_PER_SECOND 个 tick 再登记 SECOND；
+/// 换算时间、检查定时器和执行每秒回调都由主循环完成
+pub fn tick() {
+    let ticks = TICKS.fetch_add(1, Ordering::Relaxed) + 1;
+    event::raise(WorkFlags::TICK);
+    let per_second = TICKS_PER_SECOND.load(Ordering::Relaxed);
+    if per_second != 0 && ticks.is_multiple_of(per_second) {
+        event::raise(WorkFlags::SECOND);
     }
+}
 
-    /// 获取总毫秒数
-    pub fn total_milliseconds(&self) -> u64 {
-        self.uptime_info.total_ms
+/// 在主循环中调用 (处理 TICK 工作): 有定时器到期时登记 TIMER 工作
+pub fn poll_tick() {
+    let now_ms = get_uptime_ms();
+    if with_timers(|timers| timers.next_deadline()).is_some_and(|deadline| deadline <= now_ms) {
+        event::raise(WorkFlags::TIMER);
     }
 }
 
-/// 全局时间管理器
-static TIME_MANAGER: Mutex<TimeManager> = Mutex::new(TimeManager::new());
+/// 在主循环中调用 (处理 SECOND 工作): 以当前的运行秒数执行每秒回调
+pub fn run_second_callback() {
+    let callback = x86_64::instructions::interrupts::without_interrupts(|| *ON_SECOND.lock());
+    if let Some(callback) = callback {
+        callback(get_uptime_ms() / 1000);
+    }
+}
 
-/// 初始化时间系统
-pub fn init(ms_per_tick: u32) {
-    TIME_MANAGER.lock().initialize(ms_per_tick);
+/// 注册每秒回调，参数是当前的运行秒数；None 取消注册
+/// 回调不在中断处理程序中执行: tick() 只登记 SECOND 工作，由主循环开中断调用回调，
+/// 所以回调可以正常打印和加锁 (包括 WRITER)；但主循环被长时间运行的命令占用时会推迟执行，
+/// 几秒的工作合并成一次，回调应当很快返回，并以参数而不是调用次数计时
+// 给状态栏之类的周期刷新使用，目前内核还没有注册方
+#[allow(dead_code)]
+pub fn on_second(callback: Option<fn(u64)>) {
+    // 关中断加锁，避免与主循环中的 run_second_callback 在中断里交错
+    x86_64::instructions::interrupts::without_interrupts(|| *ON_SECOND.lock() = callback);
 }
 
-/// 系统tick (目前手动调用用于测试)
-pub fn tick() {
-    TIME_MANAGER.lock().tick();
+/// 软件定时器的槽位数，同时最多有这么多个定时器
+pub const MAX_TIMERS: usize = 8;
+
+/// 定时器回调，参数是触发的定时器 (周期定时器可以据此取消自己)
+pub type TimerCallback = fn(TimerId);
+
+/// 定时器的标识
+/// 槽位被复用后代数不同，旧的 TimerId 不会误取消新的定时器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerId {
+    slot: usize,
+    generation: u32,
 }
 
-/// 获取系统运行时间
-pub fn get_uptime() -> UptimeInfo {
-    TIME_MANAGER.lock().get_uptime_formatted()
+#[derive(Clone, Copy)]
+struct Timer {
+    callback: TimerCallback,
+    /// 下一次触发的运行时间 (毫秒)
+    deadline_ms: u64,
+    /// 周期 (毫秒)，一次性定时器为 None
+    period_ms: Option<u64>,
+}
+
+/// 定时器槽位表
+struct TimerTable {
+    timers: [Option<Timer>; MAX_TIMERS],
+    generations: [u32; MAX_TIMERS],
+}
+
+impl TimerTable {
+    const fn new() -> TimerTable {
+        TimerTable {
+            timers: [None; MAX_TIMERS],
+            generations: [0; MAX_TIMERS],
+        }
+    }
+
+    /// 放入一个空闲槽位，没有空闲槽位时返回 None
+    fn add(&mut self, timer: Timer) -> Option<TimerId> {
+        let slot = self.timers.iter().position(Option::is_none)?;
+        self.timers[slot] = Some(timer);
+        self.generations[slot] = self.generations[slot].wrapping_add(1);
+        Some(TimerId { slot, generation: self.generations[slot] })
+    }
+
+    // 只有 cancel_timer 调用，见那里的说明
+    #[allow(dead_code)]
+    fn cancel(&mut self, id: TimerId) -> bool {
+        match self.timers.get_mut(id.slot) {
+            Some(timer) if timer.is_some() && self.generations[id.slot] == id.generation => {
+                *timer = None;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn active_count(&self) -> usize {
+        self.timers.iter().filter(|timer| timer.is_some()).count()
+    }
+
+    /// 最早的触发时间
+    fn next_deadline(&self) -> Option<u64> {
+        self.timers.iter().flatten().map(|timer| timer.deadline_ms).min()
+    }
+
+    /// 取出一个已到期的定时器: 一次性定时器释放槽位，周期定时器推迟一个周期
+    /// 错过了多个周期时会连续到期多次，触发次数与经过的时间一致
+    fn take_expired(&mut self, now_ms: u64) -> Option<(TimerId, TimerCallback)> {
+        let slot = self
+            .timers
+            .iter()
+            .position(|timer| timer.is_some_and(|timer| timer.deadline_ms <= now_ms))?;
+        let timer = self.timers[slot]?;
+        self.timers[slot] = timer
+            .period_ms
+            .map(|period| Timer { deadline_ms: timer.deadline_ms + period, ..timer });
+        Some((TimerId { slot, generation: self.generations[slot] }, timer.callback))
+    }
+}
+
+/// 全局定时器表 (总是在关中断时加锁)
+static TIMERS: Mutex<TimerTable> = Mutex::new(TimerTable::new());
+
+/// 关中断访问定时器表
+#[cfg(not(test))]
+fn with_timers<R>(f: impl FnOnce(&mut TimerTable) -> R) -> R {
+    x86_64::instructions::interrupts::without_interrupts(|| f(&mut TIMERS.lock()))
+}
+
+/// 测试在用户态运行，不能执行 cli，直接加锁
+#[cfg(test)]
+fn with_timers<R>(f: impl FnOnce(&mut TimerTable) -> R) -> R {
+    f(&mut TIMERS.lock())
+}
+
+fn add_timer(delay_ms: u64, period_ms: Option<u64>, callback: TimerCallback) -> Option<TimerId> {
+    let deadline_ms = get_uptime_ms().saturating_add(delay_ms);
+    with_timers(|timers| timers.add(Timer { callback, deadline_ms, period_ms }))
+}
+
+/// 注册一次性定时器，`delay_ms` 毫秒后触发一次；槽位已满时返回 None
+/// 回调在主循环中开中断执行，可以正常打印和加锁，但应当很快返回
+// 定时器 API 的一部分，内核目前只用到周期定时器 (光标闪烁)，由测试覆盖
+#[allow(dead_code)]
+pub fn set_timeout(delay_ms: u64, callback: TimerCallback) -> Option<TimerId> {
+    add_timer(delay_ms, None, callback)
+}
+
+/// 注册周期定时器，每 `period_ms` 毫秒 (至少 1) 触发一次；槽位已满时返回 None
+pub fn set_interval(period_ms: u64, callback: TimerCallback) -> Option<TimerId> {
+    let period_ms = period_ms.max(1);
+    add_timer(period_ms, Some(period_ms), callback)
+}
+
+/// 取消定时器，返回它是否仍处于活动状态 (已触发的一次性定时器返回 false)
+// 定时器 API 的一部分，光标闪烁的周期定时器从不取消，目前只有测试调用
+#[allow(dead_code)]
+pub fn cancel_timer(id: TimerId) -> bool {
+    with_timers(|timers| timers.cancel(id))
+}
+
+/// 活动的定时器个数
+pub fn active_timer_count() -> usize {
+    with_timers(|timers| timers.active_count())
+}
+
+/// 在主循环中调用 (处理 TIMER 工作): 执行所有已到期的定时器回调
+/// 每次只在锁内取出一个定时器，回调执行时不持有锁，可以注册或取消定时器
+pub fn run_expired_timers() {
+    let now_ms = get_uptime_ms();
+    while let Some((id, callback)) = with_timers(|timers| timers.take_expired(now_ms)) {
+        callback(id);
+    }
+}
+
+/// 获取系统运行的微秒数 (tick 精度)
+pub fn get_uptime_us() -> u64 {
+    with_manager(|manager| manager.get_uptime_us(get_tick_count()))
 }
 
 /// 获取系统运行的毫秒数
 pub fn get_uptime_ms() -> u64 {
-    TIME_MANAGER.lock().get_uptime_ms()
+    with_manager(|manager| manager.get_uptime_ms(get_tick_count()))
 }
 
-/// 获取tick计数
+/// 获取tick计数 (不加锁，可以在中断处理程序中调用)
 pub fn get_tick_count() -> u64 {
-    TIME_MANAGER.lock().get_tick_count()
+    TICKS.load(Ordering::Relaxed)
+}
+
+/// 至少经过 `ms` 毫秒所需的 tick 数，按当前的 tick 周期换算
+pub fn ms_to_ticks(ms: u64) -> u64 {
+    with_manager(|manager| manager.ms_to_ticks(ms))
+}
+
+/// 睡眠指定的毫秒数 (必须在中断开启时调用)
+/// 每次被中断唤醒后检查 `should_stop`，返回 true 时提前结束
+/// 返回 true 表示睡满了整个时长
+pub fn sleep_ms(ms: u64, should_stop: impl Fn() -> bool) -> bool {
+    if !is_initialized() {
+        return true;
+    }
+
+    let deadline = get_uptime_ms().saturating_add(ms);
+    while get_uptime_ms() < deadline {
+        if should_stop() {
+            return false;
+        }
+        x86_64::instructions::hlt();
+    }
+    true
+}
+
+/// 获取系统运行的微秒数 (近似值)
+/// 整 tick 部分来自 tick 计数，不足一个 tick 的部分来自 PIT 通道0的倒计数
+pub fn now_micros() -> u64 {
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        let manager = TIME_MANAGER.lock();
+        if !manager.is_initialized() {
+            return 0;
+        }
+
+        let us_per_tick = manager.us_per_tick as u64;
+        let count = crate::pit::read_count();
+        // 关中断期间计数器可能已经回绕但 tick 尚未记入，限制小数部分不超过一个 tick
+        let fraction = crate::pit::count_to_elapsed_us(count).min(us_per_tick.saturating_sub(1));
+        manager.get_uptime_us(get_tick_count()) + fraction
+    })
+}
+
+/// TSC 校准时测量的 tick 数
+const TSC_CALIBRATION_TICKS: u64 = 5;
+
+/// 每毫秒的 TSC 计数 (即 kHz)，0 表示尚未校准
+static TSC_KHZ: AtomicU64 = AtomicU64::new(0);
+
+/// 用 PIT 校准 TSC: 对齐到 tick 边界后测量若干个 tick 内的 TSC 增量
+/// 必须在中断开启、时间系统初始化之后调用；返回每毫秒的 TSC 计数
+pub fn calibrate_tsc() -> Option<u64> {
+    if !is_initialized() {
+        return None;
+    }
+
+    // 先等到一个新的 tick 开始，测量区间正好是整数个 tick
+    let start_tick = get_tick_count();
+    while get_tick_count() == start_tick {
+        x86_64::instructions::hlt();
+    }
+    let first_tick = get_tick_count();
+    let start_us = get_uptime_us();
+    let start_tsc = crate::cpu::rdtsc();
+    while get_tick_count() < first_tick + TSC_CALIBRATION_TICKS {
+        x86_64::instructions::hlt();
+    }
+    let elapsed_tsc = crate::cpu::rdtsc().wrapping_sub(start_tsc);
+    let elapsed_us = get_uptime_us() - start_us;
+
+    let khz = (elapsed_tsc as u128 * 1000 / elapsed_us.max(1) as u128) as u64;
+    if khz == 0 {
+        return None;
+    }
+    TSC_KHZ.store(khz, Ordering::Relaxed);
+    Some(khz)
+}
+
+/// 校准得到的每毫秒 TSC 计数，未校准时为 None
+pub fn tsc_khz() -> Option<u64> {
+    match TSC_KHZ.load(Ordering::Relaxed) {
+        0 => None,
+        khz => Some(khz),
+    }
+}
+
+/// 把 TSC 计数换算为纳秒，未校准时返回 None
+pub fn rdtsc_to_ns(cycles: u64) -> Option<u64> {
+    tsc_khz().map(|khz| cycles_to_ns(cycles, khz))
+}
+
+/// 按每毫秒 `khz` 个计数把 TSC 计数换算为纳秒 (用 128 位中间值避免溢出)
+fn cycles_to_ns(cycles: u64, khz: u64) -> u64 {
+    (cycles as u128 * 1_000_000 / khz as u128) as u64
+}
+
+/// 漂移检测的参考点: RTC 秒边沿时的 (RTC 纪元秒数, 运行微秒数)，只在主循环中访问
+static DRIFT_REFERENCE: Mutex<Option<(u64, u64)>> = Mutex::new(None);
+
+/// 运行时间为 0 时的墙上时间，启动时由 RTC 设置
+static BOOT_TIME: Mutex<Option<DateTime>> = Mutex::new(None);
+
+/// 记录启动时刻 (运行时间为 0 时) 的墙上时间
+pub fn set_boot_time(boot: DateTime) {
+    *BOOT_TIME.lock() = Some(boot);
+}
+
+/// 按当前的 RTC 时间倒推并记录启动时刻，返回记录的启动时间
+/// 之后 current_datetime 只用运行时间推算，不再读 CMOS
+pub fn seed_boot_time_from_rtc() -> DateTime {
+    let now = crate::rtc::read().to_epoch_seconds();
+    let boot = DateTime::from_epoch_seconds(now.saturating_sub(get_uptime_ms() / 1000));
+    set_boot_time(boot);
+    boot
+}
+
+/// 启动时刻的墙上时间，还没设置时为 None
+pub fn boot_time() -> Option<DateTime> {
+    *BOOT_TIME.lock()
+}
+
+/// 当前墙上时间 = 启动时间 + 运行时间，还没设置启动时间时为 None
+pub fn current_datetime() -> Option<DateTime> {
+    BOOT_TIME.lock().map(|boot| boot.add_seconds(get_uptime_ms() / 1000))
+}
+
+/// PIT 计时与 RTC 的比较结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriftReport {
+    /// 参考点以来 RTC 走过的毫秒数 (整秒)
+    pub rtc_elapsed_ms: u64,
+    /// 参考点以来 PIT 计时走过的毫秒数
+    pub pit_elapsed_ms: u64,
+    /// PIT - RTC，正数表示 PIT 走快了
+    pub delta_ms: i64,
+}
+
+/// 漂移检测失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftError {
+    /// 还没有参考点 (这次调用已经设置)
+    NoReference,
+    /// 等不到 RTC 的秒边沿
+    RtcTimeout,
+}
+
+/// 在 RTC 秒边沿采样，返回 (RTC 纪元秒数, 运行微秒数)
+/// RTC 只有整秒精度，在边沿采样时两者的相位差不超过一个 tick
+fn sample_at_rtc_edge() -> Result<(u64, u64), DriftError> {
+    let now = crate::rtc::wait_for_second_edge().ok_or(DriftError::RtcTimeout)?;
+    Ok((now.to_epoch_seconds(), get_uptime_us()))
+}
+
+/// 以当前时刻为漂移检测的参考点 (最多等待一秒)
+pub fn set_drift_reference() -> Result<(), DriftError> {
+    let sample = sample_at_rtc_edge()?;
+    *DRIFT_REFERENCE.lock() = Some(sample);
+    Ok(())
+}
+
+/// 比较参考点以来 RTC 和 PIT 各自走过的时间 (最多等待一秒)
+/// 没有参考点时设置参考点并返回 NoReference
+pub fn check_drift() -> Result<DriftReport, DriftError> {
+    let reference = *DRIFT_REFERENCE.lock();
+    match reference {
+        Some(reference) => Ok(drift_between(reference, sample_at_rtc_edge()?)),
+        None => {
+            set_drift_reference()?;
+            Err(DriftError::NoReference)
+        }
+    }
+}
+
+/// 计算两个边沿采样之间的漂移
+fn drift_between(reference: (u64, u64), now: (u64, u64)) -> DriftReport {
+    let rtc_elapsed_ms = now.0.saturating_sub(reference.0) * 1000;
+    let pit_elapsed_ms = now.1.saturating_sub(reference.1) / 1000;
+    DriftReport {
+        rtc_elapsed_ms,
+        pit_elapsed_ms,
+        delta_ms: pit_elapsed_ms as i64 - rtc_elapsed_ms as i64,
+    }
 }
 
 /// 检查时间系统是否已初始化
 pub fn is_initialized() -> bool {
-    TIME_MANAGER.lock().is_initialized()
+    with_manager(|manager| manager.is_initialized())
 }
 
-/// 模拟时间流逝 (用于测试)
+/// 模拟时间流逝 (只用于测试，内核中时间只能由定时器中断推进)
 /// 这个函数会模拟指定数量的tick
+#[cfg(test)]
 pub fn simulate_time_passage(ticks: u64) {
-    let mut manager = TIME_MANAGER.lock();
-    for _ in 0..ticks {
-        manager.tick();
+    TICKS.fetch_add(ticks, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 以 frequency_hz 的 tick 频率经过 ticks 个 tick 后的运行毫秒数
+    fn uptime_after(frequency_hz: u32, ticks: u64) -> u64 {
+        let mut manager = TimeManager::new();
+        manager.initialize(frequency_hz, 1);
+        manager.get_uptime_ms(ticks)
+    }
+
+    #[test]
+    fn uptime_is_exact_at_60hz() {
+        // 每 tick 16.67ms，按精确周期换算不会累计误差
+        assert_eq!(uptime_after(60, 1), 16);
+        assert_eq!(uptime_after(60, 3), 50);
+        assert_eq!(uptime_after(60, 30), 500);
+        assert_eq!(uptime_after(60, 60), 1000);
+        let long = UptimeInfo::from_ms(uptime_after(60, 60 * 3661 + 45));
+        assert_eq!((long.hours, long.minutes, long.seconds, long.milliseconds), (1, 1, 1, 750));
+    }
+
+    #[test]
+    fn uptime_is_exact_at_1000hz() {
+        let uptime = UptimeInfo::from_ms(uptime_after(1000, 1234));
+        assert_eq!((uptime.seconds, uptime.milliseconds), (1, 234));
+    }
+
+    #[test]
+    fn pit_uptime_does_not_drift_over_an_hour() {
+        // PIT 100Hz 的真实周期是 11931 / 1193182 秒 ≈ 9999.3us
+        const BASE_HZ: u64 = 1_193_182;
+        const DIVISOR: u64 = 11_931;
+        let ticks = 3600 * BASE_HZ / DIVISOR;
+
+        let mut manager = TimeManager::new();
+        manager.initialize(BASE_HZ as u32, DIVISOR as u32);
+
+        let exact_us = (ticks as u128 * DIVISOR as u128 * 1_000_000 / BASE_HZ as u128) as u64;
+        assert_eq!(manager.get_uptime_us(ticks), exact_us);
+        // 一小时的 tick 数按整数截断，误差小于一个 tick
+        assert!(3_600_000_000 - manager.get_uptime_us(ticks) < 10_000);
+    }
+
+    #[test]
+    fn uptime_is_exact_after_an_hour_at_60hz() {
+        assert_eq!(uptime_after(60, 60 * 3600), 3_600_000);
+        let uptime = UptimeInfo::from_ms(uptime_after(60, 60 * 3600));
+        assert_eq!(uptime.hours, 1);
+        assert_eq!((uptime.minutes, uptime.seconds, uptime.milliseconds), (0, 0, 0));
+    }
+
+    #[test]
+    fn ms_to_ticks_rounds_up() {
+        let mut manager = TimeManager::new();
+        manager.initialize(100, 1);
+        assert_eq!(manager.ms_to_ticks(500), 50);
+        assert_eq!(manager.ms_to_ticks(55), 6);
+        assert_eq!(manager.ms_to_ticks(0), 1);
+        manager.initialize(60, 1);
+        assert_eq!(manager.ms_to_ticks(50), 3);
+    }
+
+    /// 使用全局 TIME_MANAGER 的测试持有这个锁，避免并行测试互相干扰
+    /// 其它测试都用独立的 TimeManager
+    static GLOBAL_CLOCK: Mutex<()> = Mutex::new(());
+
+    /// 推进全局时钟并返回格式化的结果
+    #[test]
+    fn simulated_global_clock_drives_formatting() {
+        use crate::format::{self, LineBuffer};
+
+        let _clock = GLOBAL_CLOCK.lock();
+        init(100, 1);
+        let mut out = LineBuffer::<64>::new();
+
+        format::write_hms(&mut out, get_uptime_ms()).unwrap();
+        assert_eq!(out.as_str(), "00:00:00");
+
+        // 1 天 1 小时 1 分 1.25 秒
+        simulate_time_passage(100 * (86_400 + 3_661) + 25);
+        assert_eq!(get_uptime_ms(), 90_061_250);
+
+        out.clear();
+        format::write_uptime(&mut out, get_uptime_ms()).unwrap();
+        assert_eq!(out.as_str(), "1 days, 01:01:01.250");
+
+        out.clear();
+        format::write_hms_millis(&mut out, get_uptime_ms()).unwrap();
+        assert_eq!(out.as_str(), "25:01:01.250");
+    }
+
+    static ONE_SHOT_FIRED: AtomicU64 = AtomicU64::new(0);
+    static PERIODIC_FIRED: AtomicU64 = AtomicU64::new(0);
+
+    #[test]
+    fn one_shot_fires_once_and_periodic_reschedules() {
+        let _clock = GLOBAL_CLOCK.lock();
+        init(100, 1);
+
+        let one_shot = set_timeout(250, |_| {
+            ONE_SHOT_FIRED.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+        let periodic = set_interval(100, |_| {
+            PERIODIC_FIRED.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+        assert_eq!(active_timer_count(), 2);
+
+        // 每 50ms 处理一次到期的定时器，共 1 秒
+        for _ in 0..20 {
+            simulate_time_passage(5);
+            run_expired_timers();
+        }
+        assert_eq!(ONE_SHOT_FIRED.load(Ordering::SeqCst), 1);
+        assert_eq!(PERIODIC_FIRED.load(Ordering::SeqCst), 10);
+        assert_eq!(active_timer_count(), 1);
+
+        // 主循环被占用时错过的周期在下次处理时补上
+        simulate_time_passage(30);
+        run_expired_timers();
+        assert_eq!(PERIODIC_FIRED.load(Ordering::SeqCst), 13);
+
+        assert!(!cancel_timer(one_shot));
+        assert!(cancel_timer(periodic));
+        assert!(!cancel_timer(periodic));
+        assert_eq!(active_timer_count(), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn stale_timer_id_does_not_cancel_reused_slot() {
+        let mut table = TimerTable::new();
+        let timer = Timer { callback: |_| {}, deadline_ms: 10, period_ms: None };
+        let first = table.add(timer).unwrap();
+        assert!(table.take_expired(10).is_some());
+        let second = table.add(timer).unwrap();
+        assert_eq!(first.slot, second.slot);
+        assert!(!table.cancel(first));
+        assert!(table.cancel(second));
+
+        for _ in 0..MAX_TIMERS {
+            assert!(table.add(timer).is_some());
+        }
+        assert!(table.add(timer).is_none());
+    }
+
+    #[test]
+    fn drift_is_pit_minus_rtc() {
+        let reference = (1_000, 5_000_000);
+        assert_eq!(
+            drift_between(reference, (1_060, 65_000_000)),
+            DriftReport { rtc_elapsed_ms: 60_000, pit_elapsed_ms: 60_000, delta_ms: 0 }
+        );
+        assert_eq!(drift_between(reference, (1_060, 65_012_500)).delta_ms, 12);
+        assert_eq!(drift_between(reference, (1_060, 64_990_000)).delta_ms, -10);
+    }
+
+    #[test]
+    fn uptime_is_zero_before_initialization() {
+        let manager = TimeManager::new();
+        assert_eq!(manager.get_uptime_ms(1), 0);
+    }
+
+    #[test]
+    fn cycles_to_ns_uses_calibrated_rate() {
+        // 2 GHz: 每毫秒 2_000_000 个计数
+        assert_eq!(cycles_to_ns(2_000_000, 2_000_000), 1_000_000);
+        assert_eq!(cycles_to_ns(3, 2_000_000), 1);
+        assert_eq!(cycles_to_ns(u64::MAX, 1_000_000), u64::MAX);
+    }
+}
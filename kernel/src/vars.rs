@@ -0,0 +1,128 @@
+// kernel/src/vars.rs
+// Shell 变量表 - 定长数组，无需堆分配
+
+/// 最多变量数量
+pub const MAX_VARIABLES: usize = 16;
+/// 变量名最大长度
+pub const MAX_NAME_LEN: usize = 16;
+/// 变量值最大长度
+pub const MAX_VALUE_LEN: usize = 64;
+
+/// 变量操作错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableError {
+    /// 名称为空或包含非法字符 (只允许字母、数字和下划线)
+    InvalidName,
+    /// 名称过长
+    NameTooLong,
+    /// 变量值过长
+    ValueTooLong,
+    /// 变量表已满
+    TableFull,
+    /// 变量不存在
+    NotFound,
+}
+
+/// 单个变量
+#[derive(Clone, Copy)]
+struct Variable {
+    name: [u8; MAX_NAME_LEN],
+    name_len: usize,
+    value: [u8; MAX_VALUE_LEN],
+    value_len: usize,
+}
+
+impl Variable {
+    const EMPTY: Variable = Variable {
+        name: [0; MAX_NAME_LEN],
+        name_len: 0,
+        value: [0; MAX_VALUE_LEN],
+        value_len: 0,
+    };
+
+    fn is_used(&self) -> bool {
+        self.name_len > 0
+    }
+
+    fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len]).unwrap_or("")
+    }
+
+    fn value(&self) -> &str {
+        core::str::from_utf8(&self.value[..self.value_len]).unwrap_or("")
+    }
+}
+
+/// 变量表
+pub struct VariableTable {
+    entries: [Variable; MAX_VARIABLES],
+}
+
+impl VariableTable {
+    pub const fn new() -> VariableTable {
+        VariableTable {
+            entries: [Variable::EMPTY; MAX_VARIABLES],
+        }
+    }
+
+    /// 设置或更新变量 (值可以为空)
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), VariableError> {
+        if name.is_empty() || !name.chars().all(is_name_char) {
+            return Err(VariableError::InvalidName);
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(VariableError::NameTooLong);
+        }
+        if value.len() > MAX_VALUE_LEN {
+            return Err(VariableError::ValueTooLong);
+        }
+
+        // 已存在则覆盖，否则使用第一个空槽位
+        let slot = match self.position(name) {
+            Some(index) => index,
+            None => self
+                .entries
+                .iter()
+                .position(|entry| !entry.is_used())
+                .ok_or(VariableError::TableFull)?,
+        };
+
+        let entry = &mut self.entries[slot];
+        entry.name[..name.len()].copy_from_slice(name.as_bytes());
+        entry.name_len = name.len();
+        entry.value[..value.len()].copy_from_slice(value.as_bytes());
+        entry.value_len = value.len();
+        Ok(())
+    }
+
+    /// 删除变量
+    pub fn remove(&mut self, name: &str) -> Result<(), VariableError> {
+        let index = self.position(name).ok_or(VariableError::NotFound)?;
+        self.entries[index] = Variable::EMPTY;
+        Ok(())
+    }
+
+    /// 查找变量的值
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.position(name).map(|index| self.entries[index].value())
+    }
+
+    /// 遍历所有变量 (名称, 值)
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_used())
+            .map(|entry| (entry.name(), entry.value()))
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.is_used() && entry.name() == name)
+    }
+}
+
+/// 变量名允许的字符
+pub fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
@@ -1,6 +1,10 @@
 // kernel/src/writer.rs
 
-use crate::font::Font8x8;
+use crate::ansi::{self, Action, AnsiParser, Attributes, CursorCommand};
+use crate::font::{self, Font};
+use crate::format::{self, LineBuffer};
+use crate::scrollback::{Cell, MAX_COLUMNS, SCROLLBACK, SCROLLBACK_LINES};
+use crate::shadow::SHADOW;
 use bootloader_api::info::FrameBufferInfo;
 use core::fmt;
 
@@ -20,6 +24,93 @@ impl Color {
     pub const BLUE: Color = Color { r: 0, g: 0, b: 255 };
     pub const YELLOW: Color = Color { r: 255, g: 255, b: 0 };
     pub const CYAN: Color = Color { r: 0, g: 255, b: 255 };  // ✨ 新增 CYAN 顏色
+
+    /// 按名字查找顏色 (不區分大小寫)
+    pub fn from_name(name: &str) -> Option<Color> {
+        const NAMES: [(&str, Color); 7] = [
+            ("black", Color::BLACK),
+            ("white", Color::WHITE),
+            ("red", Color::RED),
+            ("green", Color::GREEN),
+            ("blue", Color::BLUE),
+            ("yellow", Color::YELLOW),
+            ("cyan", Color::CYAN),
+        ];
+        NAMES.iter().find(|(known, _)| known.eq_ignore_ascii_case(name.trim())).map(|&(_, color)| color)
+    }
+}
+
+/// 啟動時的默認前景色和背景色，構建時由 KERNEL_FG / KERNEL_BG 環境變量指定
+pub fn default_colors() -> (Color, Color) {
+    parse_default_colors(env!("KERNEL_FG"), env!("KERNEL_BG"))
+}
+
+/// 解析默認顏色：未設置或無法識別時用白字黑底，前景與背景相同 (看不見文字) 時也退回
+fn parse_default_colors(fg: &str, bg: &str) -> (Color, Color) {
+    let fg = Color::from_name(fg).unwrap_or(Color::WHITE);
+    let bg = Color::from_name(bg).unwrap_or(Color::BLACK);
+    if fg == bg {
+        (Color::WHITE, Color::BLACK)
+    } else {
+        (fg, bg)
+    }
+}
+
+/// draw_box 畫出的方框 (字符格座標，包含邊框)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextBox {
+    pub row: usize,
+    pub column: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl TextBox {
+    /// 內部可寫文字的列數
+    pub fn inner_width(&self) -> usize {
+        self.width - 2
+    }
+
+    /// 內部可寫文字的行數
+    pub fn inner_height(&self) -> usize {
+        self.height - 2
+    }
+}
+
+/// 文字光標的形狀
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorShape {
+    /// 整個字符格反色
+    Block,
+    /// 字符格底部的橫線
+    Underline,
+    /// 字符格左側的豎線
+    Bar,
+}
+
+impl CursorShape {
+    pub const ALL: [CursorShape; 3] = [CursorShape::Block, CursorShape::Underline, CursorShape::Bar];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CursorShape::Block => "block",
+            CursorShape::Underline => "underline",
+            CursorShape::Bar => "bar",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<CursorShape> {
+        CursorShape::ALL.into_iter().find(|shape| shape.name() == name)
+    }
+}
+
+/// 保存的光標狀態 (位置 + 顏色)
+#[derive(Debug, Clone, Copy)]
+struct SavedCursor {
+    x: usize,
+    y: usize,
+    fg_color: Color,
+    bg_color: Color,
 }
 
 /// 文字輸出管理器
@@ -32,7 +123,29 @@ pub struct Writer {
     bg_color: Color,
     char_width: usize,
     char_height: usize,
+    font: &'static dyn Font,
     scale: usize,
+    saved_cursor: Option<SavedCursor>, // 單個保存槽位
+    saved_colors: Option<(Color, Color)>, // 高亮前用戶設置的顏色，恢復時使用
+    input_start: Option<(usize, usize)>, // 當前輸入行的起點 (提示符之後)，退格不能越過
+    top_line: usize,    // 屏幕第一行對應的回滾緩衝區邏輯行號
+    view_offset: usize, // 向上回滾查看的行數，0 表示在底部
+    front: Option<&'static mut [u8]>,   // 啟用雙緩衝時的真實幀緩衝區，此時 buffer 是後備緩衝區
+    dirty_rows: Option<(usize, usize)>, // 後備緩衝區中尚未 present 的像素行範圍 [起, 止)
+    scroll_region: Option<(usize, usize)>, // 滾動區域 (首行, 末行)，None 表示整個屏幕
+    tab_width: usize,   // 制表位間隔 (列)
+    perf_overlay: bool, // 是否在右上角顯示上一幀的渲染耗時
+    last_frame: Option<(&'static str, u64)>, // 上一次整屏滾動或重繪的 (類型, TSC 計數)
+    attributes: Attributes, // 當前的文字屬性 (粗體、暗淡、下劃線、刪除線)
+    ansi: AnsiParser,   // 輸出中 ANSI 轉義序列的解析狀態
+    split_rows: usize,  // 分屏模式下底部輸入區的行數，0 表示未分屏
+    output_cursor: Option<(usize, usize)>, // 分屏模式下焦點在輸入區時保存的輸出光標
+    cursor_shape: CursorShape, // 文字光標的形狀
+    cursor_drawn: Option<(usize, usize)>, // 屏幕上畫著光標的位置 (像素)，None 表示光標沒有顯示
+    word_wrap: bool,    // 自動換行時不把單詞拆到兩行
+    pending_word: [Cell; WRAP_WORD_MAX], // 自動換行模式下還沒輸出的單詞 (連同顏色和屬性)
+    pending_len: usize, // pending_word 中的字符數
+    bell_pending: bool, // 輸出過 BEL、還沒有閃爍屏幕
 }
 
 impl Writer {
@@ -41,18 +154,91 @@ impl Writer {
         buffer: &'static mut [u8],
         info: FrameBufferInfo,
     ) -> Writer {
-        let scale = 2;
+        let font = font::FONTS[0];
+        // 屏幕太小時自動縮小字體
+        let scale = fitting_scale(font.width(), font.height(), info.width, info.height, DEFAULT_SCALE);
+        let (fg_color, bg_color) = default_colors();
         Writer {
             buffer,
             info,
             cursor_x: 0,
             cursor_y: 0,
-            fg_color: Color::WHITE,
-            bg_color: Color::BLACK,
-            char_width: Font8x8::WIDTH * scale,
-            char_height: Font8x8::HEIGHT * scale,
+            fg_color,
+            bg_color,
+            char_width: font.width() * scale,
+            char_height: font.height() * scale,
+            font,
             scale,
+            saved_cursor: None,
+            saved_colors: None,
+            input_start: None,
+            top_line: 0,
+            view_offset: 0,
+            front: None,
+            dirty_rows: None,
+            scroll_region: None,
+            tab_width: DEFAULT_TAB_WIDTH,
+            perf_overlay: false,
+            last_frame: None,
+            attributes: Attributes::NONE,
+            ansi: AnsiParser::new(),
+            split_rows: 0,
+            output_cursor: None,
+            cursor_shape: CursorShape::Block,
+            cursor_drawn: None,
+            word_wrap: false,
+            pending_word: [Cell { ch: ' ', fg: fg_color, bg: bg_color, attributes: Attributes::NONE }; WRAP_WORD_MAX],
+            pending_len: 0,
+            bell_pending: false,
+        }
+    }
+
+    /// 啟用雙緩衝：之後所有繪製都寫入 `back`，由 present() 複製到屏幕
+    /// `back` 小於幀緩衝區或已經啟用時返回 false
+    pub fn enable_double_buffering(&mut self, back: &'static mut [u8]) -> bool {
+        let size = self.info.stride * self.info.height * self.info.bytes_per_pixel;
+        if self.front.is_some() || back.len() < size || self.buffer.len() < size {
+            return false;
+        }
+        // 以當前畫面作為後備緩衝區的初始內容
+        back[..size].copy_from_slice(&self.buffer[..size]);
+        let front = core::mem::replace(&mut self.buffer, &mut back[..size]);
+        self.front = Some(front);
+        self.dirty_rows = None;
+        true
+    }
+
+    /// 是否啟用了雙緩衝
+    pub fn is_double_buffered(&self) -> bool {
+        self.front.is_some()
+    }
+
+    /// 把後備緩衝區中變化的行複製到屏幕，未啟用雙緩衝時不做任何事
+    pub fn present(&mut self) {
+        let (start, end) = match (self.front.as_mut(), self.dirty_rows.take()) {
+            (Some(_), Some(rows)) => rows,
+            _ => return,
+        };
+        let line_bytes = self.info.stride * self.info.bytes_per_pixel;
+        let range = start * line_bytes..end * line_bytes;
+        if let Some(front) = self.front.as_mut() {
+            front[range.clone()].copy_from_slice(&self.buffer[range]);
+        }
+    }
+
+    /// 標記像素行 [start, end) 需要 present
+    fn mark_dirty(&mut self, start: usize, end: usize) {
+        if self.front.is_none() {
+            return;
+        }
+        let end = end.min(self.info.height);
+        if start >= end {
+            return;
         }
+        self.dirty_rows = Some(match self.dirty_rows {
+            Some((old_start, old_end)) => (old_start.min(start), old_end.max(end)),
+            None => (start, end),
+        });
     }
 
     /// 設置前景色
@@ -65,158 +251,1171 @@ impl Writer {
         self.bg_color = color;
     }
 
-    /// 清屏
+    /// 設置文字屬性，之後輸出的字符都使用這些屬性
+    /// 也可以在輸出中用 SGR 轉義序列設置: ESC[1m 粗體、ESC[2m 暗淡、ESC[4m 下劃線、ESC[9m 刪除線、ESC[0m 重置
+    pub fn set_attributes(&mut self, bold: bool, dim: bool, underline: bool) {
+        self.attributes = Attributes { bold, dim, underline, ..self.attributes };
+    }
+
+    /// 開關刪除線
+    pub fn set_strikethrough(&mut self, strikethrough: bool) {
+        self.attributes.strikethrough = strikethrough;
+    }
+
+    /// 當前的文字屬性
+    pub fn attributes(&self) -> Attributes {
+        self.attributes
+    }
+
+    /// 設置制表位間隔 (至少 1 列)
+    pub fn set_tab_width(&mut self, width: usize) {
+        self.tab_width = width.max(1);
+    }
+
+    /// 制表位間隔 (列)
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    /// 當前的前景色和背景色
+    pub fn current_colors(&self) -> (Color, Color) {
+        (self.fg_color, self.bg_color)
+    }
+
+    /// 臨時改用高亮前景色，背景色不變
+    /// 第一次高亮時保存用戶的顏色，連續高亮不會覆蓋保存的顏色
+    pub fn highlight(&mut self, fg: Color) {
+        if self.saved_colors.is_none() {
+            self.saved_colors = Some(self.current_colors());
+        }
+        self.fg_color = fg;
+    }
+
+    /// 恢復高亮之前的顏色，沒有高亮時不做任何事
+    pub fn restore_colors(&mut self) {
+        if let Some((fg, bg)) = self.saved_colors.take() {
+            self.fg_color = fg;
+            self.bg_color = bg;
+        }
+    }
+
+    /// 字體放大倍數
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    /// 當前字體
+    pub fn font(&self) -> &'static dyn Font {
+        self.font
+    }
+
+    /// 切換字體並按新的字符格大小重繪屏幕
+    /// 光標保持在原來的行列 (夾到新網格內)，光標所在行不會移出屏幕
+    pub fn set_font(&mut self, font: &'static dyn Font) {
+        self.hide_cursor();
+        let (row, column) = self.cursor_position();
+        let old_rows = self.text_rows();
+        self.snap_to_bottom();
+
+        self.font = font;
+        self.scale = fitting_scale(font.width(), font.height(), self.info.width, self.info.height, DEFAULT_SCALE);
+        self.char_width = font.width() * self.scale;
+        self.char_height = font.height() * self.scale;
+
+        // 行數變少時把上面的行滾出屏幕，保證光標行仍然可見
+        let rows = self.text_rows();
+        if rows > old_rows {
+            // 新露出的行可能還留有環形緩衝區中很舊的內容
+            let mut scrollback = SCROLLBACK.lock();
+            for line in self.top_line + old_rows..self.top_line + rows {
+                scrollback.clear_line(line);
+            }
+        }
+        let row = if row >= rows {
+            self.top_line += row + 1 - rows;
+            rows.saturating_sub(1)
+        } else {
+            row
+        };
+        self.set_cursor_position(row, column);
+
+        // 像素座標和滾動區域在新網格下失效，分屏模式也隨之關閉
+        self.saved_cursor = None;
+        self.input_start = None;
+        self.scroll_region = None;
+        self.split_rows = 0;
+        self.output_cursor = None;
+
+        self.force_redraw();
+        let text_bottom = rows * self.char_height;
+        self.fill_rect(0, text_bottom, self.info.width, self.info.height.saturating_sub(text_bottom), self.bg_color);
+        self.present();
+    }
+
+    pub fn cursor_shape(&self) -> CursorShape {
+        self.cursor_shape
+    }
+
+    /// 切換光標形狀，光標正在顯示時以新形狀重畫
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) {
+        let visible = self.cursor_drawn.is_some();
+        self.hide_cursor();
+        self.cursor_shape = shape;
+        if visible {
+            self.show_cursor();
+        }
+    }
+
+    /// 在光標處畫出光標；已經顯示、正在回看歷史或光標在行尾之外時不畫
+    pub fn show_cursor(&mut self) {
+        // 光標要畫在緩衝單詞之後
+        self.flush();
+        let (x, y) = (self.cursor_x, self.cursor_y);
+        if self.cursor_drawn.is_some()
+            || self.view_offset > 0
+            || x + self.char_width > self.info.width
+            || y + self.char_height > self.info.height
+        {
+            return;
+        }
+        let cell = self.cell_at(x, y);
+        match cursor_stroke(self.cursor_shape, self.char_width, self.char_height) {
+            // 塊狀光標: 交換前景色和背景色重畫字符，影子網格隨之記錄
+            None => self.draw_char_colored(cell.ch, x, y, cell.bg, cell.fg, cell.attributes),
+            Some((dx, dy, width, height)) => {
+                self.fill_rect(x + dx, y + dy, width, height, cell.fg);
+                self.invalidate_cell(x, y);
+            },
+        }
+        self.cursor_drawn = Some((x, y));
+    }
+
+    /// 擦掉光標: 按回滾緩衝區重畫光標所在的字符格
+    /// 移動光標、輸出或滾動之前必須先調用，否則光標的像素會留在屏幕上
+    pub fn hide_cursor(&mut self) {
+        if let Some((x, y)) = self.cursor_drawn.take() {
+            let cell = self.cell_at(x, y);
+            self.invalidate_cell(x, y);
+            self.draw_char_colored(cell.ch, x, y, cell.fg, cell.bg, cell.attributes);
+        }
+    }
+
+    /// 光標閃爍: 顯示與隱藏之間切換
+    pub fn toggle_cursor(&mut self) {
+        if self.cursor_drawn.is_some() {
+            self.hide_cursor();
+        } else {
+            self.show_cursor();
+        }
+    }
+
+    /// 回滾緩衝區中記錄的、畫在像素位置 (x, y) 處字符格的內容
+    /// 還沒有寫過的字符格按當前顏色的空格處理
+    fn cell_at(&self, x: usize, y: usize) -> Cell {
+        let line = self.top_line + y / self.char_height;
+        let column = x / self.char_width;
+        let blank = Cell { ch: ' ', fg: self.fg_color, bg: self.bg_color, attributes: Attributes::NONE };
+        match SCROLLBACK.lock().line(line).get(column) {
+            Some(cell) if cell.ch != '\0' => *cell,
+            _ => blank,
+        }
+    }
+
+    /// 把像素位置 (x, y) 處的字符格在影子網格中標記為未知
+    fn invalidate_cell(&self, x: usize, y: usize) {
+        let (row, column) = (y / self.char_height, x / self.char_width);
+        SHADOW.lock().invalidate(row..row + 1, column..column + 1);
+    }
+
+    /// 保存當前光標位置和顏色 (覆蓋之前保存的狀態)
+    pub fn save_cursor(&mut self) {
+        self.flush();
+        self.saved_cursor = Some(SavedCursor {
+            x: self.cursor_x,
+            y: self.cursor_y,
+            fg_color: self.fg_color,
+            bg_color: self.bg_color,
+        });
+    }
+
+    /// 恢復保存的光標位置和顏色，沒有保存過時返回 false
+    /// 保存的狀態不會被清除，可以多次恢復
+    pub fn restore_cursor(&mut self) -> bool {
+        match self.saved_cursor {
+            Some(saved) => {
+                self.flush();
+                self.hide_cursor();
+                self.cursor_x = saved.x;
+                self.cursor_y = saved.y;
+                self.fg_color = saved.fg_color;
+                self.bg_color = saved.bg_color;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 用當前背景色清屏
     pub fn clear_screen(&mut self) {
+        self.clear_to(self.bg_color);
+    }
+
+    /// 用指定顏色清屏，不改變當前背景色；光標、滾動區域和回滾緩衝區同 clear_screen 一樣重置
+    pub fn clear_to(&mut self, color: Color) {
+        // 緩衝的單詞先寫入回滾緩衝區，不會憑空消失
+        self.flush();
         let bytes_per_pixel = self.info.bytes_per_pixel;
-        let total_pixels = self.info.width * self.info.height;
+        // 每行實際佔用 stride 個像素 (可能大於 width)
+        let total_pixels = self.info.stride * self.info.height;
         let expected_size = total_pixels * bytes_per_pixel;
 
         if self.buffer.len() >= expected_size {
             for i in (0..expected_size).step_by(bytes_per_pixel) {
                 if i + bytes_per_pixel <= self.buffer.len() {
-                    self.write_pixel_at_offset(i, self.bg_color);
+                    self.write_pixel_at_offset(i, color);
                 }
             }
         }
         
-        // 重置光標
+        self.mark_dirty(0, self.info.height);
+        SHADOW.lock().invalidate_all();
+        self.cursor_drawn = None;
+
+        // 重置光標和滾動區域
         self.cursor_x = 0;
         self.cursor_y = 0;
+        self.input_start = None;
+        self.reset_scroll_region();
+
+        // 回滾緩衝區與屏幕保持一致
+        SCROLLBACK.lock().reset();
+        self.top_line = 0;
+        self.view_offset = 0;
+
+        // 分屏模式下重新畫出分隔線，焦點回到輸出區
+        if self.split_rows > 0 {
+            self.output_cursor = None;
+            self.apply_split_layout();
+        }
+    }
+
+    /// 屏幕可容納的文字行數
+    pub fn text_rows(&self) -> usize {
+        self.info.height / self.char_height
+    }
+
+    /// 每行可容納的字符數
+    pub fn text_columns(&self) -> usize {
+        self.info.width / self.char_width
+    }
+
+    /// 當前光標所在的字符格 (行, 列)
+    pub fn cursor_position(&self) -> (usize, usize) {
+        (self.cursor_y / self.char_height, self.cursor_x / self.char_width)
+    }
+
+    /// 把光標移動到字符格 (行, 列)，超出文字區域時夾到最近的格子
+    pub fn set_cursor_position(&mut self, row: usize, column: usize) {
+        self.flush();
+        self.hide_cursor();
+        let (row, column) = clamp_cell(row, column, self.text_rows(), self.text_columns());
+        self.cursor_x = column * self.char_width;
+        self.cursor_y = row * self.char_height;
+    }
+
+    /// 設置滾動區域 [top_row, bottom_row]：換行只滾動區域內的行，區域外的文字保持不動
+    /// 超出屏幕的行號會被夾到屏幕內
+    pub fn set_scroll_region(&mut self, top_row: usize, bottom_row: usize) {
+        let last_row = self.text_rows().saturating_sub(1);
+        let bottom = bottom_row.min(last_row);
+        let top = top_row.min(bottom);
+        self.scroll_region = if top == 0 && bottom == last_row { None } else { Some((top, bottom)) };
+    }
+
+    /// 恢復整個屏幕滾動
+    pub fn reset_scroll_region(&mut self) {
+        self.scroll_region = None;
+    }
+
+    /// 當前生效的滾動區域 (首行, 末行)
+    pub fn scroll_region(&self) -> (usize, usize) {
+        self.scroll_region
+            .unwrap_or((0, self.text_rows().saturating_sub(1)))
+    }
+
+    /// 開啟分屏模式: 屏幕底部保留 `input_rows` 行作為輸入區，上面一行是分隔線，
+    /// 其餘的行是輸出區，輸出只在輸出區內滾動
+    /// 輸入區至少 1 行、最多 MAX_INPUT_ROWS 行；屏幕放不下時返回 false
+    /// 注意: 區域滾動不把滾出的行留在回滾緩衝區，分屏時 Page Up 只能看到當前屏幕
+    pub fn enable_split(&mut self, input_rows: usize) -> bool {
+        let input_rows = input_rows.clamp(1, MAX_INPUT_ROWS);
+        // 輸入區 + 分隔線之外至少留一行輸出
+        if input_rows + 2 > self.text_rows() {
+            return false;
+        }
+        // 已經分屏時先按原來的行數撤銷布局
+        self.disable_split();
+        self.snap_to_bottom();
+        self.split_rows = input_rows;
+
+        // 光標落在保留區域內時，把整屏向上滾動騰出位置
+        let (_, output_bottom) = self.output_area();
+        self.reset_scroll_region();
+        let cursor_row = self.cursor_y / self.char_height;
+        if cursor_row > output_bottom {
+            self.hide_cursor();
+            self.scroll_up_n(cursor_row - output_bottom);
+        }
+        let (first_reserved, last_row) = (output_bottom + 1, self.text_rows() - 1);
+        self.clear_rows(first_reserved, last_row);
+        self.apply_split_layout();
+        self.present();
+        true
+    }
+
+    /// 關閉分屏模式，擦掉分隔線和輸入區，恢復整屏滾動
+    pub fn disable_split(&mut self) {
+        if self.split_rows == 0 {
+            return;
+        }
+        self.snap_to_bottom();
+        self.focus_output();
+        let (_, output_bottom) = self.output_area();
+        let last_row = self.text_rows() - 1;
+        self.split_rows = 0;
+        self.reset_scroll_region();
+        self.input_start = None;
+        self.clear_rows(output_bottom + 1, last_row);
+        self.present();
+    }
+
+    /// 分屏時輸入區的行數，未分屏時為 None
+    pub fn split_rows(&self) -> Option<usize> {
+        (self.split_rows > 0).then_some(self.split_rows)
+    }
+
+    /// 把光標移到 (清空後的) 輸入區開頭，之後的輸出 (提示符和回顯) 寫入輸入區
+    /// 輸出區的光標被保存，focus_output 時恢復；未分屏時不做任何事
+    pub fn focus_input(&mut self) {
+        if self.split_rows == 0 {
+            return;
+        }
+        self.hide_cursor();
+        if self.output_cursor.is_none() {
+            self.output_cursor = Some((self.cursor_x, self.cursor_y));
+        }
+        let (top, bottom) = self.input_area();
+        self.clear_rows(top, bottom);
+        self.set_scroll_region(top, bottom);
+        self.cursor_x = 0;
+        self.cursor_y = top * self.char_height;
+    }
+
+    /// 把光標移回輸出區中上次的位置，返回是否處於分屏模式
+    pub fn focus_output(&mut self) -> bool {
+        if self.split_rows == 0 {
+            return false;
+        }
+        self.hide_cursor();
+        if let Some((x, y)) = self.output_cursor.take() {
+            self.cursor_x = x;
+            self.cursor_y = y;
+        }
+        let (top, bottom) = self.output_area();
+        self.set_scroll_region(top, bottom);
+        true
+    }
+
+    /// 輸出區的 (首行, 末行)
+    fn output_area(&self) -> (usize, usize) {
+        (0, self.text_rows().saturating_sub(self.split_rows + 2))
+    }
+
+    /// 輸入區的 (首行, 末行)
+    fn input_area(&self) -> (usize, usize) {
+        let rows = self.text_rows();
+        (rows.saturating_sub(self.split_rows), rows.saturating_sub(1))
+    }
+
+    /// 按當前焦點設置滾動區域並畫出分隔線
+    fn apply_split_layout(&mut self) {
+        let (top, bottom) = if self.output_cursor.is_some() {
+            self.input_area()
+        } else {
+            self.output_area()
+        };
+        self.set_scroll_region(top, bottom);
+        let separator_row = self.output_area().1 + 1;
+        let separator = [SPLIT_SEPARATOR as u8; MAX_COLUMNS];
+        let columns = self.text_columns().min(MAX_COLUMNS);
+        // 分隔線只由 ASCII 組成
+        let separator = core::str::from_utf8(&separator[..columns]).unwrap_or("");
+        let fg = self.fg_color;
+        self.fg_color = dim_color(fg, self.bg_color);
+        self.write_at(separator_row, 0, separator);
+        self.fg_color = fg;
+    }
+
+    /// 清空 [first_row, last_row] 這些行 (屏幕和回滾緩衝區)
+    fn clear_rows(&mut self, first_row: usize, last_row: usize) {
+        if first_row > last_row {
+            return;
+        }
+        let mut scrollback = SCROLLBACK.lock();
+        for row in first_row..=last_row {
+            scrollback.clear_line(self.top_line + row);
+        }
+        drop(scrollback);
+        let y = first_row * self.char_height;
+        let height = (last_row + 1 - first_row) * self.char_height;
+        self.fill_rect(0, y, self.info.width, height, self.bg_color);
+    }
+
+    /// 在字符格 (行, 列) 處原地輸出文字，不移動光標、不換行也不滾動
+    /// 超出行尾的部分被截斷
+    pub fn write_at(&mut self, row: usize, column: usize, text: &str) {
+        self.snap_to_bottom();
+        if row >= self.text_rows() {
+            return;
+        }
+
+        let (saved_x, saved_y) = (self.cursor_x, self.cursor_y);
+        self.cursor_x = column * self.char_width;
+        self.cursor_y = row * self.char_height;
+        for ch in text.chars() {
+            if self.cursor_x + self.char_width > self.info.width {
+                break;
+            }
+            self.record_cell(ch);
+            self.draw_char(ch, self.cursor_x, self.cursor_y);
+            self.cursor_x += self.char_width;
+        }
+        self.cursor_x = saved_x;
+        self.cursor_y = saved_y;
+    }
+
+    /// 在字符格 (row, column) 處用製表符畫一個 width x height 的方框並清空內部
+    /// 有標題時顯示在上邊框中，過長的標題被截斷
+    /// 只畫像素、不寫入回滾緩衝區，方框關閉後用 force_redraw 恢復底下的內容
+    /// 小於 2x2 或超出屏幕時不畫，返回 None
+    pub fn draw_box(
+        &mut self,
+        row: usize,
+        column: usize,
+        width: usize,
+        height: usize,
+        title: Option<&str>,
+    ) -> Option<TextBox> {
+        if width < 2 || height < 2 || row + height > self.text_rows() || column + width > self.text_columns() {
+            return None;
+        }
+        self.snap_to_bottom();
+        for dy in 0..height {
+            for dx in 0..width {
+                let ch = box_border_char(dx, dy, width, height);
+                self.draw_overlay_char(row + dy, column + dx, ch);
+            }
+        }
+        let text_box = TextBox { row, column, width, height };
+        if let Some(title) = title {
+            // "┌─ 標題 ─┐"：標題前後各留一個空格
+            let room = width.saturating_sub(4);
+            let title = title.chars().take(room.saturating_sub(2));
+            for (i, ch) in core::iter::once(' ').chain(title).chain(core::iter::once(' ')).enumerate() {
+                if i < room {
+                    self.draw_overlay_char(row, column + 2 + i, ch);
+                }
+            }
+        }
+        Some(text_box)
+    }
+
+    /// 在方框內第 `line` 行左對齊輸出文字，超出內部寬度的部分被截斷
+    pub fn box_text(&mut self, text_box: &TextBox, line: usize, text: &str) {
+        self.box_text_at(text_box, line, 0, text);
+    }
+
+    /// 在方框內第 `line` 行居中輸出文字
+    pub fn box_text_centered(&mut self, text_box: &TextBox, line: usize, text: &str) {
+        let len = text.chars().count();
+        let offset = text_box.inner_width().saturating_sub(len) / 2;
+        self.box_text_at(text_box, line, offset, text);
+    }
+
+    fn box_text_at(&mut self, text_box: &TextBox, line: usize, offset: usize, text: &str) {
+        if line >= text_box.inner_height() {
+            return;
+        }
+        let row = text_box.row + 1 + line;
+        let room = text_box.inner_width().saturating_sub(offset);
+        for (i, ch) in text.chars().take(room).enumerate() {
+            self.draw_overlay_char(row, text_box.column + 1 + offset + i, ch);
+        }
+    }
+
+    /// 以當前顏色在字符格處畫一個字符，不移動光標也不寫回滾緩衝區
+    fn draw_overlay_char(&mut self, row: usize, column: usize, ch: char) {
+        let (x, y) = (column * self.char_width, row * self.char_height);
+        self.draw_char_colored(ch, x, y, self.fg_color, self.bg_color, Attributes::NONE);
+    }
+
+    /// 在字符格 (行, 列) 處繪製 `[####    ]  50%` 樣式的進度條，共佔 `width` 列
+    /// percent 超過 100 時按 100 處理；寬度不足以畫出方框時只顯示百分比
+    pub fn draw_progress_bar(&mut self, row: usize, column: usize, width: usize, percent: usize) {
+        let width = width.min(self.text_columns().saturating_sub(column));
+        let mut text = [0u8; MAX_COLUMNS];
+        let len = format_progress_bar(width, percent, &mut text);
+        // format_progress_bar 只輸出 ASCII
+        let text = core::str::from_utf8(&text[..len]).unwrap_or("");
+        self.write_at(row, column, text);
+        self.present();
+    }
+
+    /// 光標所在行的邏輯行號 (清屏後從 0 開始)
+    pub fn current_line(&self) -> usize {
+        self.top_line + self.cursor_y / self.char_height
+    }
+
+    /// 回滾緩衝區中仍然保存著的最舊邏輯行號
+    pub fn oldest_stored_line(&self) -> usize {
+        (self.top_line + self.text_rows()).saturating_sub(SCROLLBACK_LINES)
+    }
+
+    /// 把回滾緩衝區中一行的字符複製到 out，返回字符數
+    /// 該行已被覆蓋或還沒有輸出時返回 None
+    pub fn copy_stored_line(&self, line: usize, out: &mut [char; MAX_COLUMNS]) -> Option<usize> {
+        if line < self.oldest_stored_line() || line > self.current_line() {
+            return None;
+        }
+        let scrollback = SCROLLBACK.lock();
+        let cells = scrollback.line(line);
+        for (slot, cell) in out.iter_mut().zip(cells) {
+            *slot = cell.ch;
+        }
+        Some(cells.len())
+    }
+
+    /// 向上回滾查看歷史，返回實際滾動的行數
+    pub fn scroll_view_up(&mut self, lines: usize) -> usize {
+        let max_offset = self.top_line - self.oldest_stored_line();
+        let new_offset = (self.view_offset + lines).min(max_offset);
+        let scrolled = new_offset - self.view_offset;
+        if scrolled > 0 {
+            self.view_offset = new_offset;
+            self.redraw_view();
+        }
+        scrolled
+    }
+
+    /// 向下回滾，返回實際滾動的行數
+    pub fn scroll_view_down(&mut self, lines: usize) -> usize {
+        let scrolled = lines.min(self.view_offset);
+        if scrolled > 0 {
+            self.view_offset -= scrolled;
+            self.redraw_view();
+        }
+        scrolled
+    }
+
+    /// 一頁回滾的行數 (半屏)
+    pub fn page_lines(&self) -> usize {
+        (self.text_rows() / 2).max(1)
+    }
+
+    /// 回到底部的實時畫面
+    fn snap_to_bottom(&mut self) {
+        if self.view_offset > 0 {
+            self.view_offset = 0;
+            self.redraw_view();
+        }
+    }
+
+    /// 根據回滾緩衝區重繪整個屏幕的文字
+    fn redraw_view(&mut self) {
+        let start = crate::cpu::rdtsc();
+        // 整屏重畫會蓋掉光標
+        self.cursor_drawn = None;
+        let first_line = self.top_line - self.view_offset;
+        let width = self.info.width;
+
+        for row in 0..self.text_rows() {
+            let y = row * self.char_height;
+            let mut x = 0;
+            // 先複製出一行，避免在繪製時持有回滾緩衝區的鎖
+            let mut cells = [Cell { ch: ' ', fg: self.fg_color, bg: self.bg_color, attributes: Attributes::NONE }; MAX_COLUMNS];
+            let len = {
+                let scrollback = SCROLLBACK.lock();
+                let line = scrollback.line(first_line + row);
+                cells[..line.len()].copy_from_slice(line);
+                line.len()
+            };
+            for cell in &cells[..len] {
+                if x + self.char_width > width {
+                    break;
+                }
+                self.draw_char_colored(cell.ch, x, y, cell.fg, cell.bg, cell.attributes);
+                x += self.char_width;
+            }
+            // 行尾剩餘部分用背景色填充
+            self.fill_rect(x, y, width - x.min(width), self.char_height, self.bg_color);
+        }
+        self.finish_frame("redraw", start);
+    }
+
+    /// 丟棄影子網格中記錄的內容，按回滾緩衝區重繪每一個字符格
+    pub fn force_redraw(&mut self) {
+        SHADOW.lock().invalidate_all();
+        self.redraw_view();
+        self.present();
+    }
+
+    /// 開關右上角的渲染耗時顯示，關閉時重繪屏幕擦掉它
+    pub fn set_perf_overlay(&mut self, enabled: bool) {
+        self.perf_overlay = enabled;
+        if enabled {
+            self.draw_perf_overlay();
+        } else {
+            self.redraw_view();
+        }
+        self.present();
+    }
+
+    pub fn perf_overlay(&self) -> bool {
+        self.perf_overlay
+    }
+
+    /// 上一次整屏滾動或重繪的類型和耗時 (納秒，TSC 未校準時為 None)
+    pub fn last_frame(&self) -> Option<(&'static str, Option<u64>)> {
+        self.last_frame.map(|(kind, cycles)| (kind, crate::time::rdtsc_to_ns(cycles)))
+    }
+
+    /// 記錄一幀的耗時，之後再畫耗時顯示，顯示本身不計入耗時
+    fn finish_frame(&mut self, kind: &'static str, start: u64) {
+        self.last_frame = Some((kind, crate::cpu::rdtsc().wrapping_sub(start)));
+        if self.perf_overlay {
+            self.draw_perf_overlay();
+        }
+    }
+
+    /// 在第一行右端畫出上一幀的耗時 (只畫像素，不寫入回滾緩衝區)
+    fn draw_perf_overlay(&mut self) {
+        let mut text = LineBuffer::<PERF_OVERLAY_COLUMNS>::new();
+        match self.last_frame() {
+            Some((kind, ns)) => {
+                let _ = format::write_frame_time(&mut text, kind, ns);
+            },
+            None => {
+                let _ = fmt::Write::write_str(&mut text, "no frames yet");
+            },
+        }
+        // 固定寬度右對齊，較短的新值能完全蓋住舊值
+        let x = self.info.width.saturating_sub(PERF_OVERLAY_COLUMNS * self.char_width);
+        let padding = PERF_OVERLAY_COLUMNS - text.as_str().chars().count();
+        let chars = core::iter::repeat_n(' ', padding).chain(text.as_str().chars());
+        for (i, ch) in chars.enumerate() {
+            self.draw_char_colored(ch, x + i * self.char_width, 0, Color::YELLOW, self.bg_color, Attributes::NONE);
+        }
+    }
+
+    /// 記錄光標處的字符到回滾緩衝區
+    fn record_cell(&self, ch: char) {
+        self.store_cell(self.current_cell(ch));
+    }
+
+    /// 以當前顏色和屬性表示的字符格
+    fn current_cell(&self, ch: char) -> Cell {
+        Cell { ch, fg: self.fg_color, bg: self.bg_color, attributes: self.attributes }
+    }
+
+    /// 把字符格寫入回滾緩衝區的光標處
+    fn store_cell(&self, cell: Cell) {
+        let line = self.current_line();
+        let column = self.cursor_x / self.char_width;
+        SCROLLBACK.lock().set(line, column, cell);
+    }
+
+    /// 把當前光標位置標記為輸入起點 (在提示符輸出之後調用)
+    pub fn mark_input_start(&mut self) {
+        self.input_start = Some((self.cursor_x, self.cursor_y));
+    }
+
+    /// 光標是否已經位於輸入起點 (或之前)
+    fn at_input_start(&self) -> bool {
+        match self.input_start {
+            Some((x, y)) => self.cursor_y < y || (self.cursor_y == y && self.cursor_x <= x),
+            None => false,
+        }
     }
 
     /// 換行
     pub fn newline(&mut self) {
+        self.flush();
         self.cursor_x = 0;
-        self.cursor_y += self.char_height;
-        
-        // 如果超出屏幕底部，滾動屏幕
-        if self.cursor_y + self.char_height > self.info.height {
-            self.scroll_up();
+        let row = self.cursor_y / self.char_height;
+        let (_, bottom) = self.scroll_region();
+
+        if row == bottom {
+            // 在滾動區域最後一行換行，滾動區域後光標停在新的最後一行
+            self.scroll_up_n(1);
+            self.cursor_y = bottom * self.char_height;
+        } else if row + 1 < self.text_rows() {
+            self.cursor_y += self.char_height;
         }
+        // 否則在滾動區域下方的最後一行，光標停在原行
     }
 
     /// 退格功能 - 刪除前一個字符
+    /// 在行首時回到上一行的最後一列；不會越過輸入起點 (提示符)
     pub fn backspace(&mut self) {
+        self.flush();
+        if self.at_input_start() {
+            return;
+        }
+        self.hide_cursor();
+
         if self.cursor_x >= self.char_width {
             // 移動光標到前一個字符位置
             self.cursor_x -= self.char_width;
-            
-            // 在該位置繪製背景色的矩形（擦除字符）
-            self.draw_filled_rect(
-                self.cursor_x, 
-                self.cursor_y, 
-                self.char_width, 
-                self.char_height, 
-                self.bg_color
-            );
         } else if self.cursor_y >= self.char_height {
-            // 如果在行首，移動到上一行末尾
+            // 在行首 (輸入已換行)，移動到上一行的最後一列
+            // write_char 在放不下下一個字符時才換行，所以上一行最後一個字符就在這一列
             self.cursor_y -= self.char_height;
-            
-            // 找到上一行的末尾位置（簡化版本：移動到行末）
-            let chars_per_line = self.info.width / self.char_width;
-            self.cursor_x = (chars_per_line - 1) * self.char_width;
-            
-            // 從該位置向左尋找最後一個非空白字符（簡化實現）
-            self.draw_filled_rect(
-                self.cursor_x, 
-                self.cursor_y, 
-                self.char_width, 
-                self.char_height, 
-                self.bg_color
-            );
-        }
-        // 如果已經在屏幕左上角 (0,0)，則不執行任何操作
+            self.cursor_x = self.text_columns().saturating_sub(1) * self.char_width;
+        } else {
+            // 已經在屏幕左上角 (0,0)，不執行任何操作
+            return;
+        }
+
+        let line = self.current_line();
+        SCROLLBACK.lock().erase(line, self.cursor_x / self.char_width, self.bg_color);
+
+        // 在該位置繪製背景色的矩形（擦除字符）
+        self.fill_rect(
+            self.cursor_x,
+            self.cursor_y,
+            self.char_width,
+            self.char_height,
+            self.bg_color
+        );
+    }
+
+    // === 圖形繪製 ===
+    // 以下函數使用像素座標，與文字光標無關，超出屏幕的部分會被裁剪
+
+    /// 屏幕寬度 (像素)
+    pub fn width(&self) -> usize {
+        self.info.width
+    }
+
+    /// 屏幕高度 (像素)
+    pub fn height(&self) -> usize {
+        self.info.height
+    }
+
+    /// 繪製單個像素
+    pub fn draw_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.info.width && y < self.info.height {
+            self.invalidate_pixels(x, y, x + 1, y + 1);
+            self.write_pixel(x, y, color);
+        }
     }
 
     /// 繪製填充矩形
-    fn draw_filled_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
-        for dy in 0..height {
-            for dx in 0..width {
-                let pixel_x = x + dx;
-                let pixel_y = y + dy;
-                
-                if pixel_x < self.info.width && pixel_y < self.info.height {
-                    self.write_pixel(pixel_x, pixel_y, color);
-                }
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+        let x_end = x.saturating_add(width).min(self.info.width);
+        let y_end = y.saturating_add(height).min(self.info.height);
+        if x >= x_end || y >= y_end {
+            return;
+        }
+        self.invalidate_pixels(x, y, x_end, y_end);
+        for py in y..y_end {
+            for px in x..x_end {
+                self.write_pixel(px, py, color);
             }
         }
     }
 
-    /// 向上滾動一行
-    fn scroll_up(&mut self) {
-        let bytes_per_pixel = self.info.bytes_per_pixel;
-        let line_bytes = self.info.width * bytes_per_pixel;
-        let _scroll_bytes = line_bytes * self.char_height;
-
-        // 將所有行向上移動
-        for y in 0..(self.info.height - self.char_height) {
-            let src_start = (y + self.char_height) * line_bytes;
-            let dst_start = y * line_bytes;
-            
-            for x in 0..line_bytes {
-                if src_start + x < self.buffer.len() && dst_start + x < self.buffer.len() {
-                    self.buffer[dst_start + x] = self.buffer[src_start + x];
-                }
+    /// 把 `color` 以不透明度 `alpha` (0 完全透明，255 不透明) 疊加到像素上
+    /// 不透明時直接寫入；否則讀回已有的像素混合後寫回
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: Color, alpha: u8) {
+        match alpha {
+            0 => {},
+            255 => self.draw_pixel(x, y, color),
+            _ if x < self.info.width && y < self.info.height => {
+                self.invalidate_pixels(x, y, x + 1, y + 1);
+                self.blend_pixel_unchecked(x, y, color, alpha);
+            },
+            _ => {},
+        }
+    }
+
+    /// 半透明地填充矩形，例如在彈出框後面把屏幕調暗
+    pub fn blend_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color, alpha: u8) {
+        if alpha == 255 {
+            return self.fill_rect(x, y, width, height, color);
+        }
+        let x_end = x.saturating_add(width).min(self.info.width);
+        let y_end = y.saturating_add(height).min(self.info.height);
+        if alpha == 0 || x >= x_end || y >= y_end {
+            return;
+        }
+        self.invalidate_pixels(x, y, x_end, y_end);
+        for py in y..y_end {
+            for px in x..x_end {
+                self.blend_pixel_unchecked(px, py, color, alpha);
             }
         }
+    }
+
+    /// 把整個屏幕向黑色調暗，`alpha` 越大越暗
+    /// 屏幕文字可以用 force_redraw 恢復
+    pub fn dim_screen(&mut self, alpha: u8) {
+        self.blend_rect(0, 0, self.info.width, self.info.height, Color::BLACK, alpha);
+    }
+
+    /// 混合單個像素 (調用者已檢查座標並更新影子網格)
+    fn blend_pixel_unchecked(&mut self, x: usize, y: usize, color: Color, alpha: u8) {
+        let offset = (y * self.info.stride + x) * self.info.bytes_per_pixel;
+        if let Some(old) = self.read_pixel_at_offset(offset) {
+            self.write_pixel(x, y, blend(old, color, alpha));
+        }
+    }
+
+    /// 像素區域 [x0, x1) x [y0, y1) 被字符以外的內容覆蓋，把涉及的字符格標記為未知
+    fn invalidate_pixels(&self, x0: usize, y0: usize, x1: usize, y1: usize) {
+        SHADOW.lock().invalidate(
+            y0 / self.char_height..y1.div_ceil(self.char_height),
+            x0 / self.char_width..x1.div_ceil(self.char_width),
+        );
+    }
+
+    /// 繪製矩形邊框
+    pub fn draw_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let right = x.saturating_add(width - 1);
+        let bottom = y.saturating_add(height - 1);
+        self.draw_line(x, y, right, y, color);
+        self.draw_line(x, bottom, right, bottom, color);
+        self.draw_line(x, y, x, bottom, color);
+        self.draw_line(right, y, right, bottom, color);
+    }
 
-        // 清空最後幾行
-        let clear_start = (self.info.height - self.char_height) * line_bytes;
-        for i in (clear_start..self.buffer.len()).step_by(bytes_per_pixel) {
-            if i + bytes_per_pixel <= self.buffer.len() {
-                self.write_pixel_at_offset(i, self.bg_color);
+    /// 繪製直線 (Bresenham 算法)
+    pub fn draw_line(&mut self, x0: usize, y0: usize, x1: usize, y1: usize, color: Color) {
+        let (mut x, mut y) = (x0 as isize, y0 as isize);
+        let (x1, y1) = (x1 as isize, y1 as isize);
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let step_x = if x < x1 { 1 } else { -1 };
+        let step_y = if y < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+
+        loop {
+            self.draw_pixel(x as usize, y as usize, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
             }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// 滾動區域向上滾動 rows 行 (最多滾動整個區域)，光標隨內容一起上移
+    /// 一次複製整塊像素，比逐行滾動少做很多次複製
+    pub fn scroll_up_n(&mut self, rows: usize) {
+        let (top, bottom) = self.scroll_region();
+        let rows = rows.min(bottom + 1 - top);
+        if rows == 0 {
+            return;
+        }
+        let start = crate::cpu::rdtsc();
+        let line_bytes = self.info.stride * self.info.bytes_per_pixel;
+        // 按 char_height 網格計算，保持文字行對齊
+        let top_y = top * self.char_height;
+        let bottom_y = bottom * self.char_height;
+        let shift = rows * self.char_height;
+
+        // 將區域內剩下的行向上移動 rows 行
+        let src = (top_y + shift) * line_bytes..(bottom_y + self.char_height) * line_bytes;
+        if !src.is_empty() && src.end <= self.buffer.len() {
+            self.buffer.copy_within(src, top_y * line_bytes);
         }
+        self.mark_dirty(top_y, bottom_y + self.char_height);
+
+        SHADOW.lock().scroll_up(top, bottom, rows);
 
-        // 調整光標位置
-        self.cursor_y = self.info.height - self.char_height;
+        // 清空區域最後 rows 行
+        let first_cleared = bottom + 1 - rows;
+        self.fill_rect(0, first_cleared * self.char_height, self.info.width, shift, self.bg_color);
+
+        // 光標在區域內時跟著上移，滾出區域時停在區域第一行
+        if (top_y..=bottom_y).contains(&self.cursor_y) {
+            self.cursor_y = self.cursor_y.saturating_sub(shift).max(top_y);
+        }
+
+        if self.scroll_region.is_none() {
+            // 整屏滾動: 新的行進入屏幕，舊行留在回滾緩衝區
+            self.top_line += rows;
+            let mut scrollback = SCROLLBACK.lock();
+            for row in first_cleared..=bottom {
+                scrollback.clear_line(self.top_line + row);
+            }
+        } else {
+            // 區域滾動: 只在屏幕範圍內移動回滾緩衝區的行
+            let mut scrollback = SCROLLBACK.lock();
+            for row in top..first_cleared {
+                scrollback.copy_line(self.top_line + row + rows, self.top_line + row);
+            }
+            for row in first_cleared..=bottom {
+                scrollback.clear_line(self.top_line + row);
+            }
+        }
+
+        // 輸入起點在區域內時跟著上移，滾出區域時固定在區域左上角
+        self.input_start = self.input_start.map(|(x, y)| {
+            if y < top_y || y > bottom_y {
+                (x, y)
+            } else if y >= top_y + shift {
+                (x, y - shift)
+            } else {
+                (0, top_y)
+            }
+        });
+        self.finish_frame("scroll", start);
     }
 
-    /// 寫入單個字符
+    /// 光標在滾動區域最後一行、而即將輸出的文字含有多個換行時，先一次滾動出這些行
+    /// 之後的換行只移動光標，不再逐行滾動；只有一個換行時照常逐行滾動
+    fn prescroll(&mut self, text: &str) {
+        let (top, bottom) = self.scroll_region();
+        if self.text_rows() == 0 || self.cursor_y / self.char_height != bottom {
+            return;
+        }
+        // 含轉義序列的文字可能移動光標，無法預知實際要滾動的行數
+        if text.contains('\x1b') {
+            return;
+        }
+        let pending = text.bytes().filter(|&byte| byte == b'\n').count();
+        if pending < SCROLL_BATCH_MIN {
+            return;
+        }
+        self.snap_to_bottom();
+        self.hide_cursor();
+        self.scroll_up_n(pending.min(bottom - top));
+    }
+
+    /// 寫入單個字符 (先經過 ANSI 轉義序列解析)
     pub fn write_char(&mut self, ch: char) {
+        // 任何新輸出都回到底部的實時畫面
+        self.snap_to_bottom();
+        self.hide_cursor();
+
+        match self.ansi.feed(ch) {
+            Action::Print(ch) => self.put_char(ch),
+            Action::Csi { params, count, final_char: 'm' } => {
+                ansi::apply_sgr(&mut self.attributes, &params[..count]);
+            },
+            Action::Csi { params, count, final_char } => {
+                // 不支持的 CSI 序列直接丟棄
+                if let Some(command) = ansi::cursor_command(&params[..count], final_char) {
+                    self.apply_cursor_command(command);
+                }
+            },
+            Action::Consumed => {},
+        }
+    }
+
+    /// 執行光標控制序列，位置夾到文字網格內
+    /// 清屏 (ESC [ 2 J) 與 clear_screen 相同，光標回到左上角
+    fn apply_cursor_command(&mut self, command: CursorCommand) {
+        let (row, column) = self.cursor_position();
+        match command {
+            CursorCommand::Position { row, column } => self.set_cursor_position(row, column),
+            CursorCommand::Up(n) => self.set_cursor_position(row.saturating_sub(n), column),
+            CursorCommand::Down(n) => self.set_cursor_position(row.saturating_add(n), column),
+            CursorCommand::Forward(n) => self.set_cursor_position(row, column.saturating_add(n)),
+            CursorCommand::Back(n) => self.set_cursor_position(row, column.saturating_sub(n)),
+            CursorCommand::ClearScreen => self.clear_screen(),
+        }
+    }
+
+    /// 輸出一個已經過轉義序列解析的字符
+    fn put_char(&mut self, ch: char) {
+        // 屏幕連一個字符格都放不下時沒有文字網格，不輸出也不移動光標
+        if self.text_rows() == 0 || self.text_columns() == 0 {
+            return;
+        }
+        if self.word_wrap && is_word_char(ch) {
+            self.push_word_char(ch);
+            return;
+        }
+        self.flush();
         match ch {
             '\n' => self.newline(),
             '\r' => self.cursor_x = 0,
-            '\t' => { // Tab 鍵處理 - 4個空格
-                for _ in 0..4 {
-                    if self.cursor_x + self.char_width <= self.info.width {
+            '\x07' => self.bell_pending = true,
+            '\t' => { // 前進到下一個制表位，用背景色填充
+                let column = self.cursor_x / self.char_width;
+                let next_stop = next_tab_stop(column, self.tab_width);
+                if next_stop > self.text_columns() {
+                    // 放不下時換到下一行的開頭 (第 0 列本身就是制表位)
+                    self.newline();
+                } else {
+                    for _ in column..next_stop {
+                        self.record_cell(' ');
                         self.draw_char(' ', self.cursor_x, self.cursor_y);
                         self.cursor_x += self.char_width;
-                    } else {
-                        self.newline();
-                        break;
                     }
                 }
             },
-            ch => {
-                // 檢查是否需要換行
-                if self.cursor_x + self.char_width > self.info.width {
-                    self.newline();
-                }
+            ch => self.print_cell(self.current_cell(ch)),
+        }
+    }
 
-                // 繪製字符
-                self.draw_char(ch, self.cursor_x, self.cursor_y);
-                
-                // 移動光標
-                self.cursor_x += self.char_width;
+    /// 在光標處輸出一個字符格，放不下時先換行
+    fn print_cell(&mut self, cell: Cell) {
+        // 檢查是否需要換行
+        if self.cursor_x + self.char_width > self.info.width {
+            self.newline();
+        }
+
+        // 繪製字符
+        self.store_cell(cell);
+        self.draw_char_colored(cell.ch, self.cursor_x, self.cursor_y, cell.fg, cell.bg, cell.attributes);
+
+        // 移動光標
+        self.cursor_x += self.char_width;
+    }
+
+    /// 自動換行模式: 把單詞中的字符先放進緩衝區，緩衝區滿時先輸出已有部分
+    fn push_word_char(&mut self, ch: char) {
+        if self.pending_len == WRAP_WORD_MAX {
+            self.flush();
+        }
+        self.pending_word[self.pending_len] = self.current_cell(ch);
+        self.pending_len += 1;
+    }
+
+    /// 等待按鍵之前調用: 輸出緩衝的單詞並送到屏幕，用戶能看到完整的提示
+    pub fn prepare_for_input(&mut self) {
+        self.flush();
+        self.present();
+    }
+
+    /// 輸出自動換行模式下緩衝的單詞: 本行剩下的位置放不下時先換行
+    /// 換行、清屏、移動光標和顯示光標之前都會調用；命令結束時由主循環顯示光標觸發，
+    /// 命令中途等待按鍵時由 prepare_for_input 觸發
+    pub fn flush(&mut self) {
+        // 先清空計數，輸出過程中的換行再調用 flush 時不會重複輸出
+        let len = core::mem::take(&mut self.pending_len);
+        if len == 0 {
+            return;
+        }
+        if wraps_before_word(self.cursor_x / self.char_width, len, self.text_columns()) {
+            self.newline();
+        }
+        for index in 0..len {
+            self.print_cell(self.pending_word[index]);
+        }
+    }
+
+    /// 開關自動換行，關閉時輸出緩衝的單詞
+    pub fn set_word_wrap(&mut self, enabled: bool) {
+        if !enabled {
+            self.flush();
+        }
+        self.word_wrap = enabled;
+    }
+
+    pub fn word_wrap(&self) -> bool {
+        self.word_wrap
+    }
+
+    /// 取出並清除響鈴請求 (輸出 BEL 時設置)
+    /// 閃爍需要等待，由調用者在釋放 WRITER 鎖之後完成，見 crate::flash_bell
+    pub fn take_bell(&mut self) -> bool {
+        core::mem::take(&mut self.bell_pending)
+    }
+
+    /// 反轉屏幕上每個像素的顏色 (不改動 Alpha 字節和後備緩衝區)
+    /// 反轉是按位取反，做兩次正好恢復原來的像素
+    pub fn invert_screen(&mut self) {
+        let bytes_per_pixel = self.info.bytes_per_pixel;
+        let size = self.info.stride * self.info.height * bytes_per_pixel;
+        let color_bytes = bytes_per_pixel.min(3);
+        let screen = match self.front.as_mut() {
+            Some(front) => front,
+            None => &mut *self.buffer,
+        };
+        let size = size.min(screen.len());
+        for pixel in screen[..size].chunks_exact_mut(bytes_per_pixel) {
+            for byte in &mut pixel[..color_bytes] {
+                *byte = !*byte;
             }
         }
     }
 
     /// 寫入字符串
     pub fn write_string(&mut self, s: &str) {
+        self.prescroll(s);
         for ch in s.chars() {
             self.write_char(ch);
         }
     }
 
-    /// 在指定位置繪製字符
+    /// 在指定位置以當前顏色繪製字符
     fn draw_char(&mut self, ch: char, start_x: usize, start_y: usize) {
-        let char_bitmap = Font8x8::get_char(ch);
+        self.draw_char_colored(ch, start_x, start_y, self.fg_color, self.bg_color, self.attributes);
+    }
+
+    /// 在指定位置以指定顏色和屬性繪製字符
+    /// 對齊字符網格時先查影子網格，內容與上次相同就不重繪
+    fn draw_char_colored(
+        &mut self,
+        ch: char,
+        start_x: usize,
+        start_y: usize,
+        fg: Color,
+        bg: Color,
+        attributes: Attributes,
+    ) {
+        if start_x.is_multiple_of(self.char_width) && start_y.is_multiple_of(self.char_height) {
+            let (row, column) = (start_y / self.char_height, start_x / self.char_width);
+            if !SHADOW.lock().update(row, column, Cell { ch, fg, bg, attributes }) {
+                return;
+            }
+        } else {
+            // 不對齊時跨越多個字符格，這些格的內容都變為未知
+            self.invalidate_pixels(start_x, start_y, start_x + self.char_width, start_y + self.char_height);
+        }
+
+        let char_bitmap = self.font.glyph(ch);
+        let fg = if attributes.dim { dim_color(fg, bg) } else { fg };
+        let last_row = char_bitmap.len().saturating_sub(1);
+        let middle_row = char_bitmap.len() / 2;
         
+        // 每個像素都重新畫成前景色或背景色，關閉屬性後重繪時線條自然被擦掉
         for (row, &bitmap_row) in char_bitmap.iter().enumerate() {
-            for col in 0..8 {
+            let line = (attributes.underline && row == last_row)
+                || (attributes.strikethrough && row == middle_row);
+            let bitmap_row = if line {
+                0xFF
+            } else if attributes.bold {
+                // 粗體: 每個點向右多畫一個像素
+                bitmap_row | (bitmap_row << 1)
+            } else {
+                bitmap_row
+            };
+            for col in 0..self.font.width() {
                 let pixel_on = (bitmap_row >> col) & 1;
                 
                 // 繪製放大的像素塊
@@ -227,9 +1426,9 @@ impl Writer {
                         
                         if x < self.info.width && y < self.info.height {
                             let color = if pixel_on == 1 {
-                                self.fg_color
+                                fg
                             } else {
-                                self.bg_color
+                                bg
                             };
                             self.write_pixel(x, y, color);
                         }
@@ -242,13 +1441,26 @@ impl Writer {
     /// 寫入像素
     fn write_pixel(&mut self, x: usize, y: usize, color: Color) {
         let bytes_per_pixel = self.info.bytes_per_pixel;
-        let pixel_offset = (y * self.info.width + x) * bytes_per_pixel;
+        let pixel_offset = (y * self.info.stride + x) * bytes_per_pixel;
         
         if pixel_offset + bytes_per_pixel <= self.buffer.len() {
             self.write_pixel_at_offset(pixel_offset, color);
+            self.mark_dirty(y, y + 1);
         }
     }
 
+    /// 讀回指定偏移處的像素，按 write_pixel_at_offset 的編碼解碼
+    /// 每像素只有 1 字節時把它當作灰度；超出緩衝區時返回 None
+    fn read_pixel_at_offset(&self, offset: usize) -> Option<Color> {
+        let pixel = self.buffer.get(offset..offset + self.info.bytes_per_pixel)?;
+        Some(match *pixel {
+            [b, g, r, ..] => Color { r, g, b },
+            [b, g] => Color { r: 0, g, b },
+            [gray] => Color { r: gray, g: gray, b: gray },
+            [] => Color::BLACK,
+        })
+    }
+
     /// 在指定偏移處寫入像素
     fn write_pixel_at_offset(&mut self, offset: usize, color: Color) {
         let bytes_per_pixel = self.info.bytes_per_pixel;
@@ -269,10 +1481,366 @@ impl Writer {
     }
 }
 
+/// 默認字體放大倍數
+const DEFAULT_SCALE: usize = 2;
+
+/// 不超過 `preferred` 且能在屏幕上放下至少一個字符格的最大放大倍數
+/// 1 倍也放不下時返回 1，此時文字網格為空 (text_rows 或 text_columns 為 0)
+fn fitting_scale(glyph_width: usize, glyph_height: usize, width: usize, height: usize, preferred: usize) -> usize {
+    (1..=preferred.max(1))
+        .rev()
+        .find(|&scale| glyph_width * scale <= width && glyph_height * scale <= height)
+        .unwrap_or(1)
+}
+
+/// 自動換行時緩衝的單詞最大長度，更長的單詞按字符拆到多行
+const WRAP_WORD_MAX: usize = 64;
+
+/// 自動換行模式下屬於單詞、需要緩衝的字符
+fn is_word_char(ch: char) -> bool {
+    !ch.is_whitespace() && !ch.is_control()
+}
+
+/// 從第 column 列開始輸出 len 個字符的單詞前是否要先換行
+/// 已經在行首、或者整行也放不下這個單詞時不換行
+fn wraps_before_word(column: usize, len: usize, columns: usize) -> bool {
+    column > 0 && column + len > columns && len <= columns
+}
+
+/// 分屏模式輸入區的最大行數
+pub const MAX_INPUT_ROWS: usize = 4;
+
+/// 分屏模式分隔線使用的字符
+const SPLIT_SEPARATOR: char = '-';
+
+/// 渲染耗時顯示佔用的列數
+const PERF_OVERLAY_COLUMNS: usize = 20;
+
+/// 默認制表位間隔
+const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// 一段輸出中至少有這麼多個換行時才預先批量滾動
+const SCROLL_BATCH_MIN: usize = 2;
+
+/// 下劃線和豎線光標的粗細 (字符格尺寸的幾分之一，至少 1 像素)
+const CURSOR_THICKNESS_DIVISOR: usize = 8;
+
+/// 光標在字符格內要填充的矩形 (x 偏移, y 偏移, 寬, 高)；塊狀光標反色整個字符格，返回 None
+fn cursor_stroke(shape: CursorShape, char_width: usize, char_height: usize) -> Option<(usize, usize, usize, usize)> {
+    match shape {
+        CursorShape::Block => None,
+        CursorShape::Underline => {
+            let thickness = (char_height / CURSOR_THICKNESS_DIVISOR).max(1);
+            Some((0, char_height - thickness, char_width, thickness))
+        },
+        CursorShape::Bar => Some((0, 0, (char_width / CURSOR_THICKNESS_DIVISOR).max(1), char_height)),
+    }
+}
+
+/// 方框中 (x, y) 處的字符: 四角、上下邊、左右邊或內部的空格
+fn box_border_char(x: usize, y: usize, width: usize, height: usize) -> char {
+    let (left, right) = (x == 0, x + 1 == width);
+    let (top, bottom) = (y == 0, y + 1 == height);
+    match (top, bottom, left, right) {
+        (true, _, true, _) => '┌',
+        (true, _, _, true) => '┐',
+        (_, true, true, _) => '└',
+        (_, true, _, true) => '┘',
+        (true, _, _, _) | (_, true, _, _) => '─',
+        (_, _, true, _) | (_, _, _, true) => '│',
+        _ => ' ',
+    }
+}
+
+/// 按不透明度 `alpha` 把 `color` 混合到 `under` 上
+fn blend(under: Color, color: Color, alpha: u8) -> Color {
+    let alpha = alpha as u16;
+    let mix = |below: u8, above: u8| ((above as u16 * alpha + below as u16 * (255 - alpha) + 127) / 255) as u8;
+    Color { r: mix(under.r, color.r), g: mix(under.g, color.g), b: mix(under.b, color.b) }
+}
+
+/// 暗淡的前景色: 前景色和背景色的中間值
+fn dim_color(fg: Color, bg: Color) -> Color {
+    let mix = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+    Color { r: mix(fg.r, bg.r), g: mix(fg.g, bg.g), b: mix(fg.b, bg.b) }
+}
+
+/// 從 `column` 開始的下一個制表位 (總是前進至少一列)
+pub fn next_tab_stop(column: usize, tab_width: usize) -> usize {
+    (column / tab_width + 1) * tab_width
+}
+
+/// 把字符格座標夾到 rows x columns 的網格內
+fn clamp_cell(row: usize, column: usize, rows: usize, columns: usize) -> (usize, usize) {
+    (row.min(rows.saturating_sub(1)), column.min(columns.saturating_sub(1)))
+}
+
+/// 進度條末尾百分比文字的寬度 ("100%")
+const PROGRESS_LABEL_WIDTH: usize = 4;
+/// 能畫出方框的最小寬度: "[" + 至少一格 + "]" + " " + 百分比
+const PROGRESS_MIN_BAR_WIDTH: usize = 4 + PROGRESS_LABEL_WIDTH;
+
+/// 把進度條文字寫入 `out`，返回寫入的字節數 (最多 `width` 個)
+fn format_progress_bar(width: usize, percent: usize, out: &mut [u8]) -> usize {
+    let percent = percent.min(100);
+    let width = width.min(out.len());
+
+    // 百分比右對齊，例如 "  5%"
+    let mut label = [b' '; PROGRESS_LABEL_WIDTH];
+    label[PROGRESS_LABEL_WIDTH - 1] = b'%';
+    let mut value = percent;
+    let mut digit = PROGRESS_LABEL_WIDTH - 1;
+    loop {
+        digit -= 1;
+        label[digit] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+
+    if width < PROGRESS_MIN_BAR_WIDTH {
+        // 放不下方框，只顯示百分比；連百分比都放不下就不顯示
+        let label = &label[digit..];
+        if width < label.len() {
+            return 0;
+        }
+        out[..label.len()].copy_from_slice(label);
+        return label.len();
+    }
+
+    let inner = width - PROGRESS_MIN_BAR_WIDTH + 1;
+    let filled = inner * percent / 100;
+    out[0] = b'[';
+    out[1..=filled].fill(b'#');
+    out[filled + 1..=inner].fill(b' ');
+    out[inner + 1] = b']';
+    out[inner + 2] = b' ';
+    out[inner + 3..width].copy_from_slice(&label);
+    width
+}
+
 /// 實現 fmt::Write trait，支持格式化輸出
 impl fmt::Write for Writer {
     fn write_str(&mut self, s: &str) -> fmt::Result {
         self.write_string(s);
+        self.present();
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_colors_fall_back_to_white_on_black() {
+        assert_eq!(parse_default_colors("Yellow", " blue"), (Color::YELLOW, Color::BLUE));
+        assert_eq!(parse_default_colors("", ""), (Color::WHITE, Color::BLACK));
+        assert_eq!(parse_default_colors("mauve", "red"), (Color::WHITE, Color::RED));
+        assert_eq!(parse_default_colors("black", ""), (Color::WHITE, Color::BLACK));
+    }
+
+    #[test]
+    fn cursor_shapes_cover_different_parts_of_the_cell() {
+        assert_eq!(cursor_stroke(CursorShape::Block, 16, 32), None);
+        assert_eq!(cursor_stroke(CursorShape::Underline, 16, 32), Some((0, 28, 16, 4)));
+        assert_eq!(cursor_stroke(CursorShape::Bar, 16, 32), Some((0, 0, 2, 32)));
+        // 很小的字符格也至少畫 1 像素
+        assert_eq!(cursor_stroke(CursorShape::Bar, 4, 6), Some((0, 0, 1, 6)));
+        assert_eq!(CursorShape::from_name("underline"), Some(CursorShape::Underline));
+        assert_eq!(CursorShape::from_name("beam"), None);
+    }
+
+    #[test]
+    fn fitting_scale_shrinks_font_on_small_screens() {
+        assert_eq!(fitting_scale(8, 16, 1024, 768, 2), 2);
+        assert_eq!(fitting_scale(8, 16, 16, 31, 2), 1);
+        assert_eq!(fitting_scale(8, 16, 16, 32, 2), 2);
+        assert_eq!(fitting_scale(8, 16, 4, 4, 2), 1);
+        assert_eq!(fitting_scale(8, 16, 1024, 768, 0), 1);
+    }
+
+    /// 用泄漏的全零像素緩衝區創建 width x height (每像素 4 字節) 的 Writer
+    fn test_writer(width: usize, height: usize) -> Writer {
+        use bootloader_api::info::PixelFormat;
+
+        let byte_len = width * height * 4;
+        let info = FrameBufferInfo {
+            byte_len,
+            width,
+            height,
+            pixel_format: PixelFormat::Bgr,
+            bytes_per_pixel: 4,
+            stride: width,
+        };
+        Writer::new(Box::leak(vec![0u8; byte_len].into_boxed_slice()), info)
+    }
+
+    #[test]
+    fn tiny_framebuffer_does_not_panic() {
+        let mut writer = test_writer(4, 4);
+        assert_eq!(writer.scale(), 1);
+        assert_eq!((writer.text_rows(), writer.text_columns()), (0, 0));
+
+        writer.write_string("hello\nworld\n\tx");
+        writer.backspace();
+        writer.write_at(0, 0, "abc");
+        writer.set_cursor_position(5, 5);
+        assert_eq!(writer.cursor_position(), (0, 0));
+        assert!(!writer.enable_split(1));
+        // 沒有任何文字被畫到屏幕上
+        assert!(writer.buffer.iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn flush_renders_buffered_word() {
+        // 8x8 字體、縮放 1 倍: 1 行 4 列
+        let mut writer = test_writer(32, 8);
+        writer.set_word_wrap(true);
+        writer.write_string("hi");
+        // 單詞還在緩衝區裡，沒有畫出來
+        assert_eq!(writer.cursor_position(), (0, 0));
+        assert!(writer.buffer.iter().all(|&byte| byte == 0));
+
+        writer.flush();
+        assert_eq!(writer.cursor_position(), (0, 2));
+        assert!(writer.buffer.iter().any(|&byte| byte != 0));
+    }
+
+    #[test]
+    fn prompt_without_trailing_space_shows_before_waiting() {
+        let mut writer = test_writer(32, 8);
+        writer.set_word_wrap(true);
+        writer.write_string("more");
+        assert!(writer.buffer.iter().all(|&byte| byte == 0));
+
+        writer.prepare_for_input();
+        assert_eq!(writer.cursor_position(), (0, 4));
+        assert!(writer.buffer.iter().any(|&byte| byte != 0));
+    }
+
+    #[test]
+    fn scroll_region_is_clamped_to_the_screen() {
+        // 8x8 字體放大 2 倍: 4 行 2 列
+        let mut writer = test_writer(32, 64);
+        assert_eq!((writer.text_rows(), writer.text_columns()), (4, 2));
+        writer.set_scroll_region(2, 99);
+        assert_eq!(writer.scroll_region(), (2, 3));
+        writer.set_scroll_region(5, 1);
+        assert_eq!(writer.scroll_region(), (1, 1));
+        writer.reset_scroll_region();
+        assert_eq!(writer.scroll_region(), (0, 3));
+    }
+
+    #[test]
+    fn region_scroll_leaves_rows_outside_unchanged() {
+        let mut writer = test_writer(32, 64);
+        writer.write_at(0, 0, "AA");
+        writer.write_at(3, 0, "ZZ");
+        writer.set_scroll_region(1, 2);
+        writer.set_cursor_position(1, 0);
+
+        // 每個字符行 16 像素高
+        let row_bytes = 32 * 16 * 4;
+        let outside = |buffer: &[u8]| (buffer[..row_bytes].to_vec(), buffer[3 * row_bytes..].to_vec());
+        let before = outside(&writer.buffer);
+        assert!(before.0.iter().any(|&byte| byte != 0) && before.1.iter().any(|&byte| byte != 0));
+        writer.write_string("b\nc\nd\ne");
+        assert_eq!(outside(&writer.buffer), before);
+        assert_eq!(writer.cursor_position(), (2, 1));
+        assert!(writer.buffer[row_bytes..3 * row_bytes].iter().any(|&byte| byte != 0));
+    }
+
+    #[test]
+    fn words_wrap_only_when_they_fit_on_a_fresh_line() {
+        assert!(!wraps_before_word(0, 5, 10));
+        assert!(!wraps_before_word(3, 7, 10));
+        assert!(wraps_before_word(3, 8, 10));
+        // 整行都放不下的單詞直接按字符拆開
+        assert!(!wraps_before_word(3, 11, 10));
+    }
+
+    #[test]
+    fn clamp_cell_keeps_positions_inside_grid() {
+        assert_eq!(clamp_cell(0, 0, 25, 80), (0, 0));
+        assert_eq!(clamp_cell(24, 79, 25, 80), (24, 79));
+        assert_eq!(clamp_cell(10, 40, 25, 80), (10, 40));
+    }
+
+    #[test]
+    fn clamp_cell_clamps_out_of_range_positions() {
+        assert_eq!(clamp_cell(25, 80, 25, 80), (24, 79));
+        assert_eq!(clamp_cell(usize::MAX, usize::MAX, 25, 80), (24, 79));
+        assert_eq!(clamp_cell(3, 100, 25, 80), (3, 79));
+    }
+
+    #[test]
+    fn clamp_cell_handles_empty_grid() {
+        assert_eq!(clamp_cell(5, 5, 0, 0), (0, 0));
+    }
+
+    #[test]
+    fn box_border_has_corners_edges_and_blank_interior() {
+        let rows: [[char; 4]; 3] = core::array::from_fn(|y| core::array::from_fn(|x| box_border_char(x, y, 4, 3)));
+        assert_eq!(rows[0], ['┌', '─', '─', '┐']);
+        assert_eq!(rows[1], ['│', ' ', ' ', '│']);
+        assert_eq!(rows[2], ['└', '─', '─', '┘']);
+    }
+
+    #[test]
+    fn blend_mixes_by_alpha() {
+        assert_eq!(blend(Color::WHITE, Color::BLACK, 0), Color::WHITE);
+        assert_eq!(blend(Color::WHITE, Color::BLACK, 255), Color::BLACK);
+        assert_eq!(blend(Color::WHITE, Color::BLACK, 128), Color { r: 127, g: 127, b: 127 });
+        assert_eq!(blend(Color::RED, Color::BLUE, 64), Color { r: 191, g: 0, b: 64 });
+    }
+
+    #[test]
+    fn dim_color_is_halfway_to_background() {
+        assert_eq!(dim_color(Color::WHITE, Color::BLACK), Color { r: 127, g: 127, b: 127 });
+        assert_eq!(dim_color(Color::RED, Color::BLUE), Color { r: 127, g: 0, b: 127 });
+    }
+
+    #[test]
+    fn tab_stops_advance_to_next_multiple() {
+        assert_eq!(next_tab_stop(0, 4), 4);
+        assert_eq!(next_tab_stop(3, 4), 4);
+        assert_eq!(next_tab_stop(4, 4), 8);
+        assert_eq!(next_tab_stop(9, 8), 16);
+        assert_eq!(next_tab_stop(5, 1), 6);
+    }
+
+    fn progress(width: usize, percent: usize) -> ([u8; 64], usize) {
+        let mut out = [0u8; 64];
+        let len = format_progress_bar(width, percent, &mut out);
+        (out, len)
+    }
+
+    #[test]
+    fn progress_bar_fills_proportionally() {
+        let (out, len) = progress(17, 50);
+        assert_eq!(&out[..len], b"[#####     ]  50%");
+        let (out, len) = progress(17, 0);
+        assert_eq!(&out[..len], b"[          ]   0%");
+        let (out, len) = progress(17, 100);
+        assert_eq!(&out[..len], b"[##########] 100%");
+    }
+
+    #[test]
+    fn progress_bar_clamps_percent() {
+        assert_eq!(progress(17, 250), progress(17, 100));
+    }
+
+    #[test]
+    fn progress_bar_falls_back_to_label_when_narrow() {
+        let (out, len) = progress(PROGRESS_MIN_BAR_WIDTH, 7);
+        assert_eq!(&out[..len], b"[ ]   7%");
+        let (out, len) = progress(PROGRESS_MIN_BAR_WIDTH - 1, 7);
+        assert_eq!(&out[..len], b"7%");
+        let (out, len) = progress(3, 100);
+        assert_eq!(&out[..len], b"");
+        let (_, len) = progress(0, 50);
+        assert_eq!(len, 0);
+    }
+}
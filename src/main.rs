@@ -1,17 +1,97 @@
 use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// UEFI 啟動時默認使用的 OVMF 固件路徑 (可用 OVMF_FD 環境變量覆蓋)
+const DEFAULT_OVMF_PATH: &str = "/usr/share/ovmf/OVMF.fd";
+
+/// 默認數據盤的大小 (1 MiB)
+const DATA_DISK_SIZE: usize = 1024 * 1024;
+/// 新建的默認數據盤第一個扇區開頭寫入的標記，內核中用 `readsec 0` 可以看到
+const DATA_DISK_SIGNATURE: &[u8] = b"RUST OS DATA DISK\n";
+
+/// 運行參數
+struct Options {
+    /// 啟動 UEFI 映像而不是 BIOS 映像
+    uefi: bool,
+    /// 作為第二個 IDE 磁盤 (主通道從盤) 附加的原始映像，None 時使用默認數據盤
+    disk: Option<PathBuf>,
+    /// 原樣傳給 QEMU 的參數
+    qemu_args: Vec<String>,
+}
+
+/// 解析命令行參數
+/// `--uefi` 和 `--disk <映像>` 由運行器處理；`--` 之後的參數全部原樣傳給 QEMU，其它未知參數也傳給 QEMU
+/// 例如 `cargo run -- --uefi -- -serial stdio -nographic`
+fn parse_args(args: impl Iterator<Item = String>) -> Options {
+    let mut options = Options {
+        uefi: false,
+        disk: None,
+        qemu_args: Vec::new(),
+    };
+
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--uefi" => options.uefi = true,
+            "--disk" => match args.next() {
+                Some(path) => options.disk = Some(PathBuf::from(path)),
+                None => {
+                    eprintln!("--disk needs an image path");
+                    std::process::exit(2);
+                }
+            },
+            "--" => {
+                options.qemu_args.extend(args.by_ref());
+            }
+            _ => options.qemu_args.push(arg),
+        }
+    }
+    options
+}
+
+/// 默認數據盤放在啟動映像旁邊，不存在時創建 (已存在時保留內容)
+fn default_data_disk(image_path: &Path) -> io::Result<PathBuf> {
+    let path = image_path.with_file_name("data.img");
+    if !path.exists() {
+        let mut contents = vec![0u8; DATA_DISK_SIZE];
+        contents[..DATA_DISK_SIGNATURE.len()].copy_from_slice(DATA_DISK_SIGNATURE);
+        fs::write(&path, contents)?;
+    }
+    Ok(path)
+}
+
 fn main() {
+    let options = parse_args(env::args().skip(1));
+
     // 獲取磁盤映像路徑
-    let bios_path = env!("BIOS_DISK_IMAGE");
-    
+    let image_path = if options.uefi {
+        env!("UEFI_DISK_IMAGE")
+    } else {
+        env!("BIOS_DISK_IMAGE")
+    };
+
     // 使用 QEMU 運行
     let mut cmd = Command::new("qemu-system-x86_64");
-    cmd.arg("-drive").arg(format!("format=raw,file={}", bios_path));
-    
+    cmd.arg("-drive").arg(format!("format=raw,file={}", image_path));
+
+    // 數據盤接在主通道從盤 (index=1)，啟動盤是主盤
+    let disk = match options.disk {
+        Some(disk) => disk,
+        None => default_data_disk(Path::new(image_path)).expect("Failed to create data disk image"),
+    };
+    cmd.arg("-drive").arg(format!("format=raw,if=ide,index=1,file={}", disk.display()));
+    if options.uefi {
+        let ovmf = env::var("OVMF_FD").unwrap_or_else(|_| DEFAULT_OVMF_PATH.to_string());
+        cmd.arg("-bios").arg(ovmf);
+    }
+    cmd.args(&options.qemu_args);
+
     let status = cmd.status().expect("Failed to run QEMU");
-    
+
     if !status.success() {
         std::process::exit(status.code().unwrap_or(1));
     }
-}
\ No newline at end of file
+}